@@ -0,0 +1,101 @@
+//! Benchmarking verification of many CDS94 proofs of the
+//! same statement one at a time against
+//! `SelfCompiler94::verify_batch`, which computes the
+//! Lagrange coefficients once for the whole batch
+use std::collections::HashSet;
+
+use cds_compiler::selfcompiler::{
+    Proof94, SelfCompiler94, Statement94, Witness94,
+};
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use curve25519_dalek::scalar::Scalar;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sigmazk::*;
+
+const PROOFS: usize = 10;
+
+/// `PROOFS` honest proofs of a statement with `n` clauses
+/// of which the first `d` are active
+fn bench_init(
+    n: usize,
+    d: usize,
+) -> (Statement94<Schnorr>, Vec<Proof94<Schnorr>>) {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let witnesses: Vec<Scalar> = (0..n)
+        .map(|_| Scalar::random(&mut rng))
+        .collect();
+    let statement = Statement94::new(
+        n,
+        d,
+        witnesses
+            .iter()
+            .map(|w| Schnorr::init(*w))
+            .collect(),
+    );
+    let active_clauses: HashSet<usize> = (0..d).collect();
+    let witness = Witness94::new(witnesses, active_clauses);
+
+    let proofs = (0..PROOFS)
+        .map(|_| {
+            let (state, a) = SelfCompiler94::first(
+                &statement, &witness, &mut rng,
+            );
+            let c =
+                SelfCompiler94::<Schnorr>::second(&mut rng);
+            let z = SelfCompiler94::third(
+                &statement, state, &witness, &c, &mut rng,
+            );
+            (a, c, z)
+        })
+        .collect();
+
+    (statement, proofs)
+}
+
+fn cds_batch_benchmark(c: &mut Criterion) {
+    let mut group =
+        c.benchmark_group("cds_batch_benchmark");
+    group.sample_size(10);
+
+    for n in [16usize, 64, 256] {
+        let (statement, proofs) = bench_init(n, n / 2);
+
+        // Coefficients recomputed for every proof
+        group.bench_with_input(
+            BenchmarkId::new("verify", n),
+            &(&statement, &proofs),
+            |b, (statement, proofs)| {
+                b.iter(|| {
+                    proofs
+                        .iter()
+                        .map(|(a, c, z)| {
+                            SelfCompiler94::verify(
+                                statement, a, c, z,
+                            )
+                        })
+                        .collect::<Vec<bool>>()
+                })
+            },
+        );
+
+        // Coefficients computed once
+        group.bench_with_input(
+            BenchmarkId::new("verify_batch", n),
+            &(&statement, &proofs),
+            |b, (statement, proofs)| {
+                b.iter(|| {
+                    SelfCompiler94::verify_batch(
+                        statement, proofs,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cds_batch_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! Benchmarking `SelfCompiler94::verify`, which decompresses
+//! each first message while checking its clause, against
+//! `SelfCompiler94::verify_decompressed`, which decompresses
+//! all of them up front, for an honest proof and for one
+//! whose last first message is malformed
+use std::collections::HashSet;
+
+use cds_compiler::selfcompiler::{
+    Proof94, SelfCompiler94, Statement94, Witness94,
+};
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sigmazk::*;
+
+/// An honest proof of a statement with `n` clauses of which
+/// the first `d` are active
+fn bench_init(
+    n: usize,
+    d: usize,
+) -> (Statement94<Schnorr>, Proof94<Schnorr>) {
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+    let witnesses: Vec<Scalar> = (0..n)
+        .map(|_| Scalar::random(&mut rng))
+        .collect();
+    let statement = Statement94::new(
+        n,
+        d,
+        witnesses
+            .iter()
+            .map(|w| Schnorr::init(*w))
+            .collect(),
+    );
+    let active_clauses: HashSet<usize> = (0..d).collect();
+    let witness = Witness94::new(witnesses, active_clauses);
+
+    let (state, a) = SelfCompiler94::first(
+        &statement, &witness, &mut rng,
+    );
+    let c = SelfCompiler94::<Schnorr>::second(&mut rng);
+    let z = SelfCompiler94::third(
+        &statement, state, &witness, &c, &mut rng,
+    );
+    (statement, (a, c, z))
+}
+
+fn cds_decompress_benchmark(c: &mut Criterion) {
+    let mut group =
+        c.benchmark_group("cds_decompress_benchmark");
+    group.sample_size(10);
+
+    const N: usize = 64;
+    let (statement, honest) = bench_init(N, N / 2);
+    let mut malformed = honest.clone();
+    malformed.0[N - 1] = CompressedRistretto([0xff; 32]);
+
+    for (name, proof) in
+        [("honest", &honest), ("malformed", &malformed)]
+    {
+        group.bench_with_input(
+            BenchmarkId::new("verify", name),
+            &(&statement, proof),
+            |b, (statement, (a, c, z))| {
+                b.iter(|| {
+                    SelfCompiler94::verify(
+                        statement, a, c, z,
+                    )
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("verify_decompressed", name),
+            &(&statement, proof),
+            |b, (statement, (a, c, z))| {
+                b.iter(|| {
+                    SelfCompiler94::<Schnorr>::
+                        verify_decompressed(
+                            statement, a, c, z,
+                        )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cds_decompress_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,187 @@
+//! Head-to-head comparison of the CDS94 compiler and
+//! self-stacking for 1-out-of-n disjunctions of Schnorr
+//! proofs
+use benchmarks::{
+    cds94_instance, measure_cds94, measure_stacking,
+    plot_dir, plot_proofsize, plot_timings,
+    stacking_instance,
+};
+use cds_compiler::selfcompiler::SelfCompiler94;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId,
+    Criterion, Throughput,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sigmazk::{Schnorr, SigmaProtocol};
+use stacksig_compiler::stackers::SelfStacker;
+
+/// Clause counts 4, 8, ..., 1024. Stacking starts at 4
+/// clauses, the smallest partially-binding commitment.
+const MIN_LOG_CLAUSES: u32 = 2;
+const MAX_LOG_CLAUSES: u32 = 10;
+/// Proofs averaged over for the plotted timings
+const RUNS: u32 = 10;
+
+pub fn disjunction_comparison(c: &mut Criterion) {
+    let ns: Vec<usize> = (MIN_LOG_CLAUSES
+        ..=MAX_LOG_CLAUSES)
+        .map(|i| 1 << i)
+        .collect();
+
+    let mut group =
+        c.benchmark_group("disjunction_comparison");
+    group.sample_size(10);
+    for n in ns.iter() {
+        group.throughput(Throughput::Elements(*n as u64));
+
+        let (statement, witness) = cds94_instance(*n);
+        let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+        group.bench_with_input(
+            BenchmarkId::new("cds94_prover", n),
+            n,
+            |b, _| {
+                b.iter(|| {
+                    let (state, a) = SelfCompiler94::first(
+                        &statement, &witness, rng,
+                    );
+                    let c =
+                        SelfCompiler94::<Schnorr>::second(
+                            rng,
+                        );
+                    let z = SelfCompiler94::third(
+                        &statement, state, &witness, &c,
+                        rng,
+                    );
+                    (a, c, z)
+                });
+            },
+        );
+        let (state, a) = SelfCompiler94::first(
+            &statement, &witness, rng,
+        );
+        let challenge =
+            SelfCompiler94::<Schnorr>::second(rng);
+        let z = SelfCompiler94::third(
+            &statement, state, &witness, &challenge, rng,
+        );
+        group.bench_with_input(
+            BenchmarkId::new("cds94_verifier", n),
+            n,
+            |b, _| {
+                b.iter(|| {
+                    SelfCompiler94::verify(
+                        &statement, &a, &challenge, &z,
+                    )
+                });
+            },
+        );
+
+        let (statement, witness) = stacking_instance(*n);
+        group.bench_with_input(
+            BenchmarkId::new("stacking_prover", n),
+            n,
+            |b, _| {
+                b.iter(|| {
+                    let (state, a) = SelfStacker::first(
+                        &statement, &witness, rng,
+                    );
+                    let c =
+                        SelfStacker::<Schnorr>::second(rng);
+                    let z = SelfStacker::third(
+                        &statement, state, &witness, &c,
+                        rng,
+                    );
+                    (a, c, z)
+                });
+            },
+        );
+        let (state, a) =
+            SelfStacker::first(&statement, &witness, rng);
+        let challenge = SelfStacker::<Schnorr>::second(rng);
+        let z = SelfStacker::third(
+            &statement, state, &witness, &challenge, rng,
+        );
+        group.bench_with_input(
+            BenchmarkId::new("stacking_verifier", n),
+            n,
+            |b, _| {
+                b.iter(|| {
+                    SelfStacker::verify(
+                        &statement, &a, &challenge, &z,
+                    )
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let cds94: Vec<_> = ns
+        .iter()
+        .map(|n| measure_cds94(*n, RUNS))
+        .collect();
+    let stacking: Vec<_> = ns
+        .iter()
+        .map(|n| measure_stacking(*n, RUNS))
+        .collect();
+
+    let prefix =
+        format!("{}proofsize_plots/comparison/", plot_dir);
+    std::fs::create_dir_all(&prefix).unwrap();
+    plot_proofsize(
+        ns.clone(),
+        cds94
+            .iter()
+            .map(|m| m.proof_size)
+            .collect(),
+        "CDS94".into(),
+        format!("{}cds94_proofsize{}", prefix, ns.len()),
+    );
+    plot_proofsize(
+        ns.clone(),
+        stacking
+            .iter()
+            .map(|m| m.proof_size)
+            .collect(),
+        "Stacking Sigmas".into(),
+        format!("{}stacking_proofsize{}", prefix, ns.len()),
+    );
+    plot_timings(
+        ns.clone(),
+        vec![
+            (
+                "CDS94 prover".into(),
+                cds94
+                    .iter()
+                    .map(|m| m.prove_time)
+                    .collect(),
+            ),
+            (
+                "CDS94 verifier".into(),
+                cds94
+                    .iter()
+                    .map(|m| m.verify_time)
+                    .collect(),
+            ),
+            (
+                "Stacking prover".into(),
+                stacking
+                    .iter()
+                    .map(|m| m.prove_time)
+                    .collect(),
+            ),
+            (
+                "Stacking verifier".into(),
+                stacking
+                    .iter()
+                    .map(|m| m.verify_time)
+                    .collect(),
+            ),
+        ],
+        "CDS94 vs Stacking Sigmas: running time".into(),
+        format!("{}timings{}", prefix, ns.len()),
+    );
+}
+
+criterion_group!(benches, disjunction_comparison);
+criterion_main!(benches);
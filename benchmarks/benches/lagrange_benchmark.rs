@@ -0,0 +1,59 @@
+//! Benchmarking Lagrange interpolation at zero (Shamir
+//! reconstruction) with one inversion per point against a
+//! single batched inversion
+use cds_compiler::shamir_ss::lagrange::LagrangePolynomial;
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use group::ff::Field;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use wrapped_ristretto::scalar::WrappedScalar;
+
+fn lagrange_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lagrange_benchmark");
+    let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+
+    for n in [32usize, 128, 512] {
+        let xs: Vec<WrappedScalar> = (1..=n as u64)
+            .map(WrappedScalar::from)
+            .collect();
+        let ys: Vec<WrappedScalar> = (0..n)
+            .map(|_| WrappedScalar::random(&mut rng))
+            .collect();
+
+        // n field inversions
+        group.bench_with_input(
+            BenchmarkId::new("naive", n),
+            &(&xs, &ys),
+            |b, (xs, ys)| {
+                b.iter(|| {
+                    LagrangePolynomial::lagrange_interpolation(
+                        xs,
+                        ys,
+                        WrappedScalar::ZERO,
+                    )
+                })
+            },
+        );
+
+        // 1 field inversion
+        group.bench_with_input(
+            BenchmarkId::new("batched", n),
+            &(&xs, &ys),
+            |b, (xs, ys)| {
+                b.iter(|| {
+                    LagrangePolynomial::lagrange_interpolation_batched(
+                        xs,
+                        ys,
+                        WrappedScalar::ZERO,
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lagrange_benchmark);
+criterion_main!(benches);
@@ -140,7 +140,8 @@ macro_rules! bench_scheme {
                 black_box(&sk),
                 black_box(&pk[0]),
                 &[],
-            );
+            )
+            .unwrap();
         });
     }};
 }
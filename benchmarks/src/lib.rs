@@ -1,9 +1,19 @@
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
 
+use cds_compiler::selfcompiler::{
+    SelfCompiler94, Statement94, Witness94,
+};
+use curve25519_dalek::Scalar;
 use plotly::common::{Line, LineShape, Mode, Title};
 use plotly::layout::{Axis, AxisType};
 use plotly::{Layout, Plot, Scatter};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sigmazk::{Schnorr, SigmaProtocol};
+use stacksig_compiler::stackers::*;
 
 pub const plot_dir: &str = "../plots/";
 
@@ -19,12 +29,16 @@ pub fn plot_proofsize(
         .mode(Mode::LinesMarkers)
         .line(Line::new().shape(LineShape::Spline));
 
-    let title = format!("{}: Communication size growth", protocol_name);
+    let title = format!(
+        "{}: Communication size growth",
+        protocol_name
+    );
     // Set x-axis to log scale
     let x_axis = Axis::new()
         .type_(AxisType::Log)
         .title(Title::new("Number of clauses"));
-    let y_axis = Axis::new().title(Title::new("Communication size (in bytes)"));
+    let y_axis = Axis::new()
+        .title(Title::new("Communication size (in bytes)"));
     // Set layout
     let layout = Layout::new()
         .title(Title::new(&title))
@@ -39,7 +53,225 @@ pub fn plot_proofsize(
     plot.show();
 }
 
-fn filename(prefix: String, proof_sizes: &Vec<usize>) -> String {
+/// Size in bytes of an encoded non-interactive
+/// `SelfStacker<Schnorr>` proof for `clauses` clauses, for
+/// use with [`plot_proofsize`]
+pub fn stacking_proof_size(clauses: usize) -> usize {
+    let (statement, witness) = stacking_instance(clauses);
+    let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+    let (message_a, message_z) =
+        SelfStacker::prove_ni(&statement, &witness, rng)
+            .unwrap();
+    SelfStacker::encode_proof(&message_a, &message_z)
+        .unwrap()
+        .len()
+}
+
+/// Plot running times in milliseconds against the number of
+/// clauses, one line per named series, e.g. to compare the
+/// prover and verifier of two protocols
+pub fn plot_timings(
+    clauses: Vec<usize>,
+    timings: Vec<(String, Vec<Duration>)>,
+    title: String,
+    filename_prefix: String,
+) {
+    let nanos: Vec<usize> = timings
+        .iter()
+        .flat_map(|(_, times)| times.iter())
+        .map(|time| time.as_nanos() as usize)
+        .collect();
+    let filename = filename(filename_prefix, &nanos);
+
+    let x_axis = Axis::new()
+        .type_(AxisType::Log)
+        .title(Title::new("Number of clauses"));
+    let y_axis =
+        Axis::new().title(Title::new("Time (in ms)"));
+    let layout = Layout::new()
+        .title(Title::new(&title))
+        .x_axis(x_axis)
+        .y_axis(y_axis);
+    let mut plot = Plot::new();
+    for (name, times) in timings {
+        let millis: Vec<f64> = times
+            .iter()
+            .map(|time| time.as_secs_f64() * 1000.0)
+            .collect();
+        let trace = Scatter::new(clauses.clone(), millis)
+            .name(&name)
+            .mode(Mode::LinesMarkers)
+            .line(Line::new().shape(LineShape::Spline));
+        plot.add_trace(trace);
+    }
+    plot.set_layout(layout);
+    plot.use_local_plotly();
+    plot.write_html(filename);
+    plot.show();
+}
+
+/// Statement and witness of a 1-out-of-`clauses` disjunction
+/// of Schnorr proofs for `SelfCompiler94`, knowing the
+/// secret of clause 0
+pub fn cds94_instance(
+    clauses: usize,
+) -> (Statement94<Schnorr>, Witness94<Schnorr>) {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let witnesses: Vec<Scalar> = (0..clauses)
+        .map(|_| Scalar::random(rng))
+        .collect();
+    let statements = witnesses
+        .iter()
+        .map(|w| Schnorr::init(*w))
+        .collect();
+    let statement =
+        Statement94::new(clauses, 1, statements);
+    let witness =
+        Witness94::new(witnesses, HashSet::from([0]));
+    (statement, witness)
+}
+
+/// Statement and witness of a 1-out-of-`clauses` disjunction
+/// of Schnorr proofs for `SelfStacker`, knowing the secret of
+/// clause 0. Panics for fewer than 4 clauses, which the
+/// partially-binding commitment does not support.
+pub fn stacking_instance(
+    clauses: usize,
+) -> (StackedStatement<Schnorr>, StackedWitness<Scalar>) {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let witness = Scalar::random(rng);
+    let base = Schnorr::init(witness);
+    let stacker = SelfStacker::new(clauses, base);
+    let pp = stacker.setup(rng);
+    let stacker =
+        SelfStacker::new_with_params(clauses, base, pp);
+
+    let binding_index = BindingIndex::new(stacker.q(), 0);
+    let mut statements =
+        vec![
+            Schnorr::init(Scalar::random(rng));
+            stacker.clauses()
+        ];
+    statements[binding_index.index()] = base;
+    let statement = stacker
+        .statement(statements)
+        .unwrap();
+    (
+        statement,
+        StackedWitness::init(witness, binding_index),
+    )
+}
+
+/// Proof size and average running times of one disjunction
+/// protocol for a number of clauses
+#[derive(Clone, Copy, Debug)]
+pub struct DisjunctionMeasurement {
+    pub clauses: usize,
+    /// Size in bytes of the first message, the challenge and
+    /// the response
+    pub proof_size: usize,
+    pub prove_time: Duration,
+    pub verify_time: Duration,
+}
+
+/// Measure `SelfCompiler94<Schnorr>` on
+/// [`cds94_instance`], averaging the times over `runs`
+/// proofs
+pub fn measure_cds94(
+    clauses: usize,
+    runs: u32,
+) -> DisjunctionMeasurement {
+    use sigmazk::message::Message;
+
+    let (statement, witness) = cds94_instance(clauses);
+    let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+    let ((a, c, z), prove_time, verify_time) = time_runs(
+        runs,
+        || {
+            let (state, a) = SelfCompiler94::first(
+                &statement, &witness, rng,
+            );
+            let c = SelfCompiler94::<Schnorr>::second(rng);
+            let z = SelfCompiler94::third(
+                &statement, state, &witness, &c, rng,
+            );
+            (a, c, z)
+        },
+        |(a, c, z)| {
+            SelfCompiler94::verify(&statement, a, c, z)
+        },
+    );
+    DisjunctionMeasurement {
+        clauses,
+        proof_size: a.size() + c.size() + z.size(),
+        prove_time,
+        verify_time,
+    }
+}
+
+/// Measure `SelfStacker<Schnorr>` on [`stacking_instance`],
+/// averaging the times over `runs` proofs
+pub fn measure_stacking(
+    clauses: usize,
+    runs: u32,
+) -> DisjunctionMeasurement {
+    use stacksig_compiler::stackable::Message;
+
+    let (statement, witness) = stacking_instance(clauses);
+    let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+    let ((a, c, z), prove_time, verify_time) = time_runs(
+        runs,
+        || {
+            let (state, a) = SelfStacker::first(
+                &statement, &witness, rng,
+            );
+            let c = SelfStacker::<Schnorr>::second(rng);
+            let z = SelfStacker::third(
+                &statement, state, &witness, &c, rng,
+            );
+            (a, c, z)
+        },
+        |(a, c, z)| {
+            SelfStacker::verify(&statement, a, c, z)
+        },
+    );
+    DisjunctionMeasurement {
+        clauses,
+        proof_size: a.size() + c.size() + z.size(),
+        prove_time,
+        verify_time,
+    }
+}
+
+/// Run `prove` and `verify` on its proof `runs` times,
+/// returning the last proof and the average time of each.
+/// Panics if a proof does not verify.
+fn time_runs<P>(
+    runs: u32,
+    mut prove: impl FnMut() -> P,
+    verify: impl Fn(&P) -> bool,
+) -> (P, Duration, Duration) {
+    assert!(runs > 0);
+    let (mut prove_time, mut verify_time) =
+        (Duration::ZERO, Duration::ZERO);
+    let mut proof = None;
+    for _ in 0..runs {
+        let start = Instant::now();
+        let p = prove();
+        prove_time += start.elapsed();
+
+        let start = Instant::now();
+        assert!(verify(&p), "proof does not verify");
+        verify_time += start.elapsed();
+        proof = Some(p);
+    }
+    (proof.unwrap(), prove_time / runs, verify_time / runs)
+}
+
+fn filename(
+    prefix: String,
+    proof_sizes: &Vec<usize>,
+) -> String {
     let mut filename = prefix;
     let mut s = DefaultHasher::new();
     proof_sizes.hash(&mut s);
@@ -50,3 +282,25 @@ fn filename(prefix: String, proof_sizes: &Vec<usize>) -> String {
     filename.push_str(".html");
     filename
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disjunction_measurements_are_positive() {
+        for clauses in [4, 8] {
+            for m in [
+                measure_cds94(clauses, 2),
+                measure_stacking(clauses, 2),
+            ] {
+                assert_eq!(m.clauses, clauses);
+                assert!(m.proof_size > 0);
+                for time in [m.prove_time, m.verify_time] {
+                    let secs = time.as_secs_f64();
+                    assert!(secs.is_finite() && secs > 0.0);
+                }
+            }
+        }
+    }
+}
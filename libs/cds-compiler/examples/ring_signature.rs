@@ -0,0 +1,44 @@
+//! Sign a message on behalf of a ring of 16 public keys
+//! without revealing which member signed it.
+//!
+//! Run with `cargo run -p cds-compiler --example
+//! ring_signature`.
+use cds_compiler::ringsig::RingSignature;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::Scalar;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+fn main() {
+    let rng = &mut ChaCha20Rng::from_entropy();
+
+    // Every member of the ring has a key pair
+    let secrets: Vec<Scalar> = (0..16)
+        .map(|_| Scalar::random(rng))
+        .collect();
+    let ring: Vec<RistrettoPoint> = secrets
+        .iter()
+        .map(|x| x * RISTRETTO_BASEPOINT_TABLE)
+        .collect();
+
+    // Member 11 signs
+    let msg = b"meet at noon";
+    let sig = RingSignature::sign(
+        &secrets[11],
+        &ring,
+        11,
+        msg,
+        rng,
+    )
+    .expect("no public key in the ring is the identity");
+
+    println!(
+        "signature valid: {}",
+        RingSignature::verify(&ring, msg, &sig)
+    );
+    println!(
+        "signature valid for another message: {}",
+        RingSignature::verify(&ring, b"meet at one", &sig)
+    );
+}
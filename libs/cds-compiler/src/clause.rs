@@ -0,0 +1,83 @@
+//! Typed clause indices
+
+use std::fmt;
+use std::io::Write;
+
+use group::ff::PrimeField;
+use sigmazk::error::Error;
+use sigmazk::message::Message;
+
+use crate::shareable::Shareable;
+
+/// 0-indexed position of a clause in a compiled statement.
+///
+/// Clause vectors are 0-indexed while Shamir x-coordinates
+/// start at 1 (the secret sits at x = 0). All conversions
+/// between the two go through [`ClauseId::to_share_x`] and
+/// [`ClauseId::from_share_x`].
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ClauseId(pub usize);
+
+impl ClauseId {
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    /// x-coordinate of the challenge share of this clause,
+    /// i.e. `index + 1`
+    pub fn to_share_x<F: PrimeField>(self) -> F {
+        F::from((self.0 as u64) + 1)
+    }
+
+    /// Clause whose challenge share sits at x-coordinate
+    /// `x`, the inverse of [`ClauseId::to_share_x`]. Fails
+    /// with [`Error::InvalidIndex`] if `x` is 0 or does not
+    /// fit a `usize`.
+    pub fn from_share_x<S: Shareable>(
+        x: S::F,
+    ) -> Result<Self, Error> {
+        let id = S::to_usize(x)
+            .checked_sub(1)
+            .map(ClauseId)
+            .ok_or(Error::InvalidIndex)?;
+        if id.to_share_x::<S::F>() != x {
+            return Err(Error::InvalidIndex);
+        }
+        Ok(id)
+    }
+}
+
+impl fmt::Display for ClauseId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "clause {}", self.0)
+    }
+}
+
+impl From<usize> for ClauseId {
+    fn from(index: usize) -> Self {
+        ClauseId(index)
+    }
+}
+
+impl Message for ClauseId {
+    fn write<W: Write>(&self, writer: &mut W) {
+        self.0
+            .write(writer);
+    }
+}
@@ -0,0 +1,213 @@
+//! Builder for composed statements.
+//!
+//! Thresholds, disjunctions and conjunctions over clauses
+//! of a [`Composable`] protocol `S` are all statements of
+//! [`SelfCompiler94<S>`]: a disjunction is a threshold of 1
+//! and a conjunction a threshold of every clause. Since
+//! `SelfCompiler94<S>` is itself `Composable`, composed
+//! statements nest, e.g.
+//! `Compose::or(vec![Compose::and(..), Compose::and(..)])`.
+//!
+//! The builder lives here rather than in `sigmazk`, which
+//! every compiler depends on and so cannot name them, and
+//! there is no separate AND compiler: with a threshold of
+//! every clause the sharing polynomial is the constant
+//! challenge, and [`SigmaProtocol::verify`] checks that
+//! every clause answers it.
+use crate::selfcompiler::{SelfCompiler94, Statement94};
+use crate::*;
+
+/// Statement of the protocol composing the clauses of `S`
+pub type Composed<S> = Statement94<S>;
+
+/// Protocol proving a [`Composed`] statement
+pub type ComposedProtocol<S> = SelfCompiler94<S>;
+
+pub struct Compose;
+
+impl Compose {
+    /// The prover knows the witness of at least one of
+    /// `statements`
+    pub fn or<S: Composable>(
+        statements: Vec<S::Statement>,
+    ) -> Composed<S> {
+        Self::threshold(1, statements)
+    }
+
+    /// The prover knows the witness of every one of
+    /// `statements`
+    pub fn and<S: Composable>(
+        statements: Vec<S::Statement>,
+    ) -> Composed<S> {
+        Self::threshold(statements.len(), statements)
+    }
+
+    /// The prover knows the witnesses of at least `k` of
+    /// `statements`.
+    ///
+    /// Panics unless `1 <= k <= statements.len()`.
+    pub fn threshold<S: Composable>(
+        k: usize,
+        statements: Vec<S::Statement>,
+    ) -> Composed<S> {
+        Statement94::new(statements.len(), k, statements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use curve25519_dalek::Scalar;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+    use sigmazk::error::Error;
+
+    use super::*;
+    use crate::selfcompiler::Witness94;
+
+    fn prove_and_verify<S: Composable>(
+        statement: &Composed<S>,
+        witness: &Witness94<S>,
+        rng: &mut ChaCha20Rng,
+    ) -> bool {
+        let (state, a) = ComposedProtocol::first(
+            statement, witness, rng,
+        );
+        let c = ComposedProtocol::<S>::second(rng);
+        let z = ComposedProtocol::third(
+            statement, state, witness, &c, rng,
+        );
+        ComposedProtocol::verify(statement, &a, &c, &z)
+    }
+
+    #[test]
+    fn threshold_two_of_three() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let secrets: Vec<Scalar> = (0..3)
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let statement = Compose::threshold::<Schnorr>(
+            2,
+            secrets
+                .iter()
+                .map(|x| Schnorr::init(*x))
+                .collect(),
+        );
+        assert_eq!(statement.clauses(), 3);
+        assert_eq!(statement.threshold(), 2);
+
+        // Witnesses of clauses 0 and 2 are known
+        let mut witnesses = secrets.clone();
+        witnesses[1] = Scalar::random(rng);
+        let witness = Witness94::new(
+            witnesses,
+            HashSet::from([0, 2]),
+        );
+        assert!(prove_and_verify(
+            &statement, &witness, rng
+        ));
+
+        // One known witness is not enough
+        let mut witnesses = secrets.clone();
+        witnesses[0] = Scalar::random(rng);
+        witnesses[1] = Scalar::random(rng);
+        let witness = Witness94::new(
+            witnesses,
+            HashSet::from([0, 2]),
+        );
+        assert!(!prove_and_verify(
+            &statement, &witness, rng
+        ));
+    }
+
+    #[test]
+    fn and_needs_every_witness() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let secrets: Vec<Scalar> = (0..2)
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let statement = Compose::and::<Schnorr>(
+            secrets
+                .iter()
+                .map(|x| Schnorr::init(*x))
+                .collect(),
+        );
+
+        // Only clause 0 is proven: clause 1 would need a
+        // challenge of its own, which a conjunction does not
+        // leave room for
+        let witness = Witness94::new(
+            vec![secrets[0], Scalar::ZERO],
+            HashSet::from([0]),
+        );
+        let (state, _) = ComposedProtocol::first(
+            &statement, &witness, rng,
+        );
+        let c = ComposedProtocol::<Schnorr>::second(rng);
+        assert!(matches!(
+            ComposedProtocol::try_third(
+                &statement, state, &witness, &c, rng,
+            ),
+            Err(Error::SecretSharing)
+        ));
+
+        // Claiming clause 1 without its witness is caught by
+        // the verifier
+        let witness = Witness94::new(
+            vec![secrets[0], Scalar::random(rng)],
+            HashSet::from([0, 1]),
+        );
+        let (state, a) = ComposedProtocol::first(
+            &statement, &witness, rng,
+        );
+        let c = ComposedProtocol::<Schnorr>::second(rng);
+        let z = ComposedProtocol::third(
+            &statement, state, &witness, &c, rng,
+        );
+        assert!(!ComposedProtocol::verify(
+            &statement, &a, &c, &z
+        ));
+        assert_eq!(
+            ComposedProtocol::verify_batch(
+                &statement,
+                &[(a, c, z)]
+            ),
+            vec![false]
+        );
+    }
+
+    #[test]
+    fn or_of_ands() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let secrets: Vec<Scalar> = (0..4)
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let schnorr = |i: usize| Schnorr::init(secrets[i]);
+
+        // (s0 AND s1) OR (s2 AND s3)
+        let statement =
+            Compose::or::<SelfCompiler94<Schnorr>>(vec![
+                Compose::and(vec![schnorr(0), schnorr(1)]),
+                Compose::and(vec![schnorr(2), schnorr(3)]),
+            ]);
+
+        // Only the second conjunction is known
+        let unknown = Witness94::new(
+            vec![Scalar::ZERO; 2],
+            HashSet::from([0, 1]),
+        );
+        let known = Witness94::new(
+            vec![secrets[2], secrets[3]],
+            HashSet::from([0, 1]),
+        );
+        let witness =
+            Witness94::<SelfCompiler94<Schnorr>>::new(
+                vec![unknown, known],
+                HashSet::from([1]),
+            );
+        assert!(prove_and_verify(
+            &statement, &witness, rng
+        ));
+    }
+}
@@ -3,17 +3,23 @@
 
 pub extern crate shamir_ss;
 pub extern crate sigmazk;
+pub mod clause;
+pub mod compose;
+pub mod rangeproof;
+pub mod ringsig;
 pub mod selfcompiler;
 pub mod shareable;
 #[cfg(test)]
 mod tests;
 
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::Scalar;
 use itertools::Itertools;
 use rand_core::CryptoRngCore;
 use shamir_ss::shamir::{ShamirSecretSharing, Share};
 use shareable::Shareable;
 use sigmazk::message::Message;
-use sigmazk::{HVzk, Schnorr, SigmaProtocol};
+use sigmazk::{EHVzk, HVzk, Schnorr, SigmaProtocol};
 use std::fmt::{self, Debug};
 
 pub trait Composable:
@@ -26,6 +32,44 @@ pub trait Composable:
     + Debug
     + Default
 {
+    /// Simulated first and third messages of a transcript
+    /// answering `challenge`, distributed like those of an
+    /// honest proof with that challenge.
+    ///
+    /// Only needed to simulate a [`SelfCompiler94`] over
+    /// `Self`, e.g. when it is nested in another threshold.
+    /// [`HVzk`] alone cannot fix the challenge, so the
+    /// default panics; protocols with an [`EHVzk`]
+    /// simulator should override it.
+    ///
+    /// [`SelfCompiler94`]: selfcompiler::SelfCompiler94
+    fn simulate_with_challenge(
+        _statement: &Self::Statement,
+        _challenge: &Self::Challenge,
+    ) -> (Self::MessageA, Self::MessageZ) {
+        unimplemented!(
+            "simulating a transcript for a given challenge \
+             requires overriding \
+             Composable::simulate_with_challenge"
+        )
+    }
 }
 
-impl Composable for Schnorr {}
+impl Composable for Schnorr {
+    /// The response of a Schnorr transcript is uniform
+    /// whatever its challenge, so the response of a
+    /// simulated transcript answers `challenge` too
+    fn simulate_with_challenge(
+        statement: &Schnorr,
+        challenge: &Scalar,
+    ) -> (CompressedRistretto, Scalar) {
+        let (_, _, z) =
+            <Schnorr as HVzk>::simulate(statement);
+        (
+            <Schnorr as EHVzk>::simulate(
+                statement, challenge, &z,
+            ),
+            z,
+        )
+    }
+}
@@ -0,0 +1,290 @@
+//! Range proof for a Pedersen commitment from a bit
+//! decomposition of the committed value.
+//!
+//! For a commitment `C = v·H + r·G` the prover commits to
+//! each bit `b_i` of `v` as `C_i = b_i·H + r_i·G` with
+//! `Σ 2^i·r_i = r`, and proves with the CDS94 compiler that
+//! every `C_i` opens to 0 OR to 1, i.e. that it knows the
+//! discrete log of `C_i` OR of `C_i - H`. The verifier
+//! checks the OR proofs and that `Σ 2^i·C_i == C`.
+//!
+//! The challenges are derived with Fiat-Shamir so that the
+//! proof is non-interactive.
+use std::collections::HashSet;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{
+    CompressedRistretto, RistrettoPoint,
+};
+use curve25519_dalek::Scalar;
+use sha2::{Digest, Sha512};
+
+use crate::selfcompiler::{
+    CompiledZ94, SelfCompiler94, Statement94, Witness94,
+};
+use crate::*;
+
+/// Maximum number of bits that can be proven for a `u64`
+/// value
+pub const MAX_BITS: usize = 64;
+
+/// Generator `H` for the value of a commitment, with no
+/// known discrete log with respect to the basepoint `G`
+pub fn generator_h() -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(
+        b"cds-compiler rangeproof generator H",
+    )
+}
+
+/// Pedersen commitment `v·H + r·G`
+pub fn commit(
+    value: u64,
+    blinding: &Scalar,
+) -> RistrettoPoint {
+    Scalar::from(value) * generator_h()
+        + blinding * RISTRETTO_BASEPOINT_TABLE
+}
+
+/// Proof that a single bit commitment opens to 0 or 1
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct BitProof {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "sigmazk::serde_helpers::ristretto_point"
+        )
+    )]
+    commitment: RistrettoPoint,
+    a: Vec<CompressedRistretto>,
+    z: Vec<CompiledZ94<Schnorr>>,
+}
+
+impl BitProof {
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn commitment(&self) -> &RistrettoPoint {
+        &self.commitment
+    }
+
+    /// The disjunction "C_i = r_i·G OR C_i - H = r_i·G"
+    fn statement(
+        commitment: &RistrettoPoint,
+    ) -> Statement94<Schnorr> {
+        Statement94::new(
+            2,
+            1,
+            vec![
+                Schnorr {
+                    pub_key: *commitment,
+                },
+                Schnorr {
+                    pub_key: commitment - generator_h(),
+                },
+            ],
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RangeProof {
+    bits: Vec<BitProof>,
+}
+
+impl RangeProof {
+    /// Prove that `commit(value, blinding)` commits to a
+    /// value in `[0, 2^bits)`.
+    ///
+    /// If `value` is out of range the bit decomposition
+    /// does not add up to the commitment and the resulting
+    /// proof is rejected by [`RangeProof::verify`].
+    pub fn prove<R: CryptoRngCore + Clone>(
+        bits: usize,
+        value: u64,
+        blinding: &Scalar,
+        prover_rng: &mut R,
+    ) -> Self {
+        assert!(0 < bits && bits <= MAX_BITS);
+        let commitment = commit(value, blinding);
+        let two = Scalar::from(2u64);
+
+        // Blinding factors of the bits with Σ 2^i·r_i = r
+        let mut blindings: Vec<Scalar> = (0..bits - 1)
+            .map(|_| Scalar::random(prover_rng))
+            .collect();
+        let mut power = Scalar::ONE;
+        let mut remaining = *blinding;
+        for r in &blindings {
+            remaining -= power * r;
+            power *= two;
+        }
+        blindings.push(remaining * power.invert());
+
+        let bits = blindings
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let bit = ((value >> i) & 1) as usize;
+                let bit_commitment = commit(bit as u64, &r);
+                let statement =
+                    BitProof::statement(&bit_commitment);
+                let witness = Witness94::new(
+                    vec![r, r],
+                    HashSet::from([bit]),
+                );
+
+                let (state, a) = SelfCompiler94::first(
+                    &statement, &witness, prover_rng,
+                );
+                let c = Self::fiat_shamir(
+                    &commitment,
+                    &bit_commitment,
+                    &a,
+                );
+                let z = SelfCompiler94::third(
+                    &statement, state, &witness, &c,
+                    prover_rng,
+                );
+
+                BitProof {
+                    commitment: bit_commitment,
+                    a,
+                    z,
+                }
+            })
+            .collect();
+
+        RangeProof { bits }
+    }
+
+    /// Verify that `commitment` commits to a value in
+    /// `[0, 2^bits)`
+    pub fn verify(
+        &self,
+        bits: usize,
+        commitment: &RistrettoPoint,
+    ) -> bool {
+        if self
+            .bits
+            .len()
+            != bits
+        {
+            return false;
+        }
+
+        // Σ 2^i·C_i == C
+        let mut power = Scalar::ONE;
+        let mut sum = RistrettoPoint::default();
+        for bit in &self.bits {
+            sum += power * bit.commitment;
+            power *= Scalar::from(2u64);
+        }
+        if sum != *commitment {
+            return false;
+        }
+
+        self.bits
+            .iter()
+            .all(|bit| {
+                let statement =
+                    BitProof::statement(&bit.commitment);
+                let c = Self::fiat_shamir(
+                    commitment,
+                    &bit.commitment,
+                    &bit.a,
+                );
+                SelfCompiler94::verify(
+                    &statement, &bit.a, &c, &bit.z,
+                )
+            })
+    }
+
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn bits(&self) -> &Vec<BitProof> {
+        &self.bits
+    }
+
+    /// Challenge for a bit proof bound to the commitment,
+    /// the bit commitment and the first message
+    fn fiat_shamir(
+        commitment: &RistrettoPoint,
+        bit_commitment: &RistrettoPoint,
+        a: &Vec<CompressedRistretto>,
+    ) -> Scalar {
+        let mut hash = Sha512::new();
+        hash.update(
+            commitment
+                .compress()
+                .as_bytes(),
+        );
+        hash.update(
+            bit_commitment
+                .compress()
+                .as_bytes(),
+        );
+        for ai in a {
+            hash.update(ai.as_bytes());
+        }
+        Scalar::from_hash(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    const BITS: usize = 8;
+
+    #[test]
+    fn range_proof_works() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        for value in [0, 1, 100, 255] {
+            let blinding = Scalar::random(rng);
+            let proof = RangeProof::prove(
+                BITS, value, &blinding, rng,
+            );
+            assert!(proof
+                .verify(BITS, &commit(value, &blinding)));
+        }
+    }
+
+    #[test]
+    fn range_proof_out_of_range_fails() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let blinding = Scalar::random(rng);
+        let proof =
+            RangeProof::prove(BITS, 256, &blinding, rng);
+        assert!(
+            !proof.verify(BITS, &commit(256, &blinding))
+        );
+    }
+
+    #[test]
+    fn range_proof_inconsistent_bits_fail() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let blinding = Scalar::random(rng);
+        let commitment = commit(5, &blinding);
+        let proof =
+            RangeProof::prove(BITS, 5, &blinding, rng);
+
+        // Bit commitments for a different value
+        let mut other = proof.clone();
+        other.bits[0] =
+            RangeProof::prove(BITS, 4, &blinding, rng).bits
+                [0]
+            .clone();
+        assert!(!other.verify(BITS, &commitment));
+
+        // Wrong number of bits
+        assert!(!proof.verify(BITS - 1, &commitment));
+    }
+}
@@ -0,0 +1,292 @@
+//! Ring signatures from the CDS94 OR compiler.
+//!
+//! A signature on `msg` for a ring of public keys
+//! `P_0, ..., P_{n-1}` is a non-interactive proof of
+//! knowledge of the discrete log of `P_0` OR `P_1` OR ...
+//! OR `P_{n-1}`, with the challenge bound to the ring and
+//! the message by Fiat-Shamir. The proof does not reveal
+//! which key the signer knows the secret of.
+use std::collections::HashSet;
+
+use curve25519_dalek::ristretto::{
+    CompressedRistretto, RistrettoPoint,
+};
+use curve25519_dalek::Scalar;
+use sha2::{Digest, Sha512};
+use sigmazk::error::Error;
+
+use crate::selfcompiler::{
+    CompiledZ94, SelfCompiler94, Statement94, Witness94,
+};
+use crate::*;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct RingSig {
+    a: Vec<CompressedRistretto>,
+    z: Vec<CompiledZ94<Schnorr>>,
+}
+
+pub struct RingSignature;
+
+impl RingSignature {
+    /// Sign `msg` as the member `signer_index` of `ring`,
+    /// whose public key is `secret·G`.
+    ///
+    /// If `secret` is not the secret key of
+    /// `ring[signer_index]` the resulting signature is
+    /// rejected by [`RingSignature::verify`]. Fails with
+    /// [`Error::InvalidStatement`] if the ring has fewer
+    /// than two members or a member's public key is the
+    /// identity, whose secret key is known to all, and with
+    /// [`Error::InvalidActiveClauses`] if `signer_index` is
+    /// not a member of the ring.
+    pub fn sign<R: CryptoRngCore + Clone>(
+        secret: &Scalar,
+        ring: &[RistrettoPoint],
+        signer_index: usize,
+        msg: &[u8],
+        rng: &mut R,
+    ) -> Result<RingSig, Error> {
+        if ring.len() < 2 {
+            return Err(Error::InvalidStatement);
+        }
+        let statement = Self::statement(ring);
+        if !Self::is_valid_ring(&statement) {
+            return Err(Error::InvalidStatement);
+        }
+        let witness = Witness94::try_new(
+            ring.len(),
+            vec![*secret; ring.len()],
+            HashSet::from([signer_index]),
+        )?;
+
+        let (state, a) = SelfCompiler94::first(
+            &statement, &witness, rng,
+        );
+        let c = Self::fiat_shamir(ring, msg, &a);
+        let z = SelfCompiler94::third(
+            &statement, state, &witness, &c, rng,
+        );
+
+        Ok(RingSig { a, z })
+    }
+
+    /// Verify that `sig` was produced on `msg` by a member
+    /// of `ring`
+    pub fn verify(
+        ring: &[RistrettoPoint],
+        msg: &[u8],
+        sig: &RingSig,
+    ) -> bool {
+        if ring.len() < 2
+            || sig
+                .a
+                .len()
+                != ring.len()
+        {
+            return false;
+        }
+        let statement = Self::statement(ring);
+        if !Self::is_valid_ring(&statement) {
+            return false;
+        }
+        let c = Self::fiat_shamir(ring, msg, &sig.a);
+        SelfCompiler94::verify(
+            &statement, &sig.a, &c, &sig.z,
+        )
+    }
+
+    /// The disjunction "I know the secret key of one of the
+    /// members of `ring`"
+    fn statement(
+        ring: &[RistrettoPoint],
+    ) -> Statement94<Schnorr> {
+        Statement94::new(
+            ring.len(),
+            1,
+            ring.iter()
+                .map(|pub_key| Schnorr {
+                    pub_key: *pub_key,
+                })
+                .collect(),
+        )
+    }
+
+    /// No member's public key is the identity
+    fn is_valid_ring(
+        statement: &Statement94<Schnorr>,
+    ) -> bool {
+        let (.., statements) = statement.pattern_match();
+        statements
+            .iter()
+            .all(Schnorr::is_valid_statement)
+    }
+
+    /// Challenge bound to the ring, the message and the
+    /// first message
+    fn fiat_shamir(
+        ring: &[RistrettoPoint],
+        msg: &[u8],
+        a: &Vec<CompressedRistretto>,
+    ) -> Scalar {
+        let mut hash = Sha512::new();
+        hash.update((ring.len() as u64).to_le_bytes());
+        for pub_key in ring {
+            hash.update(
+                pub_key
+                    .compress()
+                    .as_bytes(),
+            );
+        }
+        hash.update((msg.len() as u64).to_le_bytes());
+        hash.update(msg);
+        for ai in a {
+            hash.update(ai.as_bytes());
+        }
+        Scalar::from_hash(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    const N: usize = 8;
+    const SIGNER: usize = 5;
+
+    fn ring(
+        rng: &mut ChaCha20Rng,
+    ) -> (Vec<Scalar>, Vec<RistrettoPoint>) {
+        let secrets: Vec<Scalar> = (0..N)
+            .map(|_| Scalar::random(rng))
+            .collect();
+        let ring = secrets
+            .iter()
+            .map(|x| x * RISTRETTO_BASEPOINT_TABLE)
+            .collect();
+        (secrets, ring)
+    }
+
+    #[test]
+    fn ring_signature_works() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (secrets, ring) = ring(rng);
+        let msg = b"hello world";
+
+        let sig = RingSignature::sign(
+            &secrets[SIGNER],
+            &ring,
+            SIGNER,
+            msg,
+            rng,
+        )
+        .unwrap();
+        assert!(RingSignature::verify(&ring, msg, &sig));
+
+        // Changing the message invalidates the signature
+        assert!(!RingSignature::verify(
+            &ring,
+            b"goodbye world",
+            &sig
+        ));
+
+        // So does changing the ring
+        let mut other_ring = ring.clone();
+        other_ring[0] = &Scalar::random(rng)
+            * RISTRETTO_BASEPOINT_TABLE;
+        assert!(!RingSignature::verify(
+            &other_ring,
+            msg,
+            &sig
+        ));
+    }
+
+    #[test]
+    fn non_member_cannot_sign() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (_, ring) = ring(rng);
+        let msg = b"hello world";
+
+        let outsider = Scalar::random(rng);
+        let sig = RingSignature::sign(
+            &outsider, &ring, SIGNER, msg, rng,
+        )
+        .unwrap();
+        assert!(!RingSignature::verify(&ring, msg, &sig));
+    }
+
+    #[test]
+    fn identity_key_in_ring_is_rejected() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (secrets, mut ring) = ring(rng);
+        let msg = b"hello world";
+        let sig = RingSignature::sign(
+            &secrets[SIGNER],
+            &ring,
+            SIGNER,
+            msg,
+            rng,
+        )
+        .unwrap();
+
+        // The identity is the public key of the secret zero
+        ring[0] = Schnorr::init(Scalar::ZERO).pub_key;
+        assert!(matches!(
+            RingSignature::sign(
+                &Scalar::ZERO,
+                &ring,
+                0,
+                msg,
+                rng
+            ),
+            Err(Error::InvalidStatement)
+        ));
+        assert!(!RingSignature::verify(&ring, msg, &sig));
+    }
+
+    #[test]
+    fn ring_too_small_is_rejected() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (secrets, ring) = ring(rng);
+        let msg = b"hello world";
+
+        // A ring of one reveals the signer, and verification
+        // rejects it, so signing does too
+        for members in [&ring[..0], &ring[..1]] {
+            assert!(matches!(
+                RingSignature::sign(
+                    &secrets[0],
+                    members,
+                    0,
+                    msg,
+                    rng
+                ),
+                Err(Error::InvalidStatement)
+            ));
+        }
+    }
+
+    #[test]
+    fn signer_outside_ring_is_rejected() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (secrets, ring) = ring(rng);
+        assert!(matches!(
+            RingSignature::sign(
+                &secrets[SIGNER],
+                &ring,
+                N,
+                b"hello world",
+                rng
+            ),
+            Err(Error::InvalidActiveClauses)
+        ));
+    }
+}
@@ -1,9 +1,19 @@
 //! CDS94 Compiler for a single protocol
 use std::collections::HashSet;
 use std::marker::PhantomData;
-
+use std::sync::Arc;
+
+use clause::ClauseId;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::Scalar;
+use group::ff::{Field, PrimeField};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha256};
+use shamir_ss::lagrange::LagrangePolynomial;
 use shareable::Shareable;
-use sigmazk::Challenge;
+use sigmazk::error::Error;
+use sigmazk::{Challenge, StatementDigest};
 
 use crate::*;
 
@@ -14,9 +24,27 @@ pub struct SelfCompiler94<S: Composable> {
     base: PhantomData<S>,
 }
 
+const SHARING_FAILED: &str =
+    "the simulated clauses do not complete the sharing of \
+     the challenge";
+
+/// Panics unless `1 <= threshold <= clauses`, as the Shamir
+/// threshold `clauses - threshold + 1` would otherwise
+/// underflow or exceed the number of shares
+fn assert_threshold(clauses: usize, threshold: usize) {
+    assert!(
+        1 <= threshold && threshold <= clauses,
+        "threshold must be between 1 and the number of \
+         clauses ({}), got {}",
+        clauses,
+        threshold
+    );
+}
+
 impl<S: Composable> SelfCompiler94<S> {
-    #[cfg_attr(coverage_nightly, no_coverage)]
+    /// Panics unless `1 <= threshold <= clauses`
     pub fn new(clauses: usize, threshold: usize) -> Self {
+        assert_threshold(clauses, threshold);
         Self {
             clauses,
             threshold,
@@ -33,29 +61,783 @@ impl<S: Composable> SelfCompiler94<S> {
     pub fn threshold(&self) -> usize {
         self.threshold
     }
+
+    /// Returns the challenge share assigned to each clause
+    /// in the third round, ordered by clause index, without
+    /// running the third round of the underlying protocol.
+    ///
+    /// Simulated clauses keep the challenge chosen in the
+    /// first round. The shares of the active clauses are
+    /// derived from `challenge` with `complete_shares`.
+    ///
+    /// Panics if the simulated clauses do not complete the
+    /// sharing, see [`SelfCompiler94::try_third`].
+    pub fn debug_shares(
+        statement: &Statement94<S>,
+        state: &State94<S>,
+        challenge: &S::Challenge,
+    ) -> Vec<(ClauseId, S::Challenge)> {
+        Self::challenge_shares(statement, state, challenge)
+            .expect(SHARING_FAILED)
+            .into_iter()
+            .map(|share| {
+                (
+                    ClauseId::from_share_x::<S::Challenge>(
+                        share.x,
+                    )
+                    .unwrap(),
+                    Shareable::derive(share.y),
+                )
+            })
+            .sorted_by_key(|(i, _)| *i)
+            .collect_vec()
+    }
+
+    /// Verify a proof received from an untrusted prover.
+    ///
+    /// Before doing any cryptographic work, rejects with
+    /// [`Error::ProofTooLarge`] a proof whose first or
+    /// third message has more entries than the statement has
+    /// clauses or than `max_clauses`, or that refers to a
+    /// clause that does not exist.
+    pub fn verify_bounded(
+        statement: &Statement94<S>,
+        a: &Vec<S::MessageA>,
+        c: &S::Challenge,
+        z: &Vec<CompiledZ94<S>>,
+        max_clauses: usize,
+    ) -> Result<bool, Error> {
+        let bound = statement
+            .clauses()
+            .min(max_clauses);
+        if a.len() > bound
+            || z.len() > bound
+            || z.iter()
+                .any(|CompiledZ94(i, _, _)| {
+                    i.index() >= a.len()
+                })
+        {
+            return Err(Error::ProofTooLarge);
+        }
+
+        Ok(Self::verify(statement, a, c, z))
+    }
+
+    /// Verify the clauses of a proof received so far.
+    ///
+    /// Each received `(i, a_i, c_i, z_i)` is checked against
+    /// clause `i` of the statement; duplicates and clauses
+    /// that do not exist are ignored. Once the valid
+    /// clauses hold enough challenge shares to reach the
+    /// Shamir threshold, the challenge is reconstructed from
+    /// them and compared to `challenge`, and every share
+    /// beyond the threshold must lie on the same
+    /// polynomial.
+    pub fn verify_partial(
+        statement: &Statement94<S>,
+        challenge: &S::Challenge,
+        received: &[ReceivedClause<S>],
+    ) -> PartialResult {
+        let (clauses, cds_threshold, statements) =
+            statement.pattern_match();
+        let shamir = ShamirSecretSharing {
+            threshold: clauses - cds_threshold + 1,
+            shares: *clauses,
+        };
+
+        let mut seen =
+            HashSet::with_capacity(received.len());
+        let shares = received
+            .iter()
+            .filter(|(i, a, c, z)| {
+                i.index() < *clauses
+                    && seen.insert(*i)
+                    && S::verify(
+                        &statements[i.index()],
+                        a,
+                        c,
+                        z,
+                    )
+            })
+            .map(|(i, _, c, _)| Share {
+                x: i.to_share_x(),
+                y: c.share(),
+            })
+            .collect_vec();
+
+        let valid_clauses = shares.len();
+        let needed_for_threshold = shamir
+            .threshold
+            .saturating_sub(valid_clauses);
+        let reconstructable = needed_for_threshold == 0
+            && shamir
+                .reconstruct_secret_checked(&shares)
+                .is_ok_and(|secret| {
+                    secret == challenge.share()
+                });
+
+        PartialResult {
+            valid_clauses,
+            needed_for_threshold,
+            reconstructable,
+        }
+    }
+
+    /// Verify many proofs of the same statement.
+    ///
+    /// Every proof holds one challenge share per clause at
+    /// the same x-coordinates, so the points its sharing
+    /// polynomial is checked at are computed once for the
+    /// whole batch.
+    ///
+    /// A proof is accepted exactly when
+    /// [`SigmaProtocol::verify`] accepts it: it answers
+    /// every clause exactly once, every clause transcript
+    /// verifies, and all challenge shares lie on one
+    /// polynomial whose secret is the challenge.
+    pub fn verify_batch(
+        statement: &Statement94<S>,
+        proofs: &[Proof94<S>],
+    ) -> Vec<bool> {
+        let (clauses, cds_threshold, statements) =
+            statement.pattern_match();
+        let check =
+            ShareCheck::new(*clauses, *cds_threshold);
+
+        proofs
+            .iter()
+            .map(|(a, c, z)| {
+                Self::clause_shares(
+                    statement,
+                    a.len(),
+                    z,
+                    |i, ci, zi| {
+                        S::verify(
+                            &statements[i],
+                            &a[i],
+                            ci,
+                            zi,
+                        )
+                    },
+                )
+                .and_then(|ys| check.secret(&ys))
+                .is_some_and(|secret| secret == c.share())
+            })
+            .collect()
+    }
+
+    /// Third round of the protocol, yielding the response
+    /// of one clause at a time instead of collecting them.
+    ///
+    /// The challenge shares are computed up front, but the
+    /// third round of the underlying protocol for an active
+    /// clause only runs when the iterator reaches it.
+    ///
+    /// Panics where [`SelfCompiler94::try_third`] fails.
+    pub fn third_streaming<'a, R: CryptoRngCore + Clone>(
+        statement: &'a Statement94<S>,
+        state: State94<S>,
+        witness: &'a Witness94<S>,
+        challenge: &S::Challenge,
+        prover_rng: &'a mut R,
+    ) -> impl Iterator<Item = CompiledZ94<S>> + 'a {
+        Self::try_third_streaming(
+            statement, state, witness, challenge,
+            prover_rng,
+        )
+        .expect(SHARING_FAILED)
+    }
+
+    /// Third round of the protocol like
+    /// [`SigmaProtocol::third`], but fails with
+    /// [`Error::SecretSharing`] instead of panicking if the
+    /// challenge cannot be shared among the clauses, e.g.
+    /// because the witness does not have exactly
+    /// `threshold` active clauses.
+    pub fn try_third<R: CryptoRngCore + Clone>(
+        statement: &Statement94<S>,
+        state: State94<S>,
+        witness: &Witness94<S>,
+        challenge: &S::Challenge,
+        prover_rng: &mut R,
+    ) -> Result<Vec<CompiledZ94<S>>, Error> {
+        Ok(Self::try_third_streaming(
+            statement, state, witness, challenge,
+            prover_rng,
+        )?
+        .collect_vec())
+    }
+
+    /// Like [`SelfCompiler94::third_streaming`], but fails
+    /// where [`SelfCompiler94::try_third`] does
+    pub fn try_third_streaming<
+        'a,
+        R: CryptoRngCore + Clone,
+    >(
+        statement: &'a Statement94<S>,
+        state: State94<S>,
+        witness: &'a Witness94<S>,
+        challenge: &S::Challenge,
+        prover_rng: &'a mut R,
+    ) -> Result<
+        impl Iterator<Item = CompiledZ94<S>> + 'a,
+        Error,
+    > {
+        // Deconstruct variables
+        let (.., statements) = statement.pattern_match();
+        let (witnesses, _) = witness.pattern_match();
+
+        let shares = Self::challenge_shares(
+            statement, &state, challenge,
+        )?;
+
+        // Take ownership of the message_zs and inner_states of
+        // underyling sigma protocols
+        let State94 {
+            mut inner_states,
+            challenges,
+            mut zs,
+        } = state;
+
+        Ok(shares
+            .into_iter()
+            .map(move |share| {
+                // Derive the clause from the field element
+                let id = ClauseId::from_share_x::<
+                    S::Challenge,
+                >(share.x)
+                .unwrap();
+                let i = id.index();
+
+                match &challenges[i] {
+                    // If this is simulated, return the simulated values
+                    Some(ci) => CompiledZ94(
+                        id,
+                        ci.clone(),
+                        zs[i]
+                            .take()
+                            .unwrap(),
+                    ),
+                    // If not simulated, run the third round of the underlying sigma protocol
+                    None => {
+                        let ci = Shareable::derive(share.y);
+                        let zi = S::third(
+                            &statements[i],
+                            inner_states[i]
+                                .take()
+                                .unwrap(),
+                            &witnesses[i],
+                            &ci,
+                            prover_rng,
+                        );
+
+                        CompiledZ94(id, ci, zi)
+                    }
+                }
+            }))
+    }
+
+    /// Verification with the check of each clause
+    /// transcript abstracted: `clause_ok(i, c_i, z_i)`
+    /// checks clause `i` against the first message of a
+    /// proof with `a_len` of them.
+    fn verify_clauses<F>(
+        statement: &Statement94<S>,
+        a_len: usize,
+        secret: &S::Challenge,
+        z: &[CompiledZ94<S>],
+        clause_ok: F,
+    ) -> bool
+    where
+        F: Fn(usize, &S::Challenge, &S::MessageZ) -> bool,
+    {
+        let (clauses, cds_threshold, _) =
+            statement.pattern_match();
+        Self::clause_shares(statement, a_len, z, clause_ok)
+            .and_then(|ys| {
+                ShareCheck::new(*clauses, *cds_threshold)
+                    .secret(&ys)
+            })
+            .is_some_and(|combined_secret| {
+                combined_secret == secret.share()
+            })
+    }
+
+    /// Challenge shares of a proof in clause order, or
+    /// `None` unless the proof has one first message and
+    /// one response per clause, every response names a
+    /// distinct clause and `clause_ok(i, c_i, z_i)` accepts
+    /// every clause transcript.
+    ///
+    /// A clause is only checked once its index is known to
+    /// be in range, so `clause_ok` may index the first
+    /// messages and statements by it.
+    fn clause_shares<F>(
+        statement: &Statement94<S>,
+        a_len: usize,
+        z: &[CompiledZ94<S>],
+        clause_ok: F,
+    ) -> Option<Vec<<S::Challenge as Shareable>::F>>
+    where
+        F: Fn(usize, &S::Challenge, &S::MessageZ) -> bool,
+    {
+        let (clauses, _, statements) =
+            statement.pattern_match();
+        if a_len != *clauses
+            || z.len() != *clauses
+            || statements.len() != *clauses
+        {
+            return None;
+        }
+
+        let mut ys = vec![None; *clauses];
+        for CompiledZ94(i, c, m2) in z {
+            let i = i.index();
+            if i >= *clauses
+                || ys[i].is_some()
+                || !clause_ok(i, c, m2)
+            {
+                return None;
+            }
+            ys[i] = Some(c.share());
+        }
+        // One entry per clause, none repeated, so every
+        // clause has a share
+        ys.into_iter()
+            .collect()
+    }
+
+    /// Shamir shares of `challenge` where each clause is
+    /// assigned the x-coordinate [`ClauseId::to_share_x`].
+    ///
+    /// The shares of the simulated clauses come first,
+    /// followed by the completed shares of the active
+    /// clauses. If the threshold is the number of clauses,
+    /// every share is the challenge itself.
+    ///
+    /// Fails with [`Error::SecretSharing`] if the simulated
+    /// clauses are not an unqualified set of the sharing.
+    fn challenge_shares(
+        statement: &Statement94<S>,
+        state: &State94<S>,
+        challenge: &S::Challenge,
+    ) -> Result<
+        Vec<Share<<S::Challenge as Shareable>::F>>,
+        Error,
+    > {
+        let (clauses, cds_threshold, _) =
+            statement.pattern_match();
+
+        // Create instance of Shamir Secret Sharing
+        let shamirs_threshold = clauses - cds_threshold + 1;
+
+        // Every clause is active: the sharing polynomial is
+        // the constant challenge, so the proof is a plain
+        // conjunction and no interpolation is needed. A
+        // simulated clause falls through to the sharing below,
+        // which rejects it.
+        if shamirs_threshold == 1
+            && state
+                .challenges()
+                .iter()
+                .all(Option::is_none)
+        {
+            return Ok((0..*clauses)
+                .map(|i| Share {
+                    x: ClauseId(i).to_share_x(),
+                    y: challenge.share(),
+                })
+                .collect());
+        }
+
+        let shamir = ShamirSecretSharing {
+            threshold: shamirs_threshold,
+            shares: *clauses,
+        };
+
+        // Initalize vectors
+        let mut shares =
+            Vec::with_capacity(shamirs_threshold);
+        let mut remaining_xs = Vec::new();
+
+        for (i, ci) in state
+            .challenges()
+            .iter()
+            .enumerate()
+        {
+            let x = ClauseId(i).to_share_x();
+            match ci {
+                // If clause is simulated, add the share to the shares vector
+                Some(ci) => {
+                    shares.push(Share { x, y: ci.share() })
+                }
+                // Otherwise the clause is active, add the x-coordinate value to the remaining_xs vector
+                None => remaining_xs.push(x),
+            }
+        }
+
+        // Get the missing shares by completing the shares vector with the remaining_xs vector x_values
+        let mut missing_shares = shamir.complete_shares(
+            &challenge.share(),
+            &shares,
+            &remaining_xs,
+        )?;
+
+        // Append the missing shares to the shares vector
+        shares.append(&mut missing_shares);
+
+        Ok(shares)
+    }
+
+    /// First round like [`SigmaProtocol::first`], taking the
+    /// simulated transcripts of the inactive clauses from
+    /// `cache` while it has any for them and simulating the
+    /// others afresh.
+    ///
+    /// Every cached transcript is used by one proof only and
+    /// is removed from the cache; see [`SimulationCache`].
+    pub fn first_cached<R: CryptoRngCore + Clone>(
+        statement: &Statement94<S>,
+        witness: &Witness94<S>,
+        cache: &mut SimulationCache<S>,
+        prover_rng: &mut R,
+    ) -> (State94<S>, Vec<S::MessageA>) {
+        Self::first_with(
+            statement,
+            witness,
+            prover_rng,
+            |i, statement| {
+                cache
+                    .take(i)
+                    .unwrap_or_else(|| {
+                        S::simulate(statement)
+                    })
+            },
+        )
+    }
+
+    /// First round with the transcripts of the inactive
+    /// clauses produced by `simulate(i, statement_i)`
+    fn first_with<R, F>(
+        statement: &Statement94<S>,
+        witness: &Witness94<S>,
+        prover_rng: &mut R,
+        mut simulate: F,
+    ) -> (State94<S>, Vec<S::MessageA>)
+    where
+        R: CryptoRngCore + Clone,
+        F: FnMut(
+            usize,
+            &S::Statement,
+        ) -> (
+            S::MessageA,
+            S::Challenge,
+            S::MessageZ,
+        ),
+    {
+        // Deconstruct variables
+        let (clauses, _cds_threshold, statements) =
+            statement.pattern_match();
+        let (witnesses, active_clauses) =
+            witness.pattern_match();
+        // Intialize vectors
+        let mut inner_states: Vec<Option<S::State>> =
+            Vec::with_capacity(*clauses);
+        let mut challenges: Vec<Option<S::Challenge>> =
+            Vec::with_capacity(*clauses);
+        let mut zs: Vec<Option<S::MessageZ>> =
+            Vec::with_capacity(*clauses);
+        let mut message_as: Vec<S::MessageA> =
+            Vec::with_capacity(*clauses);
+
+        for i in 0..*clauses {
+            // If the clause is active, run the first round of the underlying sigma protocol
+            if active_clauses.contains(&i) {
+                let (state, message_a) = S::first(
+                    &statements[i],
+                    &witnesses[i],
+                    prover_rng,
+                );
+
+                // Push relevant values to vectors
+                message_as.push(message_a);
+                inner_states.push(Some(state));
+                challenges.push(None);
+                zs.push(None);
+            } else {
+                // If the clause is not active, simulate the underyling sigma protocol
+                let (message_a, c, z) =
+                    simulate(i, &statements[i]);
+
+                // Push relevant values to vectors
+                message_as.push(message_a);
+                inner_states.push(None);
+                challenges.push(Some(c));
+                zs.push(Some(z));
+            }
+        }
+
+        (
+            State94::new(inner_states, challenges, zs),
+            message_as,
+        )
+    }
+}
+
+impl SelfCompiler94<Schnorr> {
+    /// Verify like [`SigmaProtocol::verify`], but
+    /// decompress every first message before checking any
+    /// clause. A proof with a first message that is not a
+    /// valid point is rejected with [`Error::InvalidPoint`]
+    /// before any verification work is done. A response
+    /// naming a clause the statement does not have is
+    /// rejected with `Ok(false)`.
+    pub fn verify_decompressed(
+        statement: &Statement94<Schnorr>,
+        a: &[CompressedRistretto],
+        secret: &Scalar,
+        z: &[CompiledZ94<Schnorr>],
+    ) -> Result<bool, Error> {
+        let points = a
+            .iter()
+            .map(|a| {
+                a.decompress()
+                    .ok_or(Error::InvalidPoint)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let statements = statement.statements();
+        Ok(Self::verify_clauses(
+            statement,
+            points.len(),
+            secret,
+            z,
+            |i, c, m2| {
+                Schnorr::verify_point(
+                    &statements[i],
+                    &points[i],
+                    c,
+                    m2,
+                )
+            },
+        ))
+    }
+}
+
+/// Check of the challenge shares of a proof, one per
+/// clause in clause order, against the Shamir sharing of a
+/// statement.
+///
+/// The shares of the first `threshold` clauses determine
+/// the sharing polynomial. It is evaluated at `x = 0` for
+/// the secret and at the x-coordinate of every other clause,
+/// whose share must match, so a proof cannot leave any
+/// share off the polynomial.
+pub(crate) struct ShareCheck<F: PrimeField> {
+    /// Shamir threshold `clauses - cds_threshold + 1`
+    threshold: usize,
+    /// x-coordinates of the first `threshold` clauses
+    basis: Vec<F>,
+    /// `0`, then the x-coordinates of the other clauses
+    points: Vec<F>,
+}
+
+impl<F: PrimeField> ShareCheck<F> {
+    pub(crate) fn new(
+        clauses: usize,
+        cds_threshold: usize,
+    ) -> Self {
+        let threshold = clauses - cds_threshold + 1;
+        let xs = (0..clauses)
+            .map(|i| ClauseId(i).to_share_x())
+            .collect_vec();
+        let (basis, others) = xs.split_at(threshold);
+        Self {
+            threshold,
+            basis: basis.to_vec(),
+            points: std::iter::once(F::ZERO)
+                .chain(
+                    others
+                        .iter()
+                        .copied(),
+                )
+                .collect(),
+        }
+    }
+
+    /// The secret of the shares `ys` of every clause, or
+    /// `None` if they do not all lie on one polynomial of
+    /// degree below the threshold
+    pub(crate) fn secret(&self, ys: &[F]) -> Option<F> {
+        let (basis_ys, other_ys) =
+            ys.split_at(self.threshold);
+        let poly = LagrangePolynomial::init(
+            self.basis
+                .clone(),
+            basis_ys.to_vec(),
+        )
+        .ok()?;
+        let values = poly.evaluate_at_many(&self.points);
+        (values[1..] == *other_ys).then_some(values[0])
+    }
+}
+
+/// Transcript `(a, c, z)` of a [`SelfCompiler94`] proof, as
+/// received by [`SelfCompiler94::verify_batch`]
+pub type Proof94<S> = (
+    Vec<<S as SigmaProtocol>::MessageA>,
+    <S as SigmaProtocol>::Challenge,
+    Vec<CompiledZ94<S>>,
+);
+
+/// Transcript `(i, a_i, c_i, z_i)` of clause `i`, as
+/// received by [`SelfCompiler94::verify_partial`]
+pub type ReceivedClause<S> = (
+    ClauseId,
+    <S as SigmaProtocol>::MessageA,
+    <S as SigmaProtocol>::Challenge,
+    <S as SigmaProtocol>::MessageZ,
+);
+
+/// Outcome of [`SelfCompiler94::verify_partial`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialResult {
+    /// Number of distinct clauses whose transcript verifies
+    pub valid_clauses: usize,
+    /// Number of further valid clauses needed to reach the
+    /// Shamir threshold
+    pub needed_for_threshold: usize,
+    /// The threshold is reached and the valid clauses'
+    /// challenges reconstruct the verifier's challenge
+    pub reconstructable: bool,
 }
 
+/// Deserializing checks the same invariants as
+/// [`Statement94::new`] and [`Statement94::with_labels`],
+/// failing instead of panicking
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "Statement94Fields<S>",
+        bound(
+            serialize = "S::Statement: serde::Serialize",
+            deserialize = "S::Statement: \
+                           serde::Deserialize<'de>"
+        )
+    )
+)]
 pub struct Statement94<S: SigmaProtocol> {
     clauses: usize,
     threshold: usize,
-    statements: Vec<S::Statement>,
+    /// Shared so that cloning a statement does not copy
+    /// its clauses
+    statements: Arc<[S::Statement]>,
+    /// Application labels of the clauses, empty if none
+    /// were attached. They are not bound into proofs.
+    labels: Arc<[Box<[u8]>]>,
+}
+
+/// Unchecked fields of a deserialized [`Statement94`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(
+    deserialize = "S::Statement: serde::Deserialize<'de>"
+))]
+struct Statement94Fields<S: SigmaProtocol> {
+    clauses: usize,
+    threshold: usize,
+    statements: Arc<[S::Statement]>,
+    labels: Arc<[Box<[u8]>]>,
+}
+
+#[cfg(feature = "serde")]
+impl<S: SigmaProtocol> TryFrom<Statement94Fields<S>>
+    for Statement94<S>
+{
+    type Error = &'static str;
+
+    fn try_from(
+        fields: Statement94Fields<S>,
+    ) -> Result<Self, Self::Error> {
+        if fields.threshold < 1
+            || fields.threshold > fields.clauses
+        {
+            return Err("threshold must be between 1 and \
+                        the number of clauses");
+        }
+        if fields
+            .statements
+            .len()
+            != fields.clauses
+        {
+            return Err(
+                "one statement per clause is required",
+            );
+        }
+        if !fields
+            .labels
+            .is_empty()
+            && fields
+                .labels
+                .len()
+                != fields
+                    .statements
+                    .len()
+        {
+            return Err(
+                "one label per statement is required",
+            );
+        }
+        Ok(Self {
+            clauses: fields.clauses,
+            threshold: fields.threshold,
+            statements: fields.statements,
+            labels: fields.labels,
+        })
+    }
 }
 
 impl<S: SigmaProtocol> Statement94<S> {
-    #[cfg_attr(coverage_nightly, no_coverage)]
+    /// Panics unless `1 <= threshold <= clauses` and there
+    /// is one statement per clause
     pub fn new(
         clauses: usize,
         threshold: usize,
         statements: Vec<S::Statement>,
     ) -> Self {
+        assert_threshold(clauses, threshold);
+        assert_eq!(
+            statements.len(),
+            clauses,
+            "one statement per clause is required"
+        );
         Self {
             clauses,
             threshold,
-            statements,
+            statements: statements.into(),
+            labels: Arc::new([]),
         }
     }
 
+    /// Like [`Statement94::new`], but fails with
+    /// [`Error::InvalidThreshold`] or
+    /// [`Error::StatementLengthMismatch`] instead of
+    /// panicking
+    pub fn try_new(
+        clauses: usize,
+        threshold: usize,
+        statements: Vec<S::Statement>,
+    ) -> Result<Self, Error> {
+        if threshold < 1 || threshold > clauses {
+            return Err(Error::InvalidThreshold);
+        }
+        if statements.len() != clauses {
+            return Err(Error::StatementLengthMismatch);
+        }
+        Ok(Self::new(clauses, threshold, statements))
+    }
+
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn clauses(&self) -> usize {
         self.clauses
@@ -67,10 +849,37 @@ impl<S: SigmaProtocol> Statement94<S> {
     }
 
     #[cfg_attr(coverage_nightly, no_coverage)]
-    pub fn statements(&self) -> &Vec<S::Statement> {
+    pub fn statements(&self) -> &[S::Statement] {
         &self.statements
     }
 
+    /// Attach a label to every clause, e.g. a party name or
+    /// public key fingerprint, for application bookkeeping.
+    /// Labels are not bound into proofs.
+    ///
+    /// Panics unless there is one label per statement.
+    pub fn with_labels(
+        mut self,
+        labels: Vec<Box<[u8]>>,
+    ) -> Self {
+        assert_eq!(
+            labels.len(),
+            self.statements
+                .len(),
+            "one label per statement is required"
+        );
+        self.labels = labels.into();
+        self
+    }
+
+    /// Label of the clause at `index`, or `None` if no labels
+    /// were attached or `index` is out of range
+    pub fn label_at(&self, index: usize) -> Option<&[u8]> {
+        self.labels
+            .get(index)
+            .map(AsRef::as_ref)
+    }
+
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn len(&self) -> usize {
         self.statements
@@ -80,11 +889,31 @@ impl<S: SigmaProtocol> Statement94<S> {
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn pattern_match(
         &self,
-    ) -> (&usize, &usize, &Vec<S::Statement>) {
+    ) -> (&usize, &usize, &[S::Statement]) {
         (&self.clauses, &self.threshold, &self.statements)
     }
 }
 
+impl<S: SigmaProtocol> StatementDigest for Statement94<S>
+where
+    S::Statement: StatementDigest,
+{
+    fn digest(&self) -> [u8; 32] {
+        let mut hash = Sha256::new();
+        hash.update(b"cds-compiler statement94");
+        hash.update((self.threshold as u64).to_le_bytes());
+        hash.update((self.clauses as u64).to_le_bytes());
+        for statement in self
+            .statements
+            .iter()
+        {
+            hash.update(statement.digest());
+        }
+        hash.finalize()
+            .into()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct State94<S: SigmaProtocol> {
     inner_states: Vec<Option<S::State>>,
@@ -92,13 +921,30 @@ pub struct State94<S: SigmaProtocol> {
     zs: Vec<Option<S::MessageZ>>,
 }
 
+/// Per-clause view of a [`State94`]: the clause's inner state
+/// if it is active, or its simulated challenge and third
+/// message otherwise
+pub type ClauseState<'a, S> = (
+    Option<&'a <S as SigmaProtocol>::State>,
+    Option<&'a <S as SigmaProtocol>::Challenge>,
+    Option<&'a <S as SigmaProtocol>::MessageZ>,
+);
+
 impl<S: Composable> State94<S> {
+    /// Panics unless the three vectors hold one entry per
+    /// clause each
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn new(
         inner_states: Vec<Option<S::State>>,
         challenges: Vec<Option<S::Challenge>>,
         zs: Vec<Option<S::MessageZ>>,
     ) -> Self {
+        assert!(
+            inner_states.len() == challenges.len()
+                && challenges.len() == zs.len(),
+            "inner states, challenges and third messages \
+             must have the same length"
+        );
         Self {
             inner_states,
             challenges,
@@ -131,14 +977,146 @@ impl<S: Composable> State94<S> {
     pub fn zs(&self) -> &Vec<Option<S::MessageZ>> {
         &self.zs
     }
+
+    /// Iterates over the clauses in order, yielding the
+    /// inner state, challenge and third message of each
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = ClauseState<'_, S>> {
+        self.inner_states
+            .iter()
+            .zip(
+                self.challenges
+                    .iter(),
+            )
+            .zip(
+                self.zs
+                    .iter(),
+            )
+            .map(|((state, c), z)| {
+                (state.as_ref(), c.as_ref(), z.as_ref())
+            })
+    }
+}
+
+impl<'a, S: Composable> IntoIterator for &'a State94<S> {
+    type Item = ClauseState<'a, S>;
+    type IntoIter =
+        Box<dyn Iterator<Item = Self::Item> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
 }
 
+/// Simulated transcripts `(a, c, z)` of clauses, drawn
+/// ahead of time so that [`SelfCompiler94::first_cached`]
+/// does not have to simulate the inactive clauses while
+/// proving.
+///
+/// Each transcript is handed out once. Reusing one in two
+/// proofs would repeat the clause's first message and its
+/// challenge share, and since the shares of the active
+/// clauses are derived from the fresh verifier challenge
+/// the repetition would reveal which clauses are inactive.
+/// Drawing the transcripts before the statement is proven
+/// is sound because their challenges are sampled
+/// independently of the verifier's challenge, exactly as in
+/// [`SigmaProtocol::first`].
 #[derive(Clone, Debug)]
+pub struct SimulationCache<S: SigmaProtocol> {
+    transcripts:
+        Vec<Vec<(S::MessageA, S::Challenge, S::MessageZ)>>,
+}
+
+impl<S: Composable> SimulationCache<S> {
+    /// An empty cache for a statement with `clauses`
+    /// clauses
+    pub fn new(clauses: usize) -> Self {
+        Self {
+            transcripts: vec![Vec::new(); clauses],
+        }
+    }
+
+    /// Simulate `count` more transcripts of each clause in
+    /// `clauses`, e.g. the clauses a prover is known to have
+    /// no witness for.
+    ///
+    /// Fails with [`Error::InvalidIndex`], leaving the cache
+    /// unchanged, if a clause is not one of the statement's
+    /// or of the cache's.
+    pub fn fill(
+        &mut self,
+        statement: &Statement94<S>,
+        clauses: impl IntoIterator<Item = usize>,
+        count: usize,
+    ) -> Result<(), Error> {
+        let statements = statement.statements();
+        let clauses = clauses
+            .into_iter()
+            .collect_vec();
+        if clauses
+            .iter()
+            .any(|i| {
+                *i >= statements.len()
+                    || *i
+                        >= self
+                            .transcripts
+                            .len()
+            })
+        {
+            return Err(Error::InvalidIndex);
+        }
+        for i in clauses {
+            self.transcripts[i].extend(
+                (0..count)
+                    .map(|_| S::simulate(&statements[i])),
+            );
+        }
+        Ok(())
+    }
+
+    /// Number of transcripts of clause `i` left
+    pub fn available(&self, i: usize) -> usize {
+        self.transcripts
+            .get(i)
+            .map_or(0, Vec::len)
+    }
+
+    fn take(
+        &mut self,
+        i: usize,
+    ) -> Option<(S::MessageA, S::Challenge, S::MessageZ)>
+    {
+        self.transcripts
+            .get_mut(i)?
+            .pop()
+    }
+}
+
+#[derive(Clone)]
 pub struct Witness94<S: Composable> {
     witnesses: Vec<S::Witness>,
     active_clauses: HashSet<usize>,
 }
 
+/// The witnesses are redacted, only their number is shown
+impl<S: Composable> fmt::Debug for Witness94<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Witness94")
+            .field(
+                "witnesses",
+                &format_args!(
+                    "<{} redacted>",
+                    self.witnesses
+                        .len()
+                ),
+            )
+            .field("active_clauses", &self.active_clauses)
+            .finish()
+    }
+}
+
 impl<S: Composable> Witness94<S> {
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn new(
@@ -151,6 +1129,27 @@ impl<S: Composable> Witness94<S> {
         }
     }
 
+    /// Like [`Witness94::new`], but checks that there is one
+    /// witness for each of the `clauses` clauses and that
+    /// the active clauses are a non-empty subset of them
+    pub fn try_new(
+        clauses: usize,
+        witnesses: Vec<S::Witness>,
+        active_clauses: HashSet<usize>,
+    ) -> Result<Self, Error> {
+        if witnesses.len() != clauses {
+            return Err(Error::WitnessLengthMismatch);
+        }
+        if active_clauses.is_empty()
+            || active_clauses
+                .iter()
+                .any(|i| *i >= clauses)
+        {
+            return Err(Error::InvalidActiveClauses);
+        }
+        Ok(Self::new(witnesses, active_clauses))
+    }
+
     #[cfg_attr(coverage_nightly, no_coverage)]
     pub fn active_clauses(&self) -> &HashSet<usize> {
         &self.active_clauses
@@ -195,12 +1194,27 @@ fn test_selfcompiler_display() {
 }
 
 #[derive(Clone, Default, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct CompiledZ94<S: Composable>(
-    usize,
+    ClauseId,
     S::Challenge,
     S::MessageZ,
 );
 
+impl<S: Composable> CompiledZ94<S> {
+    /// Clause index, challenge and third message of the
+    /// underlying protocol
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn pattern_match(
+        &self,
+    ) -> (&ClauseId, &S::Challenge, &S::MessageZ) {
+        (&self.0, &self.1, &self.2)
+    }
+}
+
 impl<S: Composable + Default + Debug> Message
     for CompiledZ94<S>
 {
@@ -230,6 +1244,9 @@ impl<S: Composable> SigmaProtocol for SelfCompiler94<S> {
     type MessageZ = Vec<CompiledZ94<S>>;
 
     /// The algorithm for the first round of the protocol.
+    ///
+    /// `prover_rng` is advanced, so two calls with the same
+    /// RNG draw fresh nonces for the active clauses.
     fn first<R: CryptoRngCore + Clone>(
         statement: &Self::Statement,
         witness: &Self::Witness,
@@ -238,53 +1255,11 @@ impl<S: Composable> SigmaProtocol for SelfCompiler94<S> {
     where
         Self: Sized,
     {
-        // Clone the prover_rng as we need it to be the same value for third round
-        let mut prover_rng = prover_rng.clone();
-        // Deconstruct variables
-        let (clauses, _cds_threshold, statements) =
-            statement.pattern_match();
-        let (witnesses, active_clauses) =
-            witness.pattern_match();
-        // Intialize vectors
-        let mut inner_states: Vec<Option<S::State>> =
-            Vec::with_capacity(*clauses);
-        let mut challenges: Vec<Option<S::Challenge>> =
-            Vec::with_capacity(*clauses);
-        let mut zs: Vec<Option<S::MessageZ>> =
-            Vec::with_capacity(*clauses);
-        let mut message_as: Vec<S::MessageA> =
-            Vec::with_capacity(*clauses);
-
-        for i in 0..*clauses {
-            // If the clause is active, run the first round of the underlying sigma protocol
-            if active_clauses.contains(&i) {
-                let (state, message_a) = S::first(
-                    &statements[i],
-                    &witnesses[i],
-                    &mut prover_rng,
-                );
-
-                // Push relevant values to vectors
-                message_as.push(message_a);
-                inner_states.push(Some(state));
-                challenges.push(None);
-                zs.push(None);
-            } else {
-                // If the clause is not active, simulate the underyling sigma protocol
-                let (message_a, c, z) =
-                    S::simulate(&statements[i]);
-
-                // Push relevant values to vectors
-                message_as.push(message_a);
-                inner_states.push(None);
-                challenges.push(Some(c));
-                zs.push(Some(z));
-            }
-        }
-
-        (
-            State94::new(inner_states, challenges, zs),
-            message_as,
+        Self::first_with(
+            statement,
+            witness,
+            prover_rng,
+            |_, statement| S::simulate(statement),
         )
     }
 
@@ -311,104 +1286,11 @@ impl<S: Composable> SigmaProtocol for SelfCompiler94<S> {
     where
         Self: Sized,
     {
-        // Deconstruct variables
-        let (clauses, cds_threshold, statements) =
-            statement.pattern_match();
-        let (witnesses, active_clauses) =
-            witness.pattern_match();
-
-        // Create instance of Shamir Secret Sharing
-        let shamirs_threshold = clauses - cds_threshold + 1;
-        let active_count = active_clauses.len();
-
-        let shamir = ShamirSecretSharing {
-            threshold: shamirs_threshold,
-            shares: *clauses,
-        };
-
-        // Initalize vectors
-        let mut shares =
-            Vec::with_capacity(shamirs_threshold);
-        let mut remaining_xs =
-            Vec::with_capacity(active_count);
-
-        let challenges = state.challenges();
-        for (i, ci) in challenges
-            .iter()
-            .enumerate()
-        {
-            // If clause is active, add the x-coordinate value to the remaining_xs vector
-            if active_clauses.contains(&i) {
-                remaining_xs.push(
-                    <S::Challenge as Shareable>::F::from(
-                        (i + 1) as u64,
-                    ),
-                );
-            } else {
-                // Otherwise, add the share to the shares vector
-                let share = Share {
-                    x: <S::Challenge as Shareable>::F::from(
-                        (i + 1) as u64,
-                    ),
-                    y: ci
-                        .clone()
-                        .unwrap()
-                        .share(),
-                };
-
-                shares.push(share);
-            }
-        }
-
-        // Get the missing shares by completing the shares vector with the remaining_xs vector x_values
-        let mut missing_shares = shamir
-            .complete_shares(
-                &challenge.share(),
-                &shares,
-                &remaining_xs,
-            )
-            .unwrap();
-
-        // Append the missing shares to the shares vector
-        shares.append(&mut missing_shares);
-
-        // Get the message_zs and inner_states of underyling sigma protocols
-        let message_zs = state.zs();
-        let inner_states = state.inner_states();
-
-        shares
-            .iter()
-            .map(|share| {
-                // Derive the usize from the field element
-                let i = S::Challenge::to_usize(share.x) - 1;
-
-                match &challenges[i] {
-                    // If this is simulated, return the simulated values
-                    Some(ci) => CompiledZ94(
-                        i,
-                        ci.clone(),
-                        message_zs[i]
-                            .clone()
-                            .unwrap(),
-                    ),
-                    // If not simulated, run the third round of the underlying sigma protocol
-                    None => {
-                        let ci = Shareable::derive(share.y);
-                        let zi = S::third(
-                            &statements[i],
-                            inner_states[i]
-                                .clone()
-                                .unwrap(),
-                            &witnesses[i],
-                            &ci,
-                            prover_rng,
-                        );
-
-                        CompiledZ94(i, ci, zi)
-                    }
-                }
-            })
-            .collect_vec()
+        Self::third_streaming(
+            statement, state, witness, challenge,
+            prover_rng,
+        )
+        .collect_vec()
     }
 
     /// Verification algorithm
@@ -421,94 +1303,88 @@ impl<S: Composable> SigmaProtocol for SelfCompiler94<S> {
     where
         Self: Sized,
     {
-        let valid = a.len() == z.len();
-
-        let (clauses, cds_threshold, statements) =
-            statement.pattern_match();
-
-        let mut shares = Vec::with_capacity(*clauses);
-
-        for CompiledZ94(i, c, m2) in z {
-            let m1 = &a[*i];
-
-            // Firstly verify that the transcript for current index is valid for the instance
-            if !S::verify(&statements[*i], m1, &c, &m2) {
-                return false;
-            }
-
-            // Then add the share to the shares vector
-            let share = Share {
-                x: <S::Challenge as Shareable>::F::from(
-                    (i + 1) as u64,
-                ),
-                y: c.share(),
-            };
-
-            shares.push(share);
-        }
-
-        let shamir = ShamirSecretSharing {
-            threshold: clauses - cds_threshold + 1,
-            shares: *clauses,
-        };
-
-        // Use shamir secret sharing to reconstruct secret
-        let res = shamir.reconstruct_secret(&shares);
-
-        let combined_secret = res.unwrap_or(
-            <S::Challenge as Shareable>::F::default(),
-        );
-
-        combined_secret == secret.share() && valid
+        let statements = statement.statements();
+        Self::verify_clauses(
+            statement,
+            a.len(),
+            secret,
+            z,
+            |i, c, m2| {
+                S::verify(&statements[i], &a[i], c, m2)
+            },
+        )
     }
 }
 
+/// Draws the challenge from entropy, like the simulators of
+/// the underlying protocols
 impl<S: Composable> HVzk for SelfCompiler94<S> {
     fn simulate(
         statement: &Self::Statement,
     ) -> (Self::MessageA, Self::Challenge, Self::MessageZ)
     {
+        let challenge =
+            Self::second(&mut ChaCha20Rng::from_entropy());
+        let (message_as, message_zs) =
+            Self::simulate_with_challenge(
+                statement, &challenge,
+            );
+        (message_as, challenge, message_zs)
+    }
+}
+
+impl<S: Composable> Composable for SelfCompiler94<S> {
+    /// The challenge and the challenges of the first
+    /// `clauses - threshold` clauses, simulated with
+    /// challenges of their own, fix the sharing polynomial.
+    /// The other clauses are simulated answering its values
+    /// at their x-coordinates, so that every share lies on
+    /// it as [`SigmaProtocol::verify`] requires.
+    fn simulate_with_challenge(
+        statement: &Statement94<S>,
+        challenge: &S::Challenge,
+    ) -> (Vec<S::MessageA>, Vec<CompiledZ94<S>>) {
         let (clauses, cds_threshold, statements) =
             statement.pattern_match();
+        let free = clauses - cds_threshold;
 
-        let shamir = ShamirSecretSharing {
-            threshold: clauses - cds_threshold + 1,
-            shares: *clauses,
-        };
+        let mut xs = Vec::with_capacity(free + 1);
+        let mut ys = Vec::with_capacity(free + 1);
+        xs.push(<S::Challenge as Shareable>::F::ZERO);
+        ys.push(challenge.share());
 
-        let mut shares = Vec::with_capacity(*clauses);
         let mut message_as = Vec::with_capacity(*clauses);
         let mut message_zs = Vec::with_capacity(*clauses);
 
-        for i in 0..*clauses {
+        for (i, statement) in statements[..free]
+            .iter()
+            .enumerate()
+        {
             let (message_a, c, z) =
-                S::simulate(&statements[i]);
-
+                <S as HVzk>::simulate(statement);
+            xs.push(ClauseId(i).to_share_x());
+            ys.push(c.share());
             message_as.push(message_a);
-            message_zs.push(CompiledZ94(i, c.clone(), z));
-
-            let share = Share {
-                x: <S::Challenge as Shareable>::F::from(
-                    (i + 1) as u64,
-                ),
-                y: c.share(),
-            };
-
-            shares.push(share);
+            message_zs.push(CompiledZ94(ClauseId(i), c, z));
         }
 
-        let secret = shamir.reconstruct_secret(&shares);
-
-        let combined_secret = secret.unwrap_or(
-            <S::Challenge as Shareable>::F::default(),
-        );
+        let others = (free..*clauses)
+            .map(|i| ClauseId(i).to_share_x())
+            .collect_vec();
+        let shares = LagrangePolynomial::init(xs, ys)
+            .expect("one y-coordinate per x-coordinate")
+            .evaluate_at_many(&others);
+
+        for (i, share) in (free..*clauses).zip(shares) {
+            let c = Shareable::derive(share);
+            let (message_a, z) = S::simulate_with_challenge(
+                &statements[i],
+                &c,
+            );
+            message_as.push(message_a);
+            message_zs.push(CompiledZ94(ClauseId(i), c, z));
+        }
 
-        (
-            message_as,
-            Shareable::derive(combined_secret),
-            message_zs,
-        )
+        (message_as, message_zs)
     }
 }
-
-impl<S: Composable> Composable for SelfCompiler94<S> {}
@@ -2,36 +2,64 @@ use std::fmt::Debug;
 
 use curve25519_dalek::Scalar;
 use group::ff::PrimeField;
+use sigmazk::error::Error;
 use wrapped_ristretto::scalar::WrappedScalar;
 
+use crate::clause::ClauseId;
+
+/// Prime field that challenges of type `C` are shared in,
+/// with lossless conversions in both directions
+pub trait ChallengeField<C>:
+    PrimeField + From<C> + Into<C>
+{
+}
+
+impl<C, F> ChallengeField<C> for F where
+    F: PrimeField + From<C> + Into<C>
+{
+}
+
 /// A trait for types that can be split be Shamir's Secret
 /// Sharing scheme
 ///
 /// It is a trait that is implemented to map types to a
 /// field element
-pub trait Shareable: Default + Debug {
-    type F: PrimeField;
+pub trait Shareable: Default + Debug + Clone {
+    type F: ChallengeField<Self>;
 
     // Map the type to a prime field element
-    fn share(&self) -> Self::F;
+    fn share(&self) -> Self::F {
+        Self::F::from(self.clone())
+    }
 
     // Derive the instance of the type from a field element
-    fn derive(elem: Self::F) -> Self;
+    fn derive(elem: Self::F) -> Self {
+        elem.into()
+    }
 
     // Convert field element into usize
     fn to_usize(elem: Self::F) -> usize;
-}
 
-impl Shareable for Scalar {
-    type F = WrappedScalar;
-
-    fn share(&self) -> Self::F {
-        WrappedScalar(*self)
+    /// x-coordinate of the share of clause `i`, i.e.
+    /// `i + 1`
+    #[deprecated(note = "use `ClauseId::to_share_x`")]
+    fn index_to_field(i: usize) -> Self::F {
+        ClauseId(i).to_share_x()
     }
 
-    fn derive(elem: Self::F) -> Self {
-        elem.0
+    /// Clause index of the share at x-coordinate `x`, the
+    /// inverse of [`Shareable::index_to_field`]. Fails with
+    /// [`Error::InvalidIndex`] if `x` is 0 or does not fit a
+    /// `usize`.
+    #[deprecated(note = "use `ClauseId::from_share_x`")]
+    fn field_to_index(x: Self::F) -> Result<usize, Error> {
+        ClauseId::from_share_x::<Self>(x)
+            .map(ClauseId::index)
     }
+}
+
+impl Shareable for Scalar {
+    type F = WrappedScalar;
 
     fn to_usize(elem: Self::F) -> usize {
         usize::from_le_bytes(
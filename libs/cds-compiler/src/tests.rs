@@ -5,8 +5,10 @@ use rand_chacha::ChaCha20Rng;
 use rand_core::SeedableRng;
 
 use crate::selfcompiler::{
-    SelfCompiler94, Statement94, Witness94,
+    CompiledZ94, Proof94, SelfCompiler94, SimulationCache,
+    State94, Statement94, Witness94,
 };
+use crate::shareable::Shareable;
 
 use super::*;
 
@@ -127,6 +129,34 @@ fn first_message_works() {
     assert!(testc == message_a[0]);
 }
 
+#[test]
+fn first_message_advances_rng() {
+    const N: usize = 4;
+    const D: usize = 2;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        _verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    // The prover RNG is advanced by the first round, so
+    // proving twice never reuses a nonce
+    let (_, message_a1) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let (_, message_a2) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    assert_ne!(message_a1[0], message_a2[0]);
+}
+
 #[test]
 fn third_message_works() {
     const N: usize = 3;
@@ -200,6 +230,56 @@ fn cds_works() {
     ));
 }
 
+#[test]
+fn cds_works_when_all_clauses_active() {
+    const N: usize = 4;
+    const D: usize = N;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &proof
+    ));
+}
+
+#[test]
+#[should_panic(expected = "threshold must be between 1")]
+fn zero_threshold_panics() {
+    SelfCompiler94::<Schnorr>::new(4, 0);
+}
+
+#[test]
+#[should_panic(expected = "threshold must be between 1")]
+fn threshold_above_clauses_panics() {
+    Statement94::<Schnorr>::new(
+        4,
+        5,
+        vec![Schnorr::default(); 4],
+    );
+}
+
 #[test]
 fn cds_fails() {
     // INIT //
@@ -237,6 +317,125 @@ fn cds_fails() {
     ));
 }
 
+#[test]
+fn verify_batch_matches_verify() {
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let proofs: Vec<Proof94<Schnorr>> = (0..10)
+        .map(|i| {
+            let (state, a) = SelfCompiler94::first(
+                &statement,
+                &provers_witnesses,
+                &mut provers_rng,
+            );
+            let c = SelfCompiler94::<Schnorr>::second(
+                &mut verifiers_rng,
+            );
+            let z = SelfCompiler94::third(
+                &statement,
+                state,
+                &provers_witnesses,
+                &c,
+                &mut provers_rng,
+            );
+            // Every third proof answers another challenge
+            if i % 3 == 0 {
+                let other =
+                    SelfCompiler94::<Schnorr>::second(
+                        &mut verifiers_rng,
+                    );
+                (a, other, z)
+            } else {
+                (a, c, z)
+            }
+        })
+        .collect();
+
+    // Malformed proofs built from an honest one
+    let (a, c, z) = proofs[1].clone();
+    let mut repeated = z.clone();
+    repeated[1] = repeated[0].clone();
+    let mut missing = z.clone();
+    missing.pop();
+    let mut extra = a.clone();
+    extra.push(a[0]);
+    // The last clause of another proof answering the same
+    // challenge: the clause transcript verifies, but its
+    // share lies on another polynomial
+    let (state, other_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let other_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &c,
+        &mut provers_rng,
+    );
+    let last = *z
+        .last()
+        .unwrap()
+        .pattern_match()
+        .0;
+    let mut spliced_a = a.clone();
+    spliced_a[last.index()] = other_a[last.index()];
+    let mut spliced_z = z.clone();
+    *spliced_z
+        .last_mut()
+        .unwrap() = other_z
+        .into_iter()
+        .find(|zi| {
+            *zi.pattern_match()
+                .0
+                == last
+        })
+        .unwrap();
+    let adversarial: Vec<Proof94<Schnorr>> = vec![
+        (a.clone(), c, repeated),
+        (a.clone(), c, missing),
+        (extra, c, z.clone()),
+        (spliced_a, c, spliced_z),
+    ];
+    for (a, c, z) in &adversarial {
+        assert!(!SelfCompiler94::verify(
+            &statement, a, c, z
+        ));
+    }
+    let proofs = proofs
+        .into_iter()
+        .chain(adversarial)
+        .collect_vec();
+
+    let expected = proofs
+        .iter()
+        .map(|(a, c, z)| {
+            SelfCompiler94::verify(&statement, a, c, z)
+        })
+        .collect_vec();
+    assert_eq!(
+        expected
+            .iter()
+            .filter(|v| **v)
+            .count(),
+        6
+    );
+    assert_eq!(
+        SelfCompiler94::verify_batch(&statement, &proofs),
+        expected
+    );
+}
+
 #[test]
 fn cds_hvzk_works() {
     const N: usize = 64;
@@ -249,3 +448,1245 @@ fn cds_hvzk_works() {
 
     assert!(SelfCompiler94::verify(&statement, &a, &c, &z));
 }
+
+#[test]
+fn all_active_clauses_prove_a_conjunction() {
+    const N: usize = 16;
+    const D: usize = 16;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    assert!(state
+        .challenges()
+        .iter()
+        .all(Option::is_none));
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+
+    // Every clause is answered with the challenge itself,
+    // which is what interpolating the constant polynomial
+    // would give
+    let debug_shares = SelfCompiler94::debug_shares(
+        &statement, &state, &challenge,
+    );
+    let shamir = ShamirSecretSharing {
+        threshold: 1,
+        shares: N,
+    };
+    let xs = debug_shares
+        .iter()
+        .map(|(i, _)| i.to_share_x())
+        .collect_vec();
+    let interpolated = shamir
+        .complete_shares(&challenge.share(), &vec![], &xs)
+        .unwrap();
+    for ((_, ci), share) in debug_shares
+        .iter()
+        .zip(interpolated)
+    {
+        assert_eq!(*ci, challenge);
+        assert_eq!(ci.share(), share.y);
+    }
+
+    let message_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &message_z
+    ));
+}
+
+#[test]
+fn out_of_range_active_clause_is_not_counted() {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let secrets: Vec<Scalar> = (0..2)
+        .map(|_| Scalar::random(rng))
+        .collect();
+    let statement = Statement94::<Schnorr>::new(
+        2,
+        2,
+        secrets
+            .iter()
+            .map(|x| Schnorr::init(*x))
+            .collect(),
+    );
+    // As many active clauses as clauses, but one of them
+    // does not exist
+    let witness =
+        Witness94::new(secrets, HashSet::from([0, 5]));
+
+    let (state, _) =
+        SelfCompiler94::first(&statement, &witness, rng);
+    assert!(state.challenges()[0].is_none());
+    assert!(state.challenges()[1].is_some());
+}
+
+#[test]
+fn schnorr_challenge_shares_through_wrapped_scalar() {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let challenge = Scalar::random(rng);
+    let share = challenge.share();
+    assert_eq!(share.0, challenge);
+    assert_eq!(Scalar::derive(share), challenge);
+    let five = Scalar::from(5u64);
+    assert_eq!(Scalar::to_usize(five.share()), 5);
+}
+
+#[test]
+fn three_of_five_schnorr_disjunction() {
+    const N: usize = 5;
+    const D: usize = 3;
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let secrets: Vec<Scalar> = (0..N)
+        .map(|_| Scalar::random(rng))
+        .collect();
+    let statements = secrets
+        .iter()
+        .map(|w| Schnorr::init(*w))
+        .collect_vec();
+    let statement =
+        Statement94::<Schnorr>::new(N, D, statements);
+
+    // The prover knows the secrets of clauses 0, 2 and 4
+    // only, and puts random witnesses in the other clauses
+    let active_clauses: HashSet<usize> = [0, 2, 4]
+        .into_iter()
+        .collect();
+    let witnesses = (0..N)
+        .map(|i| {
+            if active_clauses.contains(&i) {
+                secrets[i]
+            } else {
+                Scalar::random(rng)
+            }
+        })
+        .collect_vec();
+    let witness = Witness94::new(witnesses, active_clauses);
+
+    let (state, message_a) =
+        SelfCompiler94::first(&statement, &witness, rng);
+    let challenge = SelfCompiler94::<Schnorr>::second(rng);
+    let message_z = SelfCompiler94::third(
+        &statement, state, &witness, &challenge, rng,
+    );
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &message_z
+    ));
+
+    // The same proof does not verify a different challenge
+    let other = SelfCompiler94::<Schnorr>::second(rng);
+    assert!(!SelfCompiler94::verify(
+        &statement, &message_a, &other, &message_z
+    ));
+}
+
+#[test]
+fn try_third_reports_sharing_error() {
+    use sigmazk::error::Error;
+
+    const N: usize = 4;
+    const D: usize = 2;
+    let (
+        _protocol,
+        statement,
+        actual_witnesses,
+        _provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    // One active clause too many leaves too few simulated
+    // challenges to complete the sharing
+    let witness = Witness94::new(
+        actual_witnesses
+            .witnesses()
+            .clone(),
+        (0..D + 1).collect(),
+    );
+    let (state, _message_a) = SelfCompiler94::first(
+        &statement,
+        &witness,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    assert!(matches!(
+        SelfCompiler94::try_third(
+            &statement,
+            state,
+            &witness,
+            &challenge,
+            &mut provers_rng,
+        ),
+        Err(Error::SecretSharing)
+    ));
+}
+
+#[test]
+fn try_third_rejects_incomplete_conjunction() {
+    use sigmazk::error::Error;
+
+    const N: usize = 3;
+    let (
+        _protocol,
+        statement,
+        actual_witnesses,
+        _provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, N>(true);
+
+    // The threshold asks for every clause, but the witness
+    // only covers two of them
+    let witness = Witness94::new(
+        actual_witnesses
+            .witnesses()
+            .clone(),
+        HashSet::from([0, 1]),
+    );
+    let (state, _message_a) = SelfCompiler94::first(
+        &statement,
+        &witness,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    assert!(matches!(
+        SelfCompiler94::try_third(
+            &statement,
+            state,
+            &witness,
+            &challenge,
+            &mut provers_rng,
+        ),
+        Err(Error::SecretSharing)
+    ));
+}
+
+#[test]
+fn cached_and_fresh_simulations_verify() {
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    // The last inactive clause is left out of the cache and
+    // is simulated while proving
+    let mut cache = SimulationCache::new(N);
+    cache
+        .fill(&statement, D..N - 1, 2)
+        .unwrap();
+    assert_eq!(cache.available(D), 2);
+    assert_eq!(cache.available(N - 1), 0);
+    // A clause out of range fills nothing
+    assert!(matches!(
+        cache.fill(&statement, [D, N], 1),
+        Err(sigmazk::error::Error::InvalidIndex)
+    ));
+    assert_eq!(cache.available(D), 2);
+
+    let mut first_messages = Vec::new();
+    for cached in [true, true, true, false] {
+        let (state, message_a) = if cached {
+            SelfCompiler94::first_cached(
+                &statement,
+                &provers_witnesses,
+                &mut cache,
+                &mut provers_rng,
+            )
+        } else {
+            SelfCompiler94::first(
+                &statement,
+                &provers_witnesses,
+                &mut provers_rng,
+            )
+        };
+        let challenge = SelfCompiler94::<Schnorr>::second(
+            &mut verifiers_rng,
+        );
+        let message_z = SelfCompiler94::third(
+            &statement,
+            state,
+            &provers_witnesses,
+            &challenge,
+            &mut provers_rng,
+        );
+        assert!(SelfCompiler94::verify(
+            &statement, &message_a, &challenge, &message_z
+        ));
+        first_messages.push(message_a);
+    }
+
+    // Two proofs used up the cache, the third simulated
+    // afresh, and no simulated transcript was used twice
+    assert_eq!(cache.available(D), 0);
+    for i in D..N {
+        assert_ne!(
+            first_messages[0][i],
+            first_messages[1][i]
+        );
+        assert_ne!(
+            first_messages[1][i],
+            first_messages[2][i]
+        );
+    }
+}
+
+#[test]
+fn verify_decompressed_rejects_malformed_commitment() {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use sigmazk::error::Error;
+
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, mut message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    let message_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+    assert!(SelfCompiler94::verify_decompressed(
+        &statement, &message_a, &challenge, &message_z
+    )
+    .unwrap());
+
+    message_a[N - 1] = CompressedRistretto([0xff; 32]);
+    assert!(matches!(
+        SelfCompiler94::verify_decompressed(
+            &statement, &message_a, &challenge, &message_z
+        ),
+        Err(Error::InvalidPoint)
+    ));
+    assert!(!SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &message_z
+    ));
+}
+
+#[test]
+fn verify_rejects_out_of_range_clause() {
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    let message_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    // Responses for clauses 4 to 7 against a statement of
+    // the first four clauses only
+    let half = Statement94::<Schnorr>::new(
+        N / 2,
+        1,
+        statement.statements()[..N / 2].to_vec(),
+    );
+    let out_of_range = message_z
+        .iter()
+        .filter(|z| {
+            z.pattern_match()
+                .0
+                .index()
+                >= N / 2
+        })
+        .cloned()
+        .collect_vec();
+    assert!(!SelfCompiler94::verify_decompressed(
+        &half,
+        &message_a[..N / 2],
+        &challenge,
+        &out_of_range
+    )
+    .unwrap());
+    assert!(!SelfCompiler94::verify(
+        &half,
+        &message_a[..N / 2].to_vec(),
+        &challenge,
+        &out_of_range
+    ));
+}
+
+#[test]
+fn debug_shares_reconstruct_challenge() {
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, _message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+
+    let debug_shares = SelfCompiler94::debug_shares(
+        &statement, &state, &challenge,
+    );
+    assert_eq!(debug_shares.len(), N);
+
+    // Simulated clauses keep their first round challenge
+    for (i, ci) in &debug_shares {
+        if let Some(c) = &state.challenges()[i.index()] {
+            assert_eq!(c, ci);
+        }
+    }
+
+    let shares = debug_shares
+        .iter()
+        .map(|(i, ci)| {
+            Share::new(i.to_share_x(), ci.share())
+        })
+        .collect_vec();
+    let shamir = ShamirSecretSharing {
+        threshold: N - D + 1,
+        shares: N,
+    };
+    let secret = shamir
+        .reconstruct_secret(&shares)
+        .unwrap();
+    assert_eq!(secret, challenge.share());
+}
+
+#[test]
+fn state_iterates_clause_triples() {
+    const N: usize = 3;
+    const D: usize = 1;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        _verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, _message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+
+    let clauses = state
+        .iter()
+        .collect_vec();
+    assert_eq!(clauses.len(), N);
+
+    // Clause 0 is active, the others are simulated
+    let (inner_state, c, z) = clauses[0];
+    assert_eq!(
+        inner_state,
+        state.inner_states()[0].as_ref()
+    );
+    assert!(c.is_none() && z.is_none());
+    for (i, (inner_state, c, z)) in clauses
+        .into_iter()
+        .enumerate()
+        .skip(1)
+    {
+        assert!(inner_state.is_none());
+        assert_eq!(c, state.challenges()[i].as_ref());
+        assert_eq!(z, state.zs()[i].as_ref());
+        assert!(c.is_some() && z.is_some());
+    }
+
+    assert_eq!(
+        (&state)
+            .into_iter()
+            .count(),
+        N
+    );
+}
+
+#[test]
+#[should_panic(expected = "must have the same length")]
+fn state_rejects_mismatched_lengths() {
+    State94::<Schnorr>::new(
+        vec![None, None],
+        vec![None],
+        vec![None, None],
+    );
+}
+
+#[test]
+fn statement_clone_shares_clauses() {
+    let statements = (0..1000u64)
+        .map(|i| Schnorr::init(Scalar::from(i + 1)))
+        .collect();
+    let statement =
+        Statement94::<Schnorr>::new(1000, 1, statements);
+    let cloned = statement.clone();
+    assert!(std::ptr::eq(
+        statement
+            .statements()
+            .as_ptr(),
+        cloned
+            .statements()
+            .as_ptr()
+    ));
+}
+
+#[test]
+fn statement_labels_survive_clone() {
+    let witnesses: Vec<Scalar> = (1..=3u64)
+        .map(Scalar::from)
+        .collect();
+    let statements = witnesses
+        .iter()
+        .map(|w| Schnorr::init(*w))
+        .collect();
+    let labels = ["alice", "bob", "carol"]
+        .iter()
+        .map(|l| {
+            l.as_bytes()
+                .into()
+        })
+        .collect();
+    let statement =
+        Statement94::<Schnorr>::new(3, 1, statements)
+            .with_labels(labels);
+    let cloned = statement.clone();
+
+    assert_eq!(cloned.label_at(1), Some("bob".as_bytes()));
+    assert_eq!(cloned.label_at(3), None);
+    for (i, w) in witnesses
+        .iter()
+        .enumerate()
+    {
+        assert_eq!(
+            cloned.statements()[i],
+            Schnorr::init(*w)
+        );
+        assert_eq!(
+            cloned.label_at(i),
+            statement.label_at(i)
+        );
+    }
+    let unlabelled = Statement94::<Schnorr>::new(
+        1,
+        1,
+        vec![Schnorr::init(Scalar::ONE)],
+    );
+    assert_eq!(unlabelled.label_at(0), None);
+}
+
+#[test]
+fn third_streaming_matches_third() {
+    const N: usize = 16;
+    const D: usize = 4;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng.clone(),
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+
+    let proof = SelfCompiler94::third(
+        &statement,
+        state.clone(),
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng.clone(),
+    );
+    let streamed = SelfCompiler94::third_streaming(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    )
+    .collect_vec();
+
+    let encode = |z: &Vec<CompiledZ94<Schnorr>>| {
+        let mut bytes = Vec::new();
+        for zi in z {
+            zi.write(&mut bytes);
+        }
+        bytes
+    };
+    assert_eq!(encode(&streamed), encode(&proof));
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &streamed
+    ));
+}
+
+#[test]
+fn verify_bounded_rejects_oversized_proof() {
+    use sigmazk::error::Error;
+
+    const N: usize = 16;
+    const D: usize = 4;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng.clone(),
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    assert!(matches!(
+        SelfCompiler94::verify_bounded(
+            &statement, &message_a, &challenge, &proof, N
+        ),
+        Ok(true)
+    ));
+
+    // More responses than the statement has clauses
+    let oversized = proof
+        .iter()
+        .cycle()
+        .take(1 << 16)
+        .cloned()
+        .collect_vec();
+    assert!(matches!(
+        SelfCompiler94::verify_bounded(
+            &statement, &message_a, &challenge, &oversized,
+            N
+        ),
+        Err(Error::ProofTooLarge)
+    ));
+
+    // More clauses than the verifier accepts
+    assert!(matches!(
+        SelfCompiler94::verify_bounded(
+            &statement,
+            &message_a,
+            &challenge,
+            &proof,
+            N - 1
+        ),
+        Err(Error::ProofTooLarge)
+    ));
+}
+
+#[test]
+fn statement_digest_distinguishes_statements() {
+    use sigmazk::StatementDigest;
+
+    let statements: Vec<Schnorr> = (1..=4u64)
+        .map(|i| Schnorr::init(Scalar::from(i)))
+        .collect();
+    let statement = Statement94::<Schnorr>::new(
+        4,
+        2,
+        statements.clone(),
+    );
+    assert_eq!(
+        statement.digest(),
+        Statement94::<Schnorr>::new(
+            4,
+            2,
+            statements.clone()
+        )
+        .digest()
+    );
+
+    // Different threshold
+    assert_ne!(
+        statement.digest(),
+        Statement94::<Schnorr>::new(
+            4,
+            3,
+            statements.clone()
+        )
+        .digest()
+    );
+
+    // Different clause
+    let mut other = statements;
+    other[0] = Schnorr::init(Scalar::from(5u64));
+    assert_ne!(
+        statement.digest(),
+        Statement94::<Schnorr>::new(4, 2, other).digest()
+    );
+}
+
+#[test]
+fn witness_try_new_validates() {
+    use sigmazk::error::Error;
+
+    let witnesses = vec![Scalar::ONE; 4];
+
+    assert!(Witness94::<Schnorr>::try_new(
+        4,
+        witnesses.clone(),
+        HashSet::from([0, 2]),
+    )
+    .is_ok());
+
+    // One witness short
+    assert!(matches!(
+        Witness94::<Schnorr>::try_new(
+            4,
+            witnesses[..3].to_vec(),
+            HashSet::from([0]),
+        ),
+        Err(Error::WitnessLengthMismatch)
+    ));
+
+    // The prover knows no witness
+    assert!(matches!(
+        Witness94::<Schnorr>::try_new(
+            4,
+            witnesses.clone(),
+            HashSet::new(),
+        ),
+        Err(Error::InvalidActiveClauses)
+    ));
+
+    // Active clause out of range
+    assert!(matches!(
+        Witness94::<Schnorr>::try_new(
+            4,
+            witnesses,
+            HashSet::from([4]),
+        ),
+        Err(Error::InvalidActiveClauses)
+    ));
+}
+
+#[test]
+fn witness_debug_redacts_witnesses() {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let secret = Scalar::random(rng);
+    let witness = Witness94::<Schnorr>::new(
+        vec![secret; 3],
+        HashSet::from([1]),
+    );
+
+    let debug = format!("{:?}", witness);
+    assert!(debug.contains("<3 redacted>"));
+    assert!(!debug.contains(&format!("{:?}", secret)));
+    assert!(!debug
+        .contains(&format!("{:?}", secret.as_bytes())));
+}
+
+#[test]
+#[allow(deprecated)]
+fn index_conversions_delegate_to_clause_id() {
+    use crate::clause::ClauseId;
+
+    for i in 0..100 {
+        let x = Scalar::index_to_field(i);
+        assert_eq!(x, ClauseId(i).to_share_x());
+        assert_eq!(Scalar::field_to_index(x).unwrap(), i);
+    }
+    assert!(Scalar::field_to_index(Scalar::ZERO.share())
+        .is_err());
+}
+
+#[test]
+fn clause_id_share_x_round_trip() {
+    use sigmazk::error::Error;
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    use crate::clause::ClauseId;
+
+    assert_eq!(
+        ClauseId(0).to_share_x::<WrappedScalar>(),
+        WrappedScalar(Scalar::ONE)
+    );
+    for i in 0..1000 {
+        let x: WrappedScalar = ClauseId(i).to_share_x();
+        assert_eq!(
+            ClauseId::from_share_x::<Scalar>(x).unwrap(),
+            ClauseId(i)
+        );
+    }
+
+    // The secret's x-coordinate is not a clause
+    assert!(matches!(
+        ClauseId::from_share_x::<Scalar>(WrappedScalar(
+            Scalar::ZERO
+        )),
+        Err(Error::InvalidIndex)
+    ));
+    // Does not fit a usize
+    assert!(matches!(
+        ClauseId::from_share_x::<Scalar>(WrappedScalar(
+            -Scalar::ONE
+        )),
+        Err(Error::InvalidIndex)
+    ));
+}
+
+#[test]
+fn share_check_matches_shamir() {
+    use shamir_ss::shamir::{ShamirSecretSharing, Share};
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    use crate::clause::ClauseId;
+    use crate::selfcompiler::ShareCheck;
+
+    const N: usize = 16;
+    const D: usize = 5;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    let message_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &message_z
+    ));
+
+    let shares: Vec<Share<WrappedScalar>> = message_z
+        .iter()
+        .map(|z| {
+            let (i, c, _) = z.pattern_match();
+            Share {
+                x: i.to_share_x(),
+                y: c.share(),
+            }
+        })
+        .sorted_by_key(|share| {
+            ClauseId::from_share_x::<Scalar>(share.x)
+                .unwrap()
+        })
+        .collect();
+    let mut ys = shares
+        .iter()
+        .map(|share| share.y)
+        .collect_vec();
+    let shamir = ShamirSecretSharing {
+        threshold: N - D + 1,
+        shares: N,
+    };
+    let expected = shamir
+        .reconstruct_secret_checked(&shares)
+        .unwrap();
+
+    let check = ShareCheck::new(N, D);
+    assert_eq!(check.secret(&ys), Some(expected));
+    assert_eq!(expected, challenge.share());
+    // A share beyond the threshold off the polynomial
+    ys[N - 1] += WrappedScalar(Scalar::ONE);
+    assert_eq!(check.secret(&ys), None);
+}
+
+#[test]
+fn cds_works_when_only_clause_zero_active() {
+    use crate::clause::ClauseId;
+
+    const N: usize = 5;
+    const D: usize = 1;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<N, D>(true);
+    assert_eq!(
+        provers_witnesses.active_clauses(),
+        &HashSet::from([0])
+    );
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    let message_z = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    // Every clause answers exactly once, clause 0 included
+    let ids: HashSet<ClauseId> = message_z
+        .iter()
+        .map(|z| {
+            *z.pattern_match()
+                .0
+        })
+        .collect();
+    assert_eq!(
+        ids,
+        (0..N)
+            .map(ClauseId)
+            .collect()
+    );
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &message_z
+    ));
+}
+
+#[test]
+fn verify_partial_reaches_threshold() {
+    use crate::selfcompiler::PartialResult;
+
+    const N: usize = 8;
+    const D: usize = 3;
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<N, D>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    // Clauses arrive one at a time
+    let threshold = N - D + 1;
+    let mut received = Vec::new();
+    for (k, z) in proof
+        .iter()
+        .enumerate()
+    {
+        let (i, c, z) = z.pattern_match();
+        received.push((*i, message_a[i.index()], *c, *z));
+        // A duplicate does not count twice
+        received.push((*i, message_a[i.index()], *c, *z));
+
+        let result = SelfCompiler94::verify_partial(
+            &statement, &challenge, &received,
+        );
+        assert_eq!(
+            result,
+            PartialResult {
+                valid_clauses: k + 1,
+                needed_for_threshold: threshold
+                    .saturating_sub(k + 1),
+                reconstructable: k + 1 >= threshold,
+            }
+        );
+    }
+
+    // A forged clause is not counted
+    let (i, c, z) = proof[0].pattern_match();
+    let forged =
+        [(*i, message_a[i.index()], *c, z + Scalar::ONE)];
+    let result = SelfCompiler94::verify_partial(
+        &statement, &challenge, &forged,
+    );
+    assert_eq!(result.valid_clauses, 0);
+    assert!(!result.reconstructable);
+
+    // A valid transcript of the last clause whose share
+    // lies off the polynomial of the others
+    let (i, ..) = received
+        .pop()
+        .unwrap();
+    let (a, c, z) = <Schnorr as HVzk>::simulate(
+        &statement.statements()[i.index()],
+    );
+    received.pop();
+    received.push((i, a, c, z));
+    let result = SelfCompiler94::verify_partial(
+        &statement, &challenge, &received,
+    );
+    assert_eq!(result.valid_clauses, N);
+    assert!(!result.reconstructable);
+}
+
+#[test]
+fn challenge_field_round_trip() {
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    for _ in 0..100 {
+        let c = Scalar::random(rng);
+        assert_eq!(Scalar::derive(c.share()), c);
+        assert_eq!(Scalar::from(WrappedScalar::from(c)), c);
+        assert_eq!(c.share(), WrappedScalar(c));
+    }
+}
+
+#[test]
+fn cds_simulate_self_test() {
+    let (_protocol, statement, ..) =
+        test_init::<8, 3>(true);
+    assert!(SelfCompiler94::<Schnorr>::simulate_self_test(
+        &statement
+    ));
+}
+
+#[test]
+fn cds_works_with_scalar_challenges() {
+    use sigmazk::Challenge;
+
+    // Schnorr composes directly with bare Scalar challenges
+    fn assert_scalar_challenge<
+        S: Composable + SigmaProtocol<Challenge = Scalar>,
+    >() {
+    }
+    assert_scalar_challenge::<Schnorr>();
+
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        _verifiers_rng,
+    ) = test_init::<4, 2>(true);
+
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = <Scalar as Challenge>::new(&[7u8; 64]);
+    assert_eq!(
+        challenge,
+        Scalar::from_bytes_mod_order_wide(&[7u8; 64])
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &proof
+    ));
+}
+
+#[test]
+fn counting_writer_matches_encoded_length() {
+    use sigmazk::io::CountingWriter;
+
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        verifiers_rng,
+    ) = test_init::<4, 2>(true);
+    let (state, _message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng.clone(),
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    for zi in &proof {
+        let mut counter = CountingWriter::new();
+        let mut bytes = Vec::new();
+        zi.write(&mut counter);
+        zi.write(&mut bytes);
+        assert_eq!(counter.count(), bytes.len());
+        assert_eq!(zi.size(), bytes.len());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn cds_serde_round_trip() {
+    let (
+        _protocol,
+        statement,
+        _actual_witnesses,
+        provers_witnesses,
+        mut provers_rng,
+        mut verifiers_rng,
+    ) = test_init::<4, 2>(true);
+    let (state, message_a) = SelfCompiler94::first(
+        &statement,
+        &provers_witnesses,
+        &mut provers_rng,
+    );
+    let challenge = SelfCompiler94::<Schnorr>::second(
+        &mut verifiers_rng,
+    );
+    let proof = SelfCompiler94::third(
+        &statement,
+        state,
+        &provers_witnesses,
+        &challenge,
+        &mut provers_rng,
+    );
+
+    let proof: Proof94<Schnorr> =
+        (message_a, challenge, proof);
+    let json = serde_json::to_string(&(&statement, &proof))
+        .unwrap();
+    let (statement, (message_a, challenge, proof)): (
+        Statement94<Schnorr>,
+        Proof94<Schnorr>,
+    ) = serde_json::from_str(&json).unwrap();
+    assert!(SelfCompiler94::verify(
+        &statement, &message_a, &challenge, &proof
+    ));
+
+    // a threshold above the number of clauses is rejected
+    // instead of panicking later
+    let mut value =
+        serde_json::to_value(&statement).unwrap();
+    value["threshold"] = 5.into();
+    assert!(
+        serde_json::from_value::<Statement94<Schnorr>>(
+            value
+        )
+        .is_err()
+    );
+
+    // so is a statement missing one of its clauses
+    let mut value =
+        serde_json::to_value(&statement).unwrap();
+    value["statements"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    assert!(
+        serde_json::from_value::<Statement94<Schnorr>>(
+            value
+        )
+        .is_err()
+    );
+    assert!(matches!(
+        Statement94::<Schnorr>::try_new(
+            4,
+            2,
+            statement.statements()[..3].to_vec()
+        ),
+        Err(sigmazk::error::Error::StatementLengthMismatch)
+    ));
+}
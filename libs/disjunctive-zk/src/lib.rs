@@ -0,0 +1,37 @@
+//! Single entry point to the crates of this workspace, with
+//! every compiler behind a feature so that a user only
+//! compiles what they need.
+//!
+//! The core traits of [`sigmazk`] are always available. The
+//! features, all enabled by default, are:
+//!
+//! - `std`: without it [`sigmazk`] is `no_std` and only
+//!   needs `alloc`;
+//! - `schnorr`: Schnorr's protocol, [`sigmazk::Schnorr`];
+//! - `shamir`: Shamir secret sharing, [`shamir_ss`], which
+//!   enables `std`;
+//! - `cds`: the CDS94 compiler, [`cds_compiler`], which
+//!   enables `schnorr` and `shamir`;
+//! - `stacking`: the Stacking Sigmas compiler,
+//!   [`stacksig_compiler`], which enables `schnorr` and
+//!   `std`;
+//! - `speed-stacking`: the compressed stacking mechanism,
+//!   [`speed_stacking`], which enables `std`.
+//!
+//! E.g. Schnorr with Fiat-Shamir, also without `std`, only
+//! needs
+//!
+//! ```toml
+//! disjunctive-zk = { version = "0.1", default-features = false, features = ["schnorr"] }
+//! ```
+
+pub use sigmazk;
+
+#[cfg(feature = "cds")]
+pub use cds_compiler;
+#[cfg(feature = "shamir")]
+pub use shamir_ss;
+#[cfg(feature = "speed-stacking")]
+pub use speed_stacking;
+#[cfg(feature = "stacking")]
+pub use stacksig_compiler;
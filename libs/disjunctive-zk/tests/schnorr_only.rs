@@ -0,0 +1,71 @@
+//! Schnorr with Fiat-Shamir through the core traits only.
+//!
+//! The test runs with the default features, and is also
+//! the build test for the smallest useful configuration:
+//!
+//! ```bash
+//! cargo test -p disjunctive-zk --no-default-features --features schnorr
+//! ```
+//!
+//! With these features the compilers, Shamir secret sharing
+//! and their dependencies are not compiled at all, so this
+//! test only builds if Schnorr and the core traits do not
+//! depend on them. It is skipped without `schnorr`, see
+//! `required-features` in the manifest.
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use disjunctive_zk::sigmazk::{
+    Challenge, Schnorr, SigmaProtocol,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha512};
+
+/// Fiat-Shamir challenge bound to the statement and the
+/// first message
+fn challenge(
+    statement: &Schnorr,
+    a: &CompressedRistretto,
+) -> Scalar {
+    let mut hash = Sha512::new();
+    hash.update(
+        statement
+            .pub_key
+            .compress()
+            .as_bytes(),
+    );
+    hash.update(a.as_bytes());
+    <Scalar as Challenge>::new(
+        &hash
+            .finalize()
+            .into(),
+    )
+}
+
+#[test]
+fn schnorr_fiat_shamir_without_compilers() {
+    let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+    let witness = Scalar::random(rng);
+    let statement = Schnorr::init(witness);
+
+    let (state, a) =
+        Schnorr::first(&statement, &witness, rng);
+    let c = challenge(&statement, &a);
+    let z = Schnorr::third(
+        &statement, state, &witness, &c, rng,
+    );
+
+    assert!(Schnorr::verify(
+        &statement,
+        &a,
+        &challenge(&statement, &a),
+        &z
+    ));
+    assert!(!Schnorr::verify(
+        &statement,
+        &a,
+        &(c + Scalar::ONE),
+        &z
+    ));
+}
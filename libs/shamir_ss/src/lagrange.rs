@@ -59,6 +59,89 @@ impl<F: PrimeField> LagrangePolynomial<F> {
         )
     }
 
+    /// Degree of the interpolated polynomial, i.e. the
+    /// index of its highest nonzero coefficient in
+    /// [`LagrangePolynomial::coefficients`]. Constant
+    /// polynomials, including zero, have degree 0.
+    pub fn degree(&self) -> usize {
+        self.coefficients()
+            .iter()
+            .rposition(|c| !bool::from(c.is_zero()))
+            .unwrap_or(0)
+    }
+
+    /// Coefficients of the interpolated polynomial in the
+    /// monomial basis, from the constant term up. There is
+    /// one coefficient per point, the leading ones may be
+    /// zero.
+    pub fn coefficients(&self) -> Vec<F> {
+        let xs = &self.x_coordinates;
+        let n = xs.len();
+
+        // master[k] is the coefficient of x^k in
+        // (x - x_0) * ... * (x - x_{n-1})
+        let mut master = vec![F::ZERO; n + 1];
+        master[0] = F::ONE;
+        for (d, xj) in xs
+            .iter()
+            .enumerate()
+        {
+            for k in (1..=d + 1).rev() {
+                master[k] = master[k - 1] - *xj * master[k];
+            }
+            master[0] = -(*xj * master[0]);
+        }
+
+        // The numerator of the i-th Lagrange basis
+        // polynomial is master / (x - x_i), by synthetic
+        // division
+        let numerator = |xi: &F| {
+            let mut q = vec![F::ZERO; n];
+            let mut carry = F::ZERO;
+            for k in (0..n).rev() {
+                carry = master[k + 1] + *xi * carry;
+                q[k] = carry;
+            }
+            q
+        };
+
+        // and its denominator is the numerator at x_i
+        let mut denoms: Vec<F> = xs
+            .iter()
+            .map(|xi| evaluate(&numerator(xi), *xi))
+            .collect();
+        batch_invert(&mut denoms);
+
+        let mut coefficients = vec![F::ZERO; n];
+        for ((xi, yi), denom_inv) in xs
+            .iter()
+            .zip(&self.y_coordinates)
+            .zip(denoms)
+        {
+            let scale = *yi * denom_inv;
+            for (c, q) in coefficients
+                .iter_mut()
+                .zip(numerator(xi))
+            {
+                *c += scale * q;
+            }
+        }
+        coefficients
+    }
+
+    /// Evaluate the interpolated polynomial at every point
+    /// of `xs`.
+    ///
+    /// The polynomial is converted to the monomial basis
+    /// once, so each evaluation costs O(n) instead of the
+    /// O(n^2) of [`LagrangePolynomial::interpolate`].
+    pub fn evaluate_at_many(&self, xs: &[F]) -> Vec<F> {
+        let coefficients = self.coefficients();
+        xs.iter()
+            .map(|x| evaluate(&coefficients, *x))
+            .collect()
+    }
+
     /// Interpolate a polynomial using the given points
     pub fn lagrange_interpolation<S>(
         xs: &[F],
@@ -88,12 +171,117 @@ impl<F: PrimeField> LagrangePolynomial<F> {
         }
         result
     }
+
+    /// Interpolate a polynomial using the given points,
+    /// inverting all the denominators of the Lagrange
+    /// weights at once with [`batch_invert`].
+    ///
+    /// This costs a single field inversion instead of one per
+    /// point.
+    pub fn lagrange_interpolation_batched<S>(
+        xs: &[F],
+        ys: &[S],
+        x: F,
+    ) -> S
+    where
+        S: Default + Copy + AddAssign + Mul<F, Output = S>,
+    {
+        let mut result = S::default();
+        for (y, coeff) in ys
+            .iter()
+            .zip(Self::lagrange_coefficients(xs, x))
+        {
+            result += *y * coeff;
+        }
+        result
+    }
+
+    /// The Lagrange basis polynomials for `xs` evaluated at
+    /// `at`, i.e. the coefficient `λ_i` of each y-coordinate
+    /// such that `p(at) = Σ λ_i · y_i`.
+    ///
+    /// The denominators are inverted at once with
+    /// [`batch_invert`].
+    pub fn lagrange_coefficients(
+        xs: &[F],
+        at: F,
+    ) -> Vec<F> {
+        let (nums, mut denoms): (Vec<F>, Vec<F>) = xs
+            .iter()
+            .enumerate()
+            .map(|(i, xi)| {
+                let mut num = F::ONE;
+                let mut denom = F::ONE;
+                for (j, xj) in xs
+                    .iter()
+                    .enumerate()
+                {
+                    if i == j {
+                        continue;
+                    }
+                    num *= at - xj;
+                    denom *= *xi - xj;
+                }
+                (num, denom)
+            })
+            .unzip();
+        batch_invert(&mut denoms);
+
+        nums.into_iter()
+            .zip(denoms)
+            .map(|(num, denom_inv)| num * denom_inv)
+            .collect()
+    }
+}
+
+/// Evaluate the polynomial with the given monomial
+/// coefficients at `x` (Horner's rule)
+fn evaluate<F: PrimeField>(coefficients: &[F], x: F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::ZERO, |acc, c| acc * x + c)
+}
+
+/// Invert every element in place with a single field
+/// inversion (Montgomery's trick).
+///
+/// Panics if any element is zero.
+pub fn batch_invert<F: PrimeField>(elems: &mut [F]) {
+    // prefix[i] = elems[0] * ... * elems[i - 1]
+    let mut acc = F::ONE;
+    let prefix: Vec<F> = elems
+        .iter()
+        .map(|e| {
+            let p = acc;
+            acc *= e;
+            p
+        })
+        .collect();
+
+    // Invert the product of all elements and peel off one
+    // element at a time from the back
+    let mut inv = acc
+        .invert()
+        .unwrap();
+    for (e, p) in elems
+        .iter_mut()
+        .zip(prefix)
+        .rev()
+    {
+        let next = inv * *e;
+        *e = inv * p;
+        inv = next;
+    }
 }
 
 #[cfg(test)]
 mod polynomial_tests {
     use crate::lagrange::LagrangePolynomial;
     use curve25519_dalek::scalar::Scalar;
+    use group::ff::Field;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
     use wrapped_ristretto::scalar::WrappedScalar;
 
     #[test]
@@ -122,4 +310,115 @@ mod polynomial_tests {
 
         debug_assert_eq!(res, WrappedScalar::from(3u64));
     }
+
+    #[test]
+    fn degree_is_reported() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let secret = WrappedScalar::random(&mut rng);
+        for size in [1, 2, 5, 16] {
+            let poly = LagrangePolynomial::new(
+                secret, size, &mut rng,
+            );
+            assert_eq!(poly.degree(), size);
+        }
+
+        // Four points on the line y = 3x + 2
+        let xs: Vec<WrappedScalar> = (1..=4u64)
+            .map(WrappedScalar::from)
+            .collect();
+        let ys = xs
+            .iter()
+            .map(|x| {
+                *x * WrappedScalar::from(3u64)
+                    + WrappedScalar::from(2u64)
+            })
+            .collect();
+        let line = LagrangePolynomial::init(xs.clone(), ys)
+            .unwrap();
+        assert_eq!(line.degree(), 1);
+        assert_eq!(
+            line.coefficients(),
+            vec![
+                WrappedScalar::from(2u64),
+                WrappedScalar::from(3u64),
+                WrappedScalar::ZERO,
+                WrappedScalar::ZERO,
+            ]
+        );
+
+        let zero = LagrangePolynomial::init(
+            xs,
+            vec![WrappedScalar::ZERO; 4],
+        )
+        .unwrap();
+        assert_eq!(zero.degree(), 0);
+    }
+
+    #[test]
+    fn coefficients_match_evaluations() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let xs: Vec<WrappedScalar> = (0..12)
+            .map(|_| WrappedScalar::random(&mut rng))
+            .collect();
+        let ys: Vec<WrappedScalar> = (0..12)
+            .map(|_| WrappedScalar::random(&mut rng))
+            .collect();
+        let poly = LagrangePolynomial::init(
+            xs.clone(),
+            ys.clone(),
+        )
+        .unwrap();
+
+        let coefficients = poly.coefficients();
+        assert_eq!(coefficients.len(), 12);
+        let at = |x: WrappedScalar| {
+            coefficients
+                .iter()
+                .rev()
+                .fold(WrappedScalar::ZERO, |acc, c| {
+                    acc * x + c
+                })
+        };
+
+        // The polynomial passes through every point
+        assert_eq!(poly.evaluate_at_many(&xs), ys);
+        let points: Vec<WrappedScalar> = (0..8)
+            .map(|_| WrappedScalar::random(&mut rng))
+            .chain([WrappedScalar::ZERO])
+            .collect();
+        let many = poly.evaluate_at_many(&points);
+        for (x, y) in points
+            .iter()
+            .zip(many)
+        {
+            assert_eq!(at(*x), poly.interpolate(*x));
+            assert_eq!(y, poly.interpolate(*x));
+        }
+    }
+
+    #[test]
+    fn batched_interpolation_matches_naive() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let xs: Vec<WrappedScalar> = (1..=32u64)
+            .map(WrappedScalar::from)
+            .collect();
+        let ys: Vec<WrappedScalar> = (0..32)
+            .map(|_| WrappedScalar::random(&mut rng))
+            .collect();
+
+        for x in [
+            WrappedScalar::ZERO,
+            WrappedScalar::from(7u64),
+            WrappedScalar::random(&mut rng),
+        ] {
+            assert_eq!(
+                LagrangePolynomial::lagrange_interpolation_batched(
+                    &xs, &ys, x
+                ),
+                LagrangePolynomial::lagrange_interpolation(
+                    &xs, &ys, x
+                )
+            );
+        }
+    }
 }
@@ -1,4 +1,7 @@
 #![cfg_attr(coverage_nightly, feature(no_coverage))]
-mod lagrange;
+pub mod lagrange;
 pub mod shamir;
 pub mod shamir_error;
+pub mod weighted;
+
+pub use lagrange::LagrangePolynomial;
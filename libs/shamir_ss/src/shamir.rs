@@ -5,6 +5,10 @@ use crate::lagrange::LagrangePolynomial;
 use crate::shamir_error::ShamirError;
 
 #[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Share<F: PrimeField> {
     pub x: F,
     pub y: F,
@@ -16,6 +20,11 @@ impl<F: PrimeField> Share<F> {
     }
 }
 
+/// (threshold, shares) Shamir secret sharing.
+///
+/// With `threshold == shares` every share is needed to
+/// reconstruct the secret, i.e. the sharing acts as an AND
+/// over all shareholders.
 #[derive(Copy, Clone, Debug)]
 pub struct ShamirSecretSharing {
     pub threshold: usize,
@@ -23,6 +32,15 @@ pub struct ShamirSecretSharing {
 }
 
 impl ShamirSecretSharing {
+    /// Split `secret` into shares at the x-coordinates
+    /// `1, 2, ..., shares`.
+    ///
+    /// The x-coordinate `0` is reserved for the secret, the
+    /// intercept of the polynomial, and is never given out
+    /// as a share: it is the first of the `threshold`
+    /// interpolation points, and the shares start from the
+    /// second one. [`ShamirSecretSharing::complete_shares`]
+    /// rejects shares at `0` for the same reason.
     pub fn split_secret<F, R>(
         &self,
         secret: F,
@@ -70,10 +88,80 @@ impl ShamirSecretSharing {
                 y: poly.interpolate(F::from(i as u64)),
             })
         }
+        assert!(
+            shares
+                .iter()
+                .all(|share| !bool::from(
+                    share
+                        .x
+                        .is_zero()
+                )),
+            "x = 0 is reserved for the secret"
+        );
 
         Ok((poly, shares))
     }
 
+    /// Split `secret` into one share per x-coordinate in
+    /// `xs` instead of the default `1, 2, ..., shares`.
+    ///
+    /// The x-coordinates must be distinct and nonzero, and
+    /// there must be exactly `shares` of them. Fails with
+    /// [`ShamirError::InvalidThreshold`] if the threshold is
+    /// 0.
+    pub fn split_secret_at<F, R>(
+        &self,
+        secret: F,
+        xs: &[F],
+        rng: &mut R,
+    ) -> Result<Vec<Share<F>>, ShamirError>
+    where
+        F: PrimeField,
+        R: CryptoRngCore,
+    {
+        if xs.len() != self.shares {
+            return Err(ShamirError::InvalidXCoordinates);
+        }
+        for (i, x) in xs
+            .iter()
+            .enumerate()
+        {
+            if x.is_zero()
+                .into()
+                || xs[..i].contains(x)
+            {
+                return Err(
+                    ShamirError::InvalidXCoordinates,
+                );
+            }
+        }
+        if self.threshold == 0 {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        if self.threshold > self.shares {
+            return Err(ShamirError::NotEnoughShares);
+        }
+        // Random y-coordinates for the first t - 1 shares fix a
+        // random polynomial with intercept as secret
+        let (random_xs, remaining_xs) =
+            xs.split_at(self.threshold - 1);
+        let mut shares: Vec<Share<F>> = random_xs
+            .iter()
+            .map(|x| Share {
+                x: *x,
+                y: F::random(&mut *rng),
+            })
+            .collect();
+        let mut missing_shares = self.complete_shares(
+            &secret,
+            &shares,
+            &remaining_xs.to_vec(),
+        )?;
+        shares.append(&mut missing_shares);
+
+        Ok(shares)
+    }
+
     pub fn complete_shares_mut<F>(
         &self,
         secret: &F,
@@ -137,6 +225,10 @@ impl ShamirSecretSharing {
         Ok(())
     }
 
+    /// Complete an unqualified set of `threshold - 1` shares
+    /// with the shares at `remaining_xs`, given the secret.
+    ///
+    /// An empty `remaining_xs` returns an empty vector.
     pub fn complete_shares<F>(
         &self,
         secret: &F,
@@ -221,13 +313,121 @@ impl ShamirSecretSharing {
             .take(self.threshold)
             .unzip();
 
-        Ok(LagrangePolynomial::lagrange_interpolation(
+        Ok(LagrangePolynomial::lagrange_interpolation_batched(
             &xs,
             &ys,
             F::ZERO,
         ))
     }
 
+    /// Reconstructs secret from shares like
+    /// [`ShamirSecretSharing::reconstruct_secret`], and
+    /// checks that the shares beyond the first `threshold`
+    /// lie on the polynomial interpolated from those.
+    ///
+    /// Fails with [`ShamirError::InconsistentShares`] if
+    /// any of them does not, and with
+    /// [`ShamirError::InvalidXCoordinates`] if two shares
+    /// have the same x-coordinate or one has x = 0.
+    pub fn reconstruct_secret_checked<F>(
+        &self,
+        shares: &[Share<F>],
+    ) -> Result<F, ShamirError>
+    where
+        F: PrimeField,
+    {
+        if shares.len() < self.threshold {
+            return Err(ShamirError::NotEnoughShares);
+        }
+        check_xs(shares)?;
+        let (qualified, rest) =
+            shares.split_at(self.threshold);
+        let (xs, ys): (Vec<F>, Vec<F>) = qualified
+            .iter()
+            .map(|share| (share.x, share.y))
+            .unzip();
+        let poly = LagrangePolynomial::init(xs, ys)?;
+
+        if rest
+            .iter()
+            .any(|share| {
+                poly.interpolate(share.x) != share.y
+            })
+        {
+            return Err(ShamirError::InconsistentShares);
+        }
+        Ok(poly.interpolate(F::ZERO))
+    }
+
+    /// Reconstructs secret from shares of which up to
+    /// `max_errors` may be corrupt.
+    ///
+    /// Every subset of `threshold` shares is interpolated
+    /// until the polynomial agrees with all but at most
+    /// `max_errors` of the shares. This needs
+    /// `threshold + 2 * max_errors` shares, so that no
+    /// other polynomial agrees with as many, and takes up to
+    /// `shares.len()` choose `threshold` interpolations, so
+    /// it is meant for a few spare shares.
+    ///
+    /// Fails with [`ShamirError::NotEnoughShares`] if there
+    /// are too few shares for `max_errors`, and with
+    /// [`ShamirError::InconsistentShares`] if more than
+    /// `max_errors` of them are corrupt. Shares with a
+    /// repeated or zero x-coordinate are rejected up front
+    /// with [`ShamirError::InvalidXCoordinates`].
+    pub fn reconstruct_robust<F>(
+        &self,
+        shares: &[Share<F>],
+        max_errors: usize,
+    ) -> Result<F, ShamirError>
+    where
+        F: PrimeField,
+    {
+        if shares.len() < self.threshold + 2 * max_errors {
+            return Err(ShamirError::NotEnoughShares);
+        }
+        check_xs(shares)?;
+        let mut subset: Vec<usize> =
+            (0..self.threshold).collect();
+        loop {
+            let (xs, ys): (Vec<F>, Vec<F>) = subset
+                .iter()
+                .map(|i| (shares[*i].x, shares[*i].y))
+                .unzip();
+            let poly = LagrangePolynomial::init(xs, ys)?;
+            let errors = shares
+                .iter()
+                .filter(|share| {
+                    poly.interpolate(share.x) != share.y
+                })
+                .count();
+            if errors <= max_errors {
+                return Ok(poly.interpolate(F::ZERO));
+            }
+            if !next_subset(&mut subset, shares.len()) {
+                return Err(
+                    ShamirError::InconsistentShares,
+                );
+            }
+        }
+    }
+
+    /// Coefficients `λ_i` such that the secret is
+    /// `Σ λ_i · y_i` over the shares with x-coordinates `xs`
+    pub fn reconstruction_coefficients<F>(
+        &self,
+        xs: &[F],
+    ) -> Vec<F>
+    where
+        F: PrimeField,
+    {
+        LagrangePolynomial::lagrange_coefficients(
+            xs,
+            F::ZERO,
+        )
+    }
+
     /// Reconstructs secret with cached polynomial
     pub fn reconstruct_secret_fast<F>(
         &self,
@@ -240,15 +440,177 @@ impl ShamirSecretSharing {
     }
 }
 
+/// Fails with [`ShamirError::InvalidXCoordinates`] unless
+/// the x-coordinates of `shares` are distinct and nonzero,
+/// as interpolating through them requires
+fn check_xs<F: PrimeField>(
+    shares: &[Share<F>],
+) -> Result<(), ShamirError> {
+    for (i, share) in shares
+        .iter()
+        .enumerate()
+    {
+        if share
+            .x
+            .is_zero()
+            .into()
+            || shares[..i]
+                .iter()
+                .any(|other| other.x == share.x)
+        {
+            return Err(ShamirError::InvalidXCoordinates);
+        }
+    }
+    Ok(())
+}
+
+/// Advance `subset`, increasing indices below `n`, to the
+/// next subset of its size in lexicographic order. Returns
+/// `false` once every subset has been visited.
+fn next_subset(subset: &mut [usize], n: usize) -> bool {
+    let k = subset.len();
+    // Rightmost index that can still move right
+    let Some(i) =
+        (0..k).rposition(|i| subset[i] < n - k + i)
+    else {
+        return false;
+    };
+    subset[i] += 1;
+    for j in i + 1..k {
+        subset[j] = subset[j - 1] + 1;
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use curve25519_dalek::Scalar;
+    use group::ff::Field;
     use rand_chacha::ChaCha20Rng;
     use rand_core::SeedableRng;
     use wrapped_ristretto::scalar::WrappedScalar;
 
+    #[test]
+    fn split_secret_at_custom_xs() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = Scalar::random(&mut rng);
+        let shamir = ShamirSecretSharing {
+            threshold: 2,
+            shares: 3,
+        };
+        let xs = [5u64, 9, 12].map(WrappedScalar::from);
+
+        let shares = shamir
+            .split_secret_at(
+                WrappedScalar(sc),
+                &xs,
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(shares.len(), 3);
+        for (share, x) in shares
+            .iter()
+            .zip(xs)
+        {
+            assert_eq!(share.x, x);
+        }
+
+        // Any qualified subset recovers the secret
+        for subset in [[0, 1], [1, 2], [2, 0]] {
+            let subset = subset
+                .map(|i| shares[i])
+                .to_vec();
+            let res = shamir
+                .reconstruct_secret(&subset)
+                .unwrap();
+            assert_eq!(res.0, sc);
+        }
+    }
+
+    #[test]
+    fn split_secret_at_rejects_invalid_xs() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let secret = WrappedScalar::from(1u64);
+        let shamir = ShamirSecretSharing {
+            threshold: 2,
+            shares: 3,
+        };
+
+        for xs in
+            [vec![5u64, 9], vec![5, 9, 5], vec![0, 9, 12]]
+        {
+            let xs = xs
+                .into_iter()
+                .map(WrappedScalar::from)
+                .collect::<Vec<_>>();
+            assert!(matches!(
+                shamir
+                    .split_secret_at(secret, &xs, &mut rng),
+                Err(ShamirError::InvalidXCoordinates)
+            ));
+        }
+
+        let zero_threshold = ShamirSecretSharing {
+            threshold: 0,
+            shares: 3,
+        };
+        let xs = [5u64, 9, 12].map(WrappedScalar::from);
+        assert!(matches!(
+            zero_threshold
+                .split_secret_at(secret, &xs, &mut rng),
+            Err(ShamirError::InvalidThreshold)
+        ));
+    }
+
+    #[test]
+    fn threshold_equals_shares() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = WrappedScalar(Scalar::random(&mut rng));
+        let shamir = ShamirSecretSharing {
+            threshold: 3,
+            shares: 3,
+        };
+
+        let (_, shares) = shamir
+            .split_secret(sc, &mut rng)
+            .unwrap();
+        assert_eq!(shares.len(), 3);
+
+        // Every share is needed
+        assert_eq!(
+            shamir
+                .reconstruct_secret(&shares)
+                .unwrap(),
+            sc
+        );
+        assert!(matches!(
+            shamir
+                .reconstruct_secret(&shares[..2].to_vec()),
+            Err(ShamirError::NotEnoughShares)
+        ));
+
+        // Completing with no remaining x-coordinates is a
+        // no-op
+        let unqualified = shares[..2].to_vec();
+        let missing = shamir
+            .complete_shares(&sc, &unqualified, &vec![])
+            .unwrap();
+        assert!(missing.is_empty());
+
+        // Completing the last share recovers it
+        let missing = shamir
+            .complete_shares(
+                &sc,
+                &unqualified,
+                &vec![shares[2].x],
+            )
+            .unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].y, shares[2].y);
+    }
+
     #[test]
     fn split_secret_works() {
         let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
@@ -273,6 +635,211 @@ mod tests {
         assert_eq!(combined_secret, WrappedScalar(sc))
     }
 
+    #[test]
+    fn split_secret_never_shares_x_zero() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let secret =
+            WrappedScalar(Scalar::random(&mut rng));
+        for (threshold, n) in [
+            (1, 1),
+            (1, 4),
+            (2, 2),
+            (2, 5),
+            (5, 5),
+            (7, 16),
+        ] {
+            let shamir = ShamirSecretSharing {
+                threshold,
+                shares: n,
+            };
+            let (_, shares) = shamir
+                .split_secret(secret, &mut rng)
+                .unwrap();
+            assert_eq!(shares.len(), n);
+            for (i, share) in shares
+                .iter()
+                .enumerate()
+            {
+                assert!(!bool::from(
+                    share
+                        .x
+                        .is_zero()
+                ));
+                assert_eq!(
+                    share.x,
+                    WrappedScalar::from(i as u64 + 1)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reconstruct_secret_checked_detects_corruption() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = WrappedScalar(Scalar::random(&mut rng));
+        let shamir = ShamirSecretSharing {
+            threshold: 4,
+            shares: 7,
+        };
+        let (_, shares) = shamir
+            .split_secret(sc, &mut rng)
+            .unwrap();
+        assert_eq!(
+            shamir
+                .reconstruct_secret_checked(&shares)
+                .unwrap(),
+            sc
+        );
+
+        // Corrupt a share that is not used to interpolate
+        let mut corrupted = shares.clone();
+        corrupted[5].y += WrappedScalar::from(1u64);
+        assert!(matches!(
+            shamir.reconstruct_secret_checked(&corrupted),
+            Err(ShamirError::InconsistentShares)
+        ));
+        // The unchecked reconstruction does not notice
+        assert_eq!(
+            shamir
+                .reconstruct_secret(&corrupted)
+                .unwrap(),
+            sc
+        );
+
+        // Corrupt a share that is used to interpolate
+        let mut corrupted = shares.clone();
+        corrupted[0].y += WrappedScalar::from(1u64);
+        assert!(matches!(
+            shamir.reconstruct_secret_checked(&corrupted),
+            Err(ShamirError::InconsistentShares)
+        ));
+
+        // With exactly `threshold` shares nothing can be
+        // checked
+        assert_eq!(
+            shamir
+                .reconstruct_secret_checked(&shares[..4])
+                .unwrap(),
+            sc
+        );
+        assert!(matches!(
+            shamir.reconstruct_secret_checked(&shares[..3]),
+            Err(ShamirError::NotEnoughShares)
+        ));
+    }
+
+    #[test]
+    fn reconstruct_robust_corrects_errors() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = WrappedScalar(Scalar::random(&mut rng));
+        let shamir = ShamirSecretSharing {
+            threshold: 4,
+            shares: 6,
+        };
+        let (_, shares) = shamir
+            .split_secret(sc, &mut rng)
+            .unwrap();
+        assert_eq!(
+            shamir
+                .reconstruct_robust(&shares, 1)
+                .unwrap(),
+            sc
+        );
+
+        // A share used by the plain reconstruction
+        let mut corrupted = shares.clone();
+        corrupted[1].y += WrappedScalar::from(1u64);
+        assert_ne!(
+            shamir
+                .reconstruct_secret(&corrupted)
+                .unwrap(),
+            sc
+        );
+        assert_eq!(
+            shamir
+                .reconstruct_robust(&corrupted, 1)
+                .unwrap(),
+            sc
+        );
+
+        // More errors than allowed
+        corrupted[4].y += WrappedScalar::from(1u64);
+        assert!(matches!(
+            shamir.reconstruct_robust(&corrupted, 1),
+            Err(ShamirError::InconsistentShares)
+        ));
+        // Not enough redundancy to correct two errors
+        assert!(matches!(
+            shamir.reconstruct_robust(&corrupted, 2),
+            Err(ShamirError::NotEnoughShares)
+        ));
+    }
+
+    #[test]
+    fn reconstruction_rejects_repeated_xs() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = WrappedScalar(Scalar::random(&mut rng));
+        let shamir = ShamirSecretSharing {
+            threshold: 4,
+            shares: 6,
+        };
+        let (_, mut shares) = shamir
+            .split_secret(sc, &mut rng)
+            .unwrap();
+
+        // A corrupt share claiming another's x-coordinate
+        shares[2].x = shares[0].x;
+        assert!(matches!(
+            shamir.reconstruct_robust(&shares, 1),
+            Err(ShamirError::InvalidXCoordinates)
+        ));
+        assert!(matches!(
+            shamir.reconstruct_secret_checked(&shares),
+            Err(ShamirError::InvalidXCoordinates)
+        ));
+
+        // Or the secret's
+        shares[2].x = WrappedScalar::from(0u64);
+        assert!(matches!(
+            shamir.reconstruct_robust(&shares, 1),
+            Err(ShamirError::InvalidXCoordinates)
+        ));
+    }
+
+    #[test]
+    fn reconstruction_coefficients_work() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = Scalar::random(&mut rng);
+        let shamir = ShamirSecretSharing {
+            threshold: 4,
+            shares: 7,
+        };
+        let (_, shares) = shamir
+            .split_secret(WrappedScalar(sc), &mut rng)
+            .unwrap();
+        let subset = &shares[2..6];
+        let xs: Vec<WrappedScalar> = subset
+            .iter()
+            .map(|share| share.x)
+            .collect();
+
+        let coeffs =
+            shamir.reconstruction_coefficients(&xs);
+        assert_eq!(coeffs.len(), xs.len());
+        let combined: WrappedScalar = coeffs
+            .iter()
+            .zip(subset)
+            .map(|(coeff, share)| *coeff * share.y)
+            .sum();
+        assert_eq!(
+            combined,
+            shamir
+                .reconstruct_secret(&subset.to_vec())
+                .unwrap()
+        );
+        assert_eq!(combined, WrappedScalar(sc));
+    }
+
     #[test]
     fn complete_shares_work_small() {
         let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
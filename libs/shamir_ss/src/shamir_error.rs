@@ -19,4 +19,30 @@ pub enum ShamirError {
     InvalidShare,
     #[error("Not enough shares to reconstruct the secret")]
     NotEnoughShares,
+    #[error(
+        "x-coordinates must be distinct, nonzero and one \
+         per share"
+    )]
+    InvalidXCoordinates,
+    #[error(
+        "Shares do not lie on a single polynomial of \
+         degree threshold - 1"
+    )]
+    InconsistentShares,
+    #[error(
+        "Threshold must be at least 1 and at most the \
+         number of shares"
+    )]
+    InvalidThreshold,
+    #[error("Every party must hold at least one share")]
+    InvalidWeights,
+}
+
+/// Callers of the Sigma protocol compilers see every
+/// secret sharing failure as
+/// [`sigmazk::error::Error::SecretSharing`]
+impl From<ShamirError> for sigmazk::error::Error {
+    fn from(_: ShamirError) -> Self {
+        sigmazk::error::Error::SecretSharing
+    }
 }
@@ -0,0 +1,183 @@
+use group::ff::PrimeField;
+use rand_core::CryptoRngCore;
+
+use crate::shamir::{ShamirSecretSharing, Share};
+use crate::shamir_error::ShamirError;
+
+/// Weighted Shamir secret sharing where party `i` holds
+/// `weights[i]` shares.
+///
+/// Party `i` is assigned the consecutive x-coordinates
+/// following those of parties `0..i`, so any set of parties
+/// whose weights add up to at least `threshold` can
+/// reconstruct the secret.
+#[derive(Clone, Debug)]
+pub struct WeightedShamir {
+    pub threshold: usize,
+    pub weights: Vec<usize>,
+}
+
+impl WeightedShamir {
+    /// Fails with [`ShamirError::InvalidWeights`] if a party
+    /// has weight 0, and with
+    /// [`ShamirError::InvalidThreshold`] unless
+    /// `1 <= threshold <= total weight`
+    pub fn new(
+        threshold: usize,
+        weights: Vec<usize>,
+    ) -> Result<Self, ShamirError> {
+        let weighted = Self { threshold, weights };
+        weighted.validate()?;
+        Ok(weighted)
+    }
+
+    /// The checks of [`WeightedShamir::new`], for a sharing
+    /// whose fields were set directly
+    fn validate(&self) -> Result<(), ShamirError> {
+        if self
+            .weights
+            .contains(&0)
+        {
+            return Err(ShamirError::InvalidWeights);
+        }
+        if self.threshold == 0
+            || self.threshold > self.total_weight()
+        {
+            return Err(ShamirError::InvalidThreshold);
+        }
+        Ok(())
+    }
+
+    /// Total number of shares across all parties
+    pub fn total_weight(&self) -> usize {
+        self.weights
+            .iter()
+            .sum()
+    }
+
+    /// Split `secret` and group the shares by party. Fails
+    /// where [`WeightedShamir::new`] does.
+    pub fn split_weighted<F, R>(
+        &self,
+        secret: F,
+        rng: &mut R,
+    ) -> Result<Vec<Vec<Share<F>>>, ShamirError>
+    where
+        F: PrimeField,
+        R: CryptoRngCore,
+    {
+        self.validate()?;
+        let shamir = ShamirSecretSharing {
+            threshold: self.threshold,
+            shares: self.total_weight(),
+        };
+        let (_, shares) =
+            shamir.split_secret(secret, rng)?;
+
+        let mut shares = shares.into_iter();
+        Ok(self
+            .weights
+            .iter()
+            .map(|weight| {
+                shares
+                    .by_ref()
+                    .take(*weight)
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Reconstruct the secret from the shares of a subset of
+    /// parties. Fails if their combined weight is less than
+    /// the threshold.
+    pub fn reconstruct_weighted<F>(
+        &self,
+        party_shares: &[Vec<Share<F>>],
+    ) -> Result<F, ShamirError>
+    where
+        F: PrimeField,
+    {
+        let shares: Vec<Share<F>> = party_shares
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+        let shamir = ShamirSecretSharing {
+            threshold: self.threshold,
+            shares: self.total_weight(),
+        };
+        shamir.reconstruct_secret(&shares)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::Scalar;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    #[test]
+    fn weighted_shares_reconstruct() {
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        let sc = Scalar::random(&mut rng);
+        let weighted =
+            WeightedShamir::new(3, vec![2, 1, 1]).unwrap();
+
+        let shares = weighted
+            .split_weighted(WrappedScalar(sc), &mut rng)
+            .unwrap();
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares[0].len(), 2);
+
+        // Weight 2 + weight 1 meets the threshold of 3
+        let res = weighted
+            .reconstruct_weighted(&[
+                shares[0].clone(),
+                shares[2].clone(),
+            ])
+            .unwrap();
+        assert_eq!(res.0, sc);
+
+        // Weight 1 + weight 1 does not
+        let res = weighted.reconstruct_weighted(&[
+            shares[1].clone(),
+            shares[2].clone(),
+        ]);
+        assert!(matches!(
+            res,
+            Err(ShamirError::NotEnoughShares)
+        ));
+    }
+
+    #[test]
+    fn invalid_weights_are_rejected() {
+        assert!(matches!(
+            WeightedShamir::new(2, vec![2, 0, 1]),
+            Err(ShamirError::InvalidWeights)
+        ));
+        assert!(matches!(
+            WeightedShamir::new(0, vec![2, 1]),
+            Err(ShamirError::InvalidThreshold)
+        ));
+        assert!(matches!(
+            WeightedShamir::new(4, vec![2, 1]),
+            Err(ShamirError::InvalidThreshold)
+        ));
+
+        // Fields set directly are checked when splitting
+        let weighted = WeightedShamir {
+            threshold: 4,
+            weights: vec![2, 1],
+        };
+        let mut rng = ChaCha20Rng::from_seed([0u8; 32]);
+        assert!(matches!(
+            weighted.split_weighted(
+                WrappedScalar::from(1u64),
+                &mut rng
+            ),
+            Err(ShamirError::InvalidThreshold)
+        ));
+    }
+}
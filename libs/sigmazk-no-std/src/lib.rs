@@ -0,0 +1,74 @@
+//! Build check for `sigmazk` without its `std` feature.
+//!
+//! This crate is `no_std` and uses the Sigma protocol
+//! traits and Schnorr's protocol the way firmware would.
+//! Built for the host it checks that the API is available
+//! without `std`; built for a target without `std`, e.g.
+//!
+//! ```bash
+//! cargo build -p sigmazk-no-std --target thumbv7em-none-eabihf
+//! ```
+//!
+//! it also checks that no dependency pulls `std` in.
+#![no_std]
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::Scalar;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sigmazk::message::Message;
+use sigmazk::{EHVzk, Schnorr, SigmaProtocol};
+
+/// Run Schnorr's protocol for `witness` with randomness from
+/// `seed`, and return whether it verified together with the
+/// encoded conversation `(a, c, z)`
+pub fn schnorr_run(
+    statement: &Schnorr,
+    witness: &Scalar,
+    seed: [u8; 32],
+) -> (bool, Vec<u8>) {
+    let rng = &mut ChaCha20Rng::from_seed(seed);
+    let (state, a) =
+        Schnorr::first(statement, witness, rng);
+    let c = Schnorr::second(rng);
+    let z =
+        Schnorr::third(statement, state, witness, &c, rng);
+
+    let mut bytes = Vec::new();
+    a.write(&mut bytes);
+    c.write(&mut bytes);
+    z.write(&mut bytes);
+    let simulated =
+        <Schnorr as EHVzk>::simulate(statement, &c, &z);
+    (
+        Schnorr::verify(statement, &a, &c, &z)
+            && Schnorr::verify(
+                statement, &simulated, &c, &z,
+            ),
+        bytes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schnorr_runs_without_std() {
+        let witness = Scalar::from(7u64);
+        let statement = Schnorr::init(witness);
+        let (ok, bytes) =
+            schnorr_run(&statement, &witness, [0u8; 32]);
+        assert!(ok);
+        assert_eq!(bytes.len(), 96);
+
+        let (ok, _) = schnorr_run(
+            &statement,
+            &Scalar::ONE,
+            [0u8; 32],
+        );
+        assert!(!ok);
+    }
+}
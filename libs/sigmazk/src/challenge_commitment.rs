@@ -0,0 +1,112 @@
+//! Commitments to the verifier's challenge, sent before the
+//! prover's first message.
+//!
+//! Honest-verifier zero knowledge only covers verifiers that
+//! draw the challenge independently of the first message. A
+//! verifier that commits to its challenge up front and opens
+//! it after receiving `a` can no longer choose it adaptively,
+//! which is the order a cut-and-choose hardened composition
+//! needs. See [`SigmaProtocol::verifier_commit`] and
+//! [`SigmaProtocol::verify_committed`].
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::message::Message;
+
+/// `SHA-256(challenge)`, domain separated.
+///
+/// The commitment is only hiding because challenges are
+/// drawn uniformly from a large space, as for the 252-bit
+/// scalars of [`Schnorr`](crate::Schnorr).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct ChallengeCommitment(pub [u8; 32]);
+
+impl ChallengeCommitment {
+    pub fn commit<C: Message>(challenge: &C) -> Self {
+        let mut bytes = Vec::new();
+        challenge.write(&mut bytes);
+        let mut hash = Sha256::new();
+        hash.update(b"sigmazk challenge commitment");
+        hash.update(bytes);
+        Self(
+            hash.finalize()
+                .into(),
+        )
+    }
+
+    /// Whether `challenge` is the challenge committed to.
+    /// The digests are compared in constant time.
+    pub fn opens<C: Message>(&self, challenge: &C) -> bool {
+        Self::commit(challenge)
+            .0
+            .ct_eq(&self.0)
+            .into()
+    }
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use curve25519_dalek::Scalar;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use crate::error::Error;
+    use crate::{Schnorr, SigmaProtocol};
+
+    #[test]
+    fn committed_challenge_order() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let statement = Schnorr::init(witness);
+        let provers_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let verifiers_rng =
+            &mut ChaCha20Rng::from_seed([3u8; 32]);
+
+        // The verifier commits before seeing `a`
+        let (commitment, c) =
+            Schnorr::verifier_commit(verifiers_rng);
+        let (state, a) = Schnorr::first(
+            &statement,
+            &witness,
+            provers_rng,
+        );
+        assert!(commitment.opens(&c));
+        let z = Schnorr::third(
+            &statement,
+            state,
+            &witness,
+            &c,
+            provers_rng,
+        );
+        assert!(Schnorr::verify_committed(
+            &statement,
+            &commitment,
+            &a,
+            &c,
+            &z
+        )
+        .unwrap());
+
+        // Opening to another challenge is detected
+        let other = Schnorr::second(verifiers_rng);
+        assert!(!commitment.opens(&other));
+        assert!(matches!(
+            Schnorr::verify_committed(
+                &statement,
+                &commitment,
+                &a,
+                &other,
+                &z
+            ),
+            Err(Error::ChallengeMismatch)
+        ));
+    }
+}
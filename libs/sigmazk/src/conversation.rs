@@ -0,0 +1,182 @@
+//! Record of a complete run of a Sigma protocol
+
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+
+use crate::error::Error;
+use crate::message::{
+    read_framed, write_framed, ReadMessage,
+};
+use crate::SigmaProtocol;
+
+/// The transcript `(statement, a, c, z)` of an interactive
+/// run of a Sigma protocol.
+///
+/// Unlike Fiat-Shamir, the challenge is the one actually
+/// issued by the verifier and is stored verbatim, so the
+/// conversation can be replayed and verified later, e.g.
+/// for audit logs.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Conversation<S: SigmaProtocol> {
+    pub statement: S::Statement,
+    pub a: S::MessageA,
+    pub c: S::Challenge,
+    pub z: S::MessageZ,
+}
+
+impl<S: SigmaProtocol> Conversation<S> {
+    pub fn new(
+        statement: S::Statement,
+        a: S::MessageA,
+        c: S::Challenge,
+        z: S::MessageZ,
+    ) -> Self {
+        Self { statement, a, c, z }
+    }
+
+    /// Run the protocol between the prover and the verifier
+    /// and record the messages exchanged
+    pub fn record<R: CryptoRngCore + Clone>(
+        statement: S::Statement,
+        witness: &S::Witness,
+        prover_rng: &mut R,
+        verifier_rng: &mut R,
+    ) -> Self {
+        let (state, a) =
+            S::first(&statement, witness, prover_rng);
+        let c = S::second(verifier_rng);
+        let z = S::third(
+            &statement, state, witness, &c, prover_rng,
+        );
+        Self { statement, a, c, z }
+    }
+
+    /// Verify the recorded transcript
+    pub fn verify(&self) -> bool {
+        S::verify(
+            &self.statement,
+            &self.a,
+            &self.c,
+            &self.z,
+        )
+    }
+}
+
+impl<S: SigmaProtocol> Conversation<S>
+where
+    S::Statement: ReadMessage,
+    S::MessageA: ReadMessage,
+    S::Challenge: ReadMessage,
+    S::MessageZ: ReadMessage,
+{
+    /// Encode the statement and the three messages, each
+    /// prefixed with its length as a little-endian `u32`
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        write_framed(&self.statement, &mut bytes)?;
+        write_framed(&self.a, &mut bytes)?;
+        write_framed(&self.c, &mut bytes)?;
+        write_framed(&self.z, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by [`Conversation::to_bytes`]
+    pub fn from_bytes(
+        mut bytes: &[u8],
+    ) -> Result<Self, Error> {
+        let statement =
+            S::Statement::read(read_framed(&mut bytes)?)?;
+        let a =
+            S::MessageA::read(read_framed(&mut bytes)?)?;
+        let c =
+            S::Challenge::read(read_framed(&mut bytes)?)?;
+        let z =
+            S::MessageZ::read(read_framed(&mut bytes)?)?;
+        if !bytes.is_empty() {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(Self { statement, a, c, z })
+    }
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use curve25519_dalek::Scalar;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+    use crate::Schnorr;
+
+    #[test]
+    fn conversation_round_trip() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let conversation = Conversation::<Schnorr>::record(
+            Schnorr::init(witness),
+            &witness,
+            &mut ChaCha20Rng::from_seed([2u8; 32]),
+            &mut ChaCha20Rng::from_seed([3u8; 32]),
+        );
+        assert!(conversation.verify());
+
+        let bytes = conversation
+            .to_bytes()
+            .unwrap();
+        let reloaded =
+            Conversation::<Schnorr>::from_bytes(&bytes)
+                .unwrap();
+        assert_eq!(
+            reloaded.statement,
+            conversation.statement
+        );
+        assert_eq!(reloaded.c, conversation.c);
+        assert!(reloaded.verify());
+
+        // The last segment, the 32-byte third message, is
+        // cut short
+        assert!(matches!(
+            Conversation::<Schnorr>::from_bytes(
+                &bytes[..bytes.len() - 1]
+            ),
+            Err(Error::InvalidLength {
+                expected: 32,
+                got: 31
+            })
+        ));
+    }
+
+    #[test]
+    fn record_advances_the_prover_rng() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let statement = Schnorr::init(witness);
+        let mut prover_rng =
+            ChaCha20Rng::from_seed([2u8; 32]);
+        let mut verifier_rng =
+            ChaCha20Rng::from_seed([3u8; 32]);
+        let first = Conversation::<Schnorr>::record(
+            statement,
+            &witness,
+            &mut prover_rng,
+            &mut verifier_rng,
+        );
+        let second = Conversation::<Schnorr>::record(
+            statement,
+            &witness,
+            &mut prover_rng,
+            &mut verifier_rng,
+        );
+        // Reusing the first message under two challenges
+        // would reveal the witness
+        assert_ne!(first.a, second.a);
+        assert!(first.verify() && second.verify());
+    }
+}
@@ -0,0 +1,293 @@
+//! Object-safe interface to Sigma protocols over serialized
+//! messages, so that clauses of different protocols can be
+//! held together as `Vec<Box<dyn DynSimulator>>`
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use crate::error::Error;
+use crate::message::{Message, ReadMessage};
+use crate::*;
+
+/// Prover state of a clause run through [`DynSimulator`]
+pub type DynState = Box<dyn Any>;
+
+/// A clause of a disjunction, seen through messages
+/// serialized with [`Message::write`].
+///
+/// Every byte argument is parsed with
+/// [`ReadMessage::read`], and fails with its error, e.g.
+/// [`Error::InvalidLength`], if it does not hold a message
+/// of the clause's protocol.
+pub trait DynSimulator {
+    /// First round of the protocol with the serialized
+    /// witness. Returns the prover state to pass to
+    /// [`DynSimulator::third_dyn`] and the first message.
+    fn first_dyn(
+        &self,
+        witness: &[u8],
+        prover_rng: &mut dyn CryptoRngCore,
+    ) -> Result<(DynState, Vec<u8>), Error>;
+
+    /// Third round of the protocol. Fails with
+    /// [`Error::InvalidTranscriptState`] if `state` was not
+    /// returned by `first_dyn` of the same protocol.
+    fn third_dyn(
+        &self,
+        state: DynState,
+        witness: &[u8],
+        challenge_bytes: &[u8],
+        prover_rng: &mut dyn CryptoRngCore,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// First message that makes `(a, challenge, z)` an
+    /// accepting transcript, as in [`EHVzk::simulate`]
+    fn simulate_dyn(
+        &self,
+        challenge_bytes: &[u8],
+        z_bytes: &[u8],
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Verify a serialized transcript. Bytes that do not
+    /// parse are rejected.
+    fn verify_dyn(
+        &self,
+        a_bytes: &[u8],
+        challenge_bytes: &[u8],
+        z_bytes: &[u8],
+    ) -> bool;
+}
+
+/// Protocols whose statement is the protocol itself, e.g.
+/// [`Schnorr`] and [`Okamoto`](crate::okamoto::Okamoto),
+/// are clauses on their own
+impl<S> DynSimulator for S
+where
+    S: SigmaProtocol<
+            Statement = S,
+            Witness: ReadMessage,
+            MessageA: ReadMessage,
+            Challenge: ReadMessage,
+            MessageZ: ReadMessage,
+            State: 'static,
+        > + EHVzk,
+{
+    fn first_dyn(
+        &self,
+        witness: &[u8],
+        prover_rng: &mut dyn CryptoRngCore,
+    ) -> Result<(DynState, Vec<u8>), Error> {
+        let witness = S::Witness::read(witness)?;
+        let (state, a) = S::first(
+            self,
+            &witness,
+            &mut reseed(prover_rng),
+        );
+        Ok((Box::new(state), to_bytes(&a)))
+    }
+
+    fn third_dyn(
+        &self,
+        state: DynState,
+        witness: &[u8],
+        challenge_bytes: &[u8],
+        prover_rng: &mut dyn CryptoRngCore,
+    ) -> Result<Vec<u8>, Error> {
+        let state = state
+            .downcast::<S::State>()
+            .map_err(|_| Error::InvalidTranscriptState)?;
+        let witness = S::Witness::read(witness)?;
+        let c = S::Challenge::read(challenge_bytes)?;
+        let z = S::third(
+            self,
+            *state,
+            &witness,
+            &c,
+            &mut reseed(prover_rng),
+        );
+        Ok(to_bytes(&z))
+    }
+
+    fn simulate_dyn(
+        &self,
+        challenge_bytes: &[u8],
+        z_bytes: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let c = S::Challenge::read(challenge_bytes)?;
+        let z = S::MessageZ::read(z_bytes)?;
+        Ok(to_bytes(&<S as EHVzk>::simulate(self, &c, &z)))
+    }
+
+    fn verify_dyn(
+        &self,
+        a_bytes: &[u8],
+        challenge_bytes: &[u8],
+        z_bytes: &[u8],
+    ) -> bool {
+        match (
+            S::MessageA::read(a_bytes),
+            S::Challenge::read(challenge_bytes),
+            S::MessageZ::read(z_bytes),
+        ) {
+            (Ok(a), Ok(c), Ok(z)) => {
+                S::verify(self, &a, &c, &z)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn to_bytes<M: Message>(message: &M) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    message.write(&mut bytes);
+    bytes
+}
+
+/// The protocols take a `Clone` RNG, which a trait object
+/// is not, so seed one from it
+fn reseed(rng: &mut dyn CryptoRngCore) -> ChaCha20Rng {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    ChaCha20Rng::from_seed(seed)
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use super::*;
+    use crate::okamoto::{Okamoto, ScalarPair};
+
+    /// 1-out-of-2 OR proof over `clauses`, knowing the
+    /// witness of clause `active`. `random_z` holds a
+    /// random third message for every clause, used to
+    /// simulate the other one.
+    fn prove_and_verify(
+        clauses: &[Box<dyn DynSimulator>],
+        active: usize,
+        witness: &[u8],
+        random_z: &[Vec<u8>],
+        rng: &mut ChaCha20Rng,
+    ) -> bool {
+        let other = 1 - active;
+
+        // Prover: simulate the other clause, run the active one
+        let c_other = Scalar::random(rng);
+        let a_other = clauses[other]
+            .simulate_dyn(
+                c_other.as_bytes(),
+                &random_z[other],
+            )
+            .unwrap();
+        let (state, a_active) = clauses[active]
+            .first_dyn(witness, rng)
+            .unwrap();
+
+        // Verifier
+        let c = Scalar::random(rng);
+
+        // Prover: the challenges add up to `c`
+        let c_active = c - c_other;
+        let z_active = clauses[active]
+            .third_dyn(
+                state,
+                witness,
+                c_active.as_bytes(),
+                rng,
+            )
+            .unwrap();
+
+        let mut a = [a_active.clone(), a_other];
+        let mut cs = [c_active, c_other];
+        let mut zs = [z_active, random_z[other].clone()];
+        if active == 1 {
+            a.swap(0, 1);
+            cs.swap(0, 1);
+            zs.swap(0, 1);
+        }
+        cs[0] + cs[1] == c
+            && (0..2).all(|i| {
+                clauses[i].verify_dyn(
+                    &a[i],
+                    cs[i].as_bytes(),
+                    &zs[i],
+                )
+            })
+    }
+
+    #[test]
+    fn or_of_schnorr_and_okamoto() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let schnorr_witness = Scalar::random(rng);
+        let okamoto_witness = ScalarPair::random(rng);
+        let clauses: Vec<Box<dyn DynSimulator>> = vec![
+            Box::new(Schnorr::init(schnorr_witness)),
+            Box::new(Okamoto::init(okamoto_witness)),
+        ];
+        let random_z = vec![
+            to_bytes(&Scalar::random(rng)),
+            to_bytes(&ScalarPair::random(rng)),
+        ];
+
+        // Knowing either witness is enough
+        assert!(prove_and_verify(
+            &clauses,
+            0,
+            &to_bytes(&schnorr_witness),
+            &random_z,
+            rng
+        ));
+        assert!(prove_and_verify(
+            &clauses,
+            1,
+            &to_bytes(&okamoto_witness),
+            &random_z,
+            rng
+        ));
+
+        // A wrong witness is not
+        assert!(!prove_and_verify(
+            &clauses,
+            1,
+            &to_bytes(&ScalarPair::random(rng)),
+            &random_z,
+            rng
+        ));
+    }
+
+    #[test]
+    fn dyn_rejects_malformed_input() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let schnorr: Box<dyn DynSimulator> =
+            Box::new(Schnorr::init(Scalar::random(rng)));
+        let okamoto: Box<dyn DynSimulator> = Box::new(
+            Okamoto::init(ScalarPair::random(rng)),
+        );
+
+        // An Okamoto witness is not a Schnorr witness
+        let witness = to_bytes(&ScalarPair::random(rng));
+        assert!(matches!(
+            schnorr.first_dyn(&witness, rng),
+            Err(Error::InvalidLength {
+                expected: 32,
+                got: 64
+            })
+        ));
+
+        // Nor is a Schnorr state an Okamoto state
+        let witness = to_bytes(&Scalar::random(rng));
+        let (state, _) = schnorr
+            .first_dyn(&witness, rng)
+            .unwrap();
+        assert!(matches!(
+            okamoto.third_dyn(
+                state,
+                &to_bytes(&ScalarPair::random(rng)),
+                Scalar::ONE.as_bytes(),
+                rng
+            ),
+            Err(Error::InvalidTranscriptState)
+        ));
+
+        assert!(!schnorr.verify_dyn(&[0u8; 3], &[], &[]));
+    }
+}
@@ -13,4 +13,54 @@ pub enum Error {
     /// Indicates that the prover might have called the method on the wrong instance of the protocol
     /// or the prover is not saving the challenge appropriately.
     ChallengeMismatch,
+    /// When bytes cannot be decoded into a message, e.g. a
+    /// framed proof is truncated or a field is malformed.
+    InvalidEncoding,
+    /// When a message or a length-prefixed segment does not
+    /// have the number of bytes its encoding requires.
+    InvalidLength { expected: usize, got: usize },
+    /// When a proof has more components than the statement
+    /// or the verifier allows.
+    ProofTooLarge,
+    /// When a compiled witness does not have one witness per
+    /// clause.
+    WitnessLengthMismatch,
+    /// When a compiled statement does not have one statement
+    /// per clause.
+    StatementLengthMismatch,
+    /// When the threshold of a compiled statement is not
+    /// between 1 and the number of clauses.
+    InvalidThreshold,
+    /// When a compiled witness has no active clause, or an
+    /// active clause that does not exist.
+    InvalidActiveClauses,
+    /// When a field element is not the x-coordinate of a
+    /// clause index.
+    InvalidIndex,
+    /// When bytes are not the encoding of a valid Ristretto
+    /// point.
+    InvalidPoint,
+    /// When bytes are not the canonical encoding of a
+    /// scalar, i.e. are not reduced modulo the group order.
+    InvalidScalar,
+    /// When the randomness of a partially-binding
+    /// commitment does not have one component per layer of
+    /// the scheme.
+    RandomnessDepthMismatch,
+    /// When the inner binding index of a base-level binding
+    /// index, which has no inner level, is requested.
+    BaseBindingIndex,
+    /// When a statement is degenerate, e.g. a Schnorr
+    /// public key that is the identity point.
+    InvalidStatement,
+    /// When the depth `q` of a 1-of-2^q partially-binding
+    /// commitment scheme is outside the supported range.
+    UnsupportedDepth,
+    /// When secret sharing a challenge among the clauses
+    /// fails, e.g. the simulated clauses are not an
+    /// unqualified set of the Shamir scheme.
+    SecretSharing,
+    /// When a message is too long for its length to fit in
+    /// the `u32` prefix of a framed encoding.
+    MessageTooLarge,
 }
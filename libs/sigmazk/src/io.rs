@@ -0,0 +1,111 @@
+//! I/O helpers for encoding messages
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Sink that [`Message::write`](crate::message::Message)
+/// writes encodings to
+#[cfg(feature = "std")]
+pub use std::io::Write;
+
+/// Sink that [`Message::write`](crate::message::Message)
+/// writes encodings to without `std`: the part of
+/// `std::io::Write` that messages use
+#[cfg(not(feature = "std"))]
+pub trait Write {
+    /// Write all of `buf`
+    fn write_all(
+        &mut self,
+        buf: &[u8],
+    ) -> Result<(), core::fmt::Error>;
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for Vec<u8> {
+    fn write_all(
+        &mut self,
+        buf: &[u8],
+    ) -> Result<(), core::fmt::Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: Write + ?Sized> Write for &mut W {
+    fn write_all(
+        &mut self,
+        buf: &[u8],
+    ) -> Result<(), core::fmt::Error> {
+        (**self).write_all(buf)
+    }
+}
+
+/// A writer that discards the bytes written to it and only
+/// counts them, to compute the length of an encoding
+/// without allocating it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CountingWriter {
+    count: usize,
+}
+
+impl CountingWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bytes written so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for CountingWriter {
+    fn write_all(
+        &mut self,
+        buf: &[u8],
+    ) -> Result<(), core::fmt::Error> {
+        self.count += buf.len();
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+    use crate::message::Message;
+    use crate::Schnorr;
+
+    #[test]
+    fn counting_writer_matches_vec() {
+        let statement = Schnorr::init(Scalar::ONE);
+        let msg = vec![Scalar::ONE; 3];
+
+        let mut counter = CountingWriter::new();
+        let mut bytes = Vec::new();
+        statement.write(&mut counter);
+        statement.write(&mut bytes);
+        msg.write(&mut counter);
+        msg.write(&mut bytes);
+
+        assert_eq!(counter.count(), bytes.len());
+        assert_eq!(msg.size(), 96);
+    }
+}
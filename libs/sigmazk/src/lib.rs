@@ -1,15 +1,41 @@
 //! This crate defines traits related to Sigma Protocols and
 //! includes an implementation of Schnorr's identification
 //! scheme implementing said traits.
+//!
+//! Without the default `std` feature the crate is
+//! `no_std` and needs `alloc` only. What draws randomness
+//! from the operating system, such as [`HVzk`] simulators
+//! and `SchnorrVerifier::new`, then is not available.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
 extern crate rand;
 extern crate rand_chacha;
 extern crate rand_core;
+pub mod challenge_commitment;
+pub mod conversation;
+pub mod dynamic;
 pub mod error;
+pub mod io;
 pub mod message;
+pub mod okamoto;
+#[cfg(feature = "schnorr")]
 pub mod schnorr;
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
 pub mod sigma;
+pub mod statement_digest;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(all(
+    feature = "schnorr",
+    any(test, feature = "test-vectors")
+))]
+pub mod testvectors;
+pub mod transcript;
 pub mod zk;
 
+pub use challenge_commitment::*;
+pub use conversation::*;
 use curve25519_dalek::constants::{
     RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE,
 };
@@ -17,11 +43,14 @@ use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use rand_chacha::ChaCha20Rng;
 use rand_core::{CryptoRngCore, SeedableRng};
+#[cfg(feature = "schnorr")]
 pub use schnorr::*;
 pub use sigma::*;
+pub use statement_digest::*;
+pub use transcript::*;
 pub use zk::*;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "schnorr"))]
 mod tests {
     use super::*;
 
@@ -67,6 +96,105 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn schnorr_verifier_with_seed_is_reproducible() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let protocol = Schnorr::init(witness);
+        let mut verifier =
+            SchnorrVerifier::with_seed([3u8; 32]);
+        let mut replay =
+            SchnorrVerifier::with_seed([3u8; 32]);
+
+        let provers_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let (state, commitment) = Schnorr::first(
+            &protocol,
+            &witness,
+            provers_rng,
+        );
+        // Nothing to answer before a challenge is issued
+        assert!(!verifier.verify(
+            &protocol,
+            &commitment,
+            &Scalar::ZERO
+        ));
+        let challenge = verifier.second();
+        assert_eq!(challenge, replay.second());
+
+        let proof = Schnorr::third(
+            &protocol,
+            state,
+            &witness,
+            &challenge,
+            provers_rng,
+        );
+        assert!(verifier.verify(
+            &protocol,
+            &commitment,
+            &proof
+        ));
+        assert!(replay.verify(
+            &protocol,
+            &commitment,
+            &proof
+        ));
+
+        // The response only answers the challenge it was
+        // computed for
+        assert_eq!(verifier.second(), replay.second());
+        assert!(!verifier.verify(
+            &protocol,
+            &commitment,
+            &proof
+        ));
+    }
+
+    #[test]
+    fn schnorr_bounded_challenge() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let provers_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let verifiers_rng =
+            &mut ChaCha20Rng::from_seed([3u8; 32]);
+        let protocol = Schnorr::init(witness);
+
+        for bits in [128, 67, 1] {
+            let (state, commitment) = Schnorr::first(
+                &protocol,
+                &witness,
+                provers_rng,
+            );
+            let challenge = Schnorr::second_bounded(
+                verifiers_rng,
+                bits,
+            );
+            // Every bit from `bits` upwards is clear
+            let bytes = challenge.to_bytes();
+            let bits = bits as usize;
+            assert!((bits..256).all(|i| {
+                bytes[i / 8] >> (i % 8) & 1 == 0
+            }));
+
+            let proof = Schnorr::third(
+                &protocol,
+                state,
+                &witness,
+                &challenge,
+                provers_rng,
+            );
+            assert!(Schnorr::verify(
+                &protocol,
+                &commitment,
+                &challenge,
+                &proof
+            ));
+        }
+    }
+
     #[test]
     fn schnorr_fails() {
         let actual_witness = Scalar::random(
@@ -109,6 +237,322 @@ mod tests {
     }
 
     #[test]
+    fn schnorr_identity_statement_is_invalid() {
+        assert!(!Schnorr::init(Scalar::ZERO)
+            .is_valid_statement());
+        assert!(
+            Schnorr::init(Scalar::ONE).is_valid_statement()
+        );
+    }
+
+    #[test]
+    fn schnorr_default_is_padding_statement() {
+        let padding = Schnorr::default();
+        assert!(padding.is_valid_statement());
+        assert_eq!(padding, Schnorr::default());
+        assert_ne!(padding, Schnorr::init(Scalar::ONE));
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let (c, z) =
+            (Scalar::random(rng), Scalar::random(rng));
+        let a =
+            <Schnorr as EHVzk>::simulate(&padding, &c, &z);
+        assert!(Schnorr::verify(&padding, &a, &c, &z));
+    }
+
+    #[test]
+    fn schnorr_rerandomize() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let offset = Scalar::random(
+            &mut ChaCha20Rng::from_seed([1u8; 32]),
+        );
+        let provers_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let verifiers_rng =
+            &mut ChaCha20Rng::from_seed([3u8; 32]);
+
+        let protocol = Schnorr::init(witness);
+        let (rerandomized, transform) =
+            protocol.rerandomize(offset);
+        assert_ne!(rerandomized.pub_key, protocol.pub_key);
+
+        let new_witness = transform(witness);
+        let (state, commitment) = Schnorr::first(
+            &rerandomized,
+            &new_witness,
+            provers_rng,
+        );
+        let challenge = Schnorr::second(verifiers_rng);
+        let proof = Schnorr::third(
+            &rerandomized,
+            state,
+            &new_witness,
+            &challenge,
+            provers_rng,
+        );
+
+        assert!(Schnorr::verify(
+            &rerandomized,
+            &commitment,
+            &challenge,
+            &proof,
+        ));
+    }
+
+    #[test]
+    fn schnorr_compressed_round_trip() {
+        let protocol = Schnorr::init(Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        ));
+        let bytes = protocol.to_compressed();
+        assert_eq!(
+            Schnorr::from_compressed(&bytes).unwrap(),
+            protocol
+        );
+        let parsed: Schnorr = (&bytes)
+            .try_into()
+            .unwrap();
+        assert_eq!(parsed, protocol);
+    }
+
+    #[test]
+    fn schnorr_from_invalid_bytes_fails() {
+        // Not a canonical field element
+        assert!(matches!(
+            Schnorr::from_compressed(&[0xff; 32]),
+            Err(error::Error::InvalidPoint)
+        ));
+        // Canonical, but not the encoding of a point
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        assert!(matches!(
+            Schnorr::try_from(&bytes),
+            Err(error::Error::InvalidPoint)
+        ));
+    }
+
+    #[test]
+    fn schnorr_reuses_state_for_two_challenges() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let provers_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let verifiers_rng =
+            &mut ChaCha20Rng::from_seed([3u8; 32]);
+
+        let protocol = Schnorr::init(witness);
+        let (state, commitment) = Schnorr::first(
+            &protocol,
+            &witness,
+            provers_rng,
+        );
+
+        // Rewind the prover: same state, two challenges
+        let c1 = Schnorr::second(verifiers_rng);
+        let c2 = Schnorr::second(verifiers_rng);
+        let z1 = Schnorr::third(
+            &protocol,
+            state,
+            &witness,
+            &c1,
+            provers_rng,
+        );
+        let z2 = Schnorr::third(
+            &protocol,
+            state,
+            &witness,
+            &c2,
+            provers_rng,
+        );
+        assert!(Schnorr::verify(
+            &protocol,
+            &commitment,
+            &c1,
+            &z1
+        ));
+        assert!(Schnorr::verify(
+            &protocol,
+            &commitment,
+            &c2,
+            &z2
+        ));
+
+        assert_eq!(
+            Schnorr::extract((&c1, &z1), (&c2, &z2)),
+            Some(witness)
+        );
+        assert_eq!(
+            Schnorr::extract((&c1, &z1), (&c1, &z1)),
+            None
+        );
+    }
+
+    #[test]
+    fn schnorr_simulate_self_test() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let protocol = Schnorr::init(Scalar::random(rng));
+        #[cfg(feature = "std")]
+        assert!(<Schnorr as HVzk>::simulate_self_test(
+            &protocol
+        ));
+        assert!(<Schnorr as EHVzk>::simulate_self_test(
+            &protocol,
+            &Scalar::random(rng),
+            rng
+        ));
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    fn assert_thread_safe<S: ThreadSafeProtocol>() {
+        assert_send_sync::<S>();
+        assert_send_sync::<S::Statement>();
+        assert_send_sync::<S::Witness>();
+        assert_send_sync::<S::State>();
+        assert_send_sync::<S::MessageA>();
+        assert_send_sync::<S::Challenge>();
+        assert_send_sync::<S::MessageZ>();
+    }
+
+    #[test]
+    fn schnorr_is_thread_safe() {
+        assert_thread_safe::<Schnorr>();
+    }
+
+    #[test]
+    fn schnorr_same_witness() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = RistrettoPoint::random(rng);
+        let x = Scalar::random(rng);
+        let (p1, p2) = (x * g, x * h);
+
+        let proof = Schnorr::prove_same_witness(
+            &g, &h, &p1, &p2, &x, rng,
+        );
+        assert!(Schnorr::verify_same_witness(
+            &g, &h, &p1, &p2, &proof
+        ));
+        // The proof is bound to the keys and generators
+        assert!(!Schnorr::verify_same_witness(
+            &g, &h, &p2, &p1, &proof
+        ));
+        assert!(!Schnorr::verify_same_witness(
+            &h, &g, &p1, &p2, &proof
+        ));
+
+        // Keys of different secrets
+        let p2 = Scalar::random(rng) * h;
+        let proof = Schnorr::prove_same_witness(
+            &g, &h, &p1, &p2, &x, rng,
+        );
+        assert!(!Schnorr::verify_same_witness(
+            &g, &h, &p1, &p2, &proof
+        ));
+    }
+
+    /// `bytes + l` for the group order `l`, a non-canonical
+    /// encoding of the same scalar when it fits in 32 bytes
+    fn add_group_order(bytes: [u8; 32]) -> [u8; 32] {
+        const L: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58,
+            0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0x10,
+        ];
+        let mut sum = [0u8; 32];
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let digit =
+                bytes[i] as u16 + L[i] as u16 + carry;
+            sum[i] = digit as u8;
+            carry = digit >> 8;
+        }
+        assert_eq!(carry, 0, "bytes + l overflows");
+        sum
+    }
+
+    #[test]
+    fn proof_scalar_canonicity() {
+        let scalar = Scalar::from(42u64);
+        let canonical = scalar.to_bytes();
+        let reduced = add_group_order(canonical);
+        assert_ne!(canonical, reduced);
+
+        for bytes in [canonical, reduced] {
+            assert_eq!(
+                ProofScalar::from_bytes_reduced(bytes).0,
+                scalar
+            );
+            assert_eq!(ProofScalar::from(bytes).0, scalar);
+        }
+        assert_eq!(
+            ProofScalar::from_bytes_canonical(canonical),
+            Some(ProofScalar(scalar))
+        );
+        assert_eq!(
+            ProofScalar::from_bytes_canonical(reduced),
+            None
+        );
+
+        assert!(matches!(
+            ProofScalar::try_from(canonical.as_slice()),
+            Ok(ProofScalar(s)) if s == scalar
+        ));
+        assert!(matches!(
+            ProofScalar::try_from(reduced.as_slice()),
+            Err(error::Error::InvalidScalar)
+        ));
+        assert!(matches!(
+            ProofScalar::try_from(&canonical[1..]),
+            Err(error::Error::InvalidLength {
+                expected: 32,
+                got: 31
+            })
+        ));
+    }
+
+    #[test]
+    fn schnorr_verify_strict() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let witness = Scalar::random(rng);
+        let protocol = Schnorr::init(witness);
+        let (state, a) =
+            Schnorr::first(&protocol, &witness, rng);
+        let c = Schnorr::second(rng);
+        let z = Schnorr::third(
+            &protocol, state, &witness, &c, rng,
+        );
+        let (c, z) = (c.to_bytes(), z.to_bytes());
+        assert!(Schnorr::verify_strict(
+            &protocol, &a, &c, &z
+        ));
+
+        // Same scalars, other encodings
+        for (c, z) in [
+            (add_group_order(c), z),
+            (c, add_group_order(z)),
+        ] {
+            let lossy = |bytes| {
+                ProofScalar::from_bytes_reduced(bytes).0
+            };
+            assert!(Schnorr::verify(
+                &protocol,
+                &a,
+                &lossy(c),
+                &lossy(z)
+            ));
+            assert!(!Schnorr::verify_strict(
+                &protocol, &a, &c, &z
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
     fn schnorr_simulator() {
         let witness = Scalar::random(
             &mut ChaCha20Rng::from_seed([0u8; 32]),
@@ -119,4 +563,22 @@ mod tests {
         let result = Schnorr::verify(&protocol, &a, &c, &z);
         assert!(result);
     }
+
+    crate::sigma_conformance_tests! {
+        mod schnorr_conformance;
+        protocol: Schnorr,
+        generate: |rng| {
+            let witness = Scalar::random(rng);
+            (Schnorr::init(witness), witness)
+        },
+        seeds: [[0u8; 32], [1u8; 32], [2u8; 32]],
+    }
+
+    // The macro generates one test per property
+    const _: [fn(); 4] = [
+        schnorr_conformance::completeness,
+        schnorr_conformance::soundness,
+        schnorr_conformance::hvzk,
+        schnorr_conformance::determinism,
+    ];
 }
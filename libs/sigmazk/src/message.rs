@@ -1,19 +1,78 @@
-use std::fmt::Debug;
-use std::io::Write;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::Scalar;
 
+use crate::error::Error;
+use crate::io::{CountingWriter, Write};
+#[cfg(feature = "schnorr")]
+use crate::Schnorr;
+
 pub trait Message: Debug + Default + Clone {
     fn write<W: Write>(&self, writer: &mut W)
     where
         Self: Sized;
 
+    /// Length of the encoding written by
+    /// [`Message::write`], computed without allocating it
     fn size(&self) -> usize {
-        let mut v: Vec<u8> = Vec::new();
-        self.write(&mut v);
-        v.len()
+        let mut counter = CountingWriter::new();
+        self.write(&mut counter);
+        counter.count()
+    }
+}
+
+/// A [`Message`] that can be read back from the bytes
+/// written by [`Message::write`]
+pub trait ReadMessage: Message {
+    fn read(bytes: &[u8]) -> Result<Self, Error>;
+}
+
+/// Write `message` prefixed with its length as a
+/// little-endian `u32`, so that messages of varying length
+/// can be concatenated and split again with
+/// [`read_framed`]. Fails with [`Error::MessageTooLarge`]
+/// if the encoding is too long for its length to fit in a
+/// `u32`.
+pub fn write_framed<M: Message, W: Write>(
+    message: &M,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let len = u32::try_from(message.size())
+        .map_err(|_| Error::MessageTooLarge)?;
+    writer
+        .write_all(&len.to_le_bytes())
+        .unwrap();
+    message.write(writer);
+    Ok(())
+}
+
+/// Split the next length-prefixed segment off the front of
+/// `bytes`, as written by [`write_framed`]
+pub fn read_framed<'a>(
+    bytes: &mut &'a [u8],
+) -> Result<&'a [u8], Error> {
+    if bytes.len() < 4 {
+        return Err(Error::InvalidLength {
+            expected: 4,
+            got: bytes.len(),
+        });
     }
+    let (len, rest) = bytes.split_at(4);
+    let len = u32::from_le_bytes(
+        len.try_into()
+            .unwrap(),
+    ) as usize;
+    if rest.len() < len {
+        return Err(Error::InvalidLength {
+            expected: len,
+            got: rest.len(),
+        });
+    }
+    let (segment, rest) = rest.split_at(len);
+    *bytes = rest;
+    Ok(segment)
 }
 
 impl Message for usize {
@@ -50,6 +109,20 @@ impl Message for CompressedRistretto {
     }
 }
 
+impl ReadMessage for CompressedRistretto {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let point = CompressedRistretto::from_slice(bytes)
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                got: bytes.len(),
+            })?;
+        match point.decompress() {
+            Some(_) => Ok(point),
+            None => Err(Error::InvalidPoint),
+        }
+    }
+}
+
 #[test]
 fn test_write_compressedristretto() {
     let mut buf = Vec::new();
@@ -70,6 +143,19 @@ impl Message for Scalar {
     }
 }
 
+impl ReadMessage for Scalar {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                got: bytes.len(),
+            })?;
+        Option::from(Scalar::from_canonical_bytes(bytes))
+            .ok_or(Error::InvalidScalar)
+    }
+}
+
 #[test]
 fn test_write_scalar() {
     let mut buf = Vec::new();
@@ -81,6 +167,73 @@ fn test_write_scalar() {
     assert_eq!(buf, expected);
 }
 
+#[test]
+fn test_read_errors() {
+    assert!(matches!(
+        Scalar::read(&[0u8; 31]),
+        Err(Error::InvalidLength {
+            expected: 32,
+            got: 31
+        })
+    ));
+    // Larger than the group order
+    assert!(matches!(
+        Scalar::read(&[0xffu8; 32]),
+        Err(Error::InvalidScalar)
+    ));
+    assert!(matches!(
+        CompressedRistretto::read(&[0u8; 33]),
+        Err(Error::InvalidLength {
+            expected: 32,
+            got: 33
+        })
+    ));
+    assert!(matches!(
+        CompressedRistretto::read(&[0xffu8; 32]),
+        Err(Error::InvalidPoint)
+    ));
+}
+
+#[cfg(feature = "schnorr")]
+impl Message for Schnorr {
+    fn write<W: Write>(&self, writer: &mut W) {
+        self.pub_key
+            .compress()
+            .write(writer);
+    }
+}
+
+#[cfg(feature = "schnorr")]
+impl ReadMessage for Schnorr {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: &[u8; 32] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                got: bytes.len(),
+            })?;
+        let statement = Schnorr::from_compressed(bytes)?;
+        if !statement.is_valid_statement() {
+            return Err(Error::InvalidStatement);
+        }
+        Ok(statement)
+    }
+}
+
+#[cfg(feature = "schnorr")]
+#[test]
+fn test_read_schnorr_rejects_invalid_statements() {
+    assert!(matches!(
+        Schnorr::read(&[0xffu8; 32]),
+        Err(Error::InvalidPoint)
+    ));
+    // The compressed identity is the all-zero encoding
+    assert!(matches!(
+        Schnorr::read(&[0u8; 32]),
+        Err(Error::InvalidStatement)
+    ));
+}
+
 impl<M: Message> Message for Vec<M> {
     fn write<W: Write>(&self, writer: &mut W) {
         for m in self {
@@ -0,0 +1,229 @@
+//! Okamoto's identification protocol: knowledge of a
+//! representation `(x1, x2)` of `Y = x1·G + x2·H`
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use sha2::Sha512;
+
+use crate::error::Error;
+use crate::io::Write;
+use crate::message::{Message, ReadMessage};
+use crate::*;
+
+/// Second generator `H`, with unknown discrete log relative
+/// to `G`
+pub fn okamoto_h() -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(
+        b"sigmazk okamoto generator H",
+    )
+}
+
+/// A pair of scalars, used for Okamoto's witness, state and
+/// third message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct ScalarPair(
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::scalar")
+    )]
+    pub Scalar,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::scalar")
+    )]
+    pub Scalar,
+);
+
+impl ScalarPair {
+    pub fn random<R: CryptoRngCore>(rng: &mut R) -> Self {
+        ScalarPair(Scalar::random(rng), Scalar::random(rng))
+    }
+}
+
+impl Message for ScalarPair {
+    fn write<W: Write>(&self, writer: &mut W) {
+        self.0
+            .write(writer);
+        self.1
+            .write(writer);
+    }
+}
+
+impl ReadMessage for ScalarPair {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 64 {
+            return Err(Error::InvalidLength {
+                expected: 64,
+                got: bytes.len(),
+            });
+        }
+        let (x1, x2) = bytes.split_at(32);
+        Ok(ScalarPair(Scalar::read(x1)?, Scalar::read(x2)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Okamoto {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::serde_helpers::ristretto_point"
+        )
+    )]
+    pub pub_key: RistrettoPoint,
+}
+
+impl Okamoto {
+    /// Initialize the Okamoto protocol with a witness
+    pub fn init(witness: ScalarPair) -> Self {
+        let ScalarPair(x1, x2) = witness;
+        Okamoto {
+            pub_key: RISTRETTO_BASEPOINT_POINT * x1
+                + okamoto_h() * x2,
+        }
+    }
+}
+
+/// Sigma protocol implementation for Okamoto
+impl SigmaProtocol for Okamoto {
+    type Statement = Okamoto;
+    type Witness = ScalarPair;
+
+    type State = ScalarPair;
+    type MessageA = CompressedRistretto;
+    type Challenge = Scalar;
+    type MessageZ = ScalarPair;
+
+    /// First round: `a = r1·G + r2·H`
+    fn first<R: CryptoRngCore>(
+        _statement: &Okamoto,
+        _witness: &ScalarPair,
+        prover_rng: &mut R,
+    ) -> (Self::State, Self::MessageA) {
+        let state = ScalarPair::random(prover_rng);
+        let ScalarPair(r1, r2) = state;
+        let message = &r1 * RISTRETTO_BASEPOINT_TABLE
+            + okamoto_h() * r2;
+
+        (state, message.compress())
+    }
+
+    fn second<R: CryptoRngCore>(
+        verifier_rng: &mut R,
+    ) -> Self::Challenge {
+        Scalar::random(verifier_rng)
+    }
+
+    /// Third round: `z = (r1 + c·x1, r2 + c·x2)`
+    fn third<R: CryptoRngCore>(
+        _statement: &Okamoto,
+        state: ScalarPair,
+        witness: &ScalarPair,
+        challenge: &Scalar,
+        _prover_rng: &mut R,
+    ) -> Self::MessageZ {
+        ScalarPair(
+            state.0 + challenge * witness.0,
+            state.1 + challenge * witness.1,
+        )
+    }
+
+    /// Check `z1·G + z2·H == a + c·Y`
+    fn verify(
+        statement: &Okamoto,
+        a: &CompressedRistretto,
+        c: &Scalar,
+        z: &ScalarPair,
+    ) -> bool {
+        let Some(a) = a.decompress() else {
+            return false;
+        };
+        &z.0 * RISTRETTO_BASEPOINT_TABLE + okamoto_h() * z.1
+            - c * statement.pub_key
+            == a
+    }
+}
+
+/// Draws the simulated transcript from entropy, so it needs
+/// the `std` feature
+#[cfg(feature = "std")]
+impl HVzk for Okamoto {
+    fn simulate(
+        statement: &Self::Statement,
+    ) -> (Self::MessageA, Self::Challenge, Self::MessageZ)
+    {
+        let mut rng = ChaCha20Rng::from_entropy();
+        let z = ScalarPair::random(&mut rng);
+        let c = Scalar::random(&mut rng);
+        (
+            <Okamoto as EHVzk>::simulate(statement, &c, &z),
+            c,
+            z,
+        )
+    }
+}
+
+impl EHVzk for Okamoto {
+    fn simulate(
+        statement: &Self::Statement,
+        challenge: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> Self::MessageA {
+        (&z.0 * RISTRETTO_BASEPOINT_TABLE
+            + okamoto_h() * z.1
+            - challenge * statement.pub_key)
+            .compress()
+    }
+}
+
+impl ThreadSafeProtocol for Okamoto {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn okamoto_works() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let witness = ScalarPair::random(rng);
+        let statement = Okamoto::init(witness);
+
+        let (state, a) =
+            Okamoto::first(&statement, &witness, rng);
+        let c = Okamoto::second(rng);
+        let z = Okamoto::third(
+            &statement, state, &witness, &c, rng,
+        );
+        assert!(Okamoto::verify(&statement, &a, &c, &z));
+
+        // Knowing only one half of the representation fails
+        let wrong =
+            ScalarPair(witness.0, Scalar::random(rng));
+        let z = Okamoto::third(
+            &statement, state, &wrong, &c, rng,
+        );
+        assert!(!Okamoto::verify(&statement, &a, &c, &z));
+
+        #[cfg(feature = "std")]
+        assert!(<Okamoto as HVzk>::simulate_self_test(
+            &statement
+        ));
+    }
+
+    crate::sigma_conformance_tests! {
+        mod okamoto_conformance;
+        protocol: Okamoto,
+        generate: |rng| {
+            let witness = ScalarPair::random(rng);
+            (Okamoto::init(witness), witness)
+        },
+        seeds: [[0u8; 32], [1u8; 32], [2u8; 32]],
+    }
+}
@@ -1,18 +1,48 @@
 use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::traits::IsIdentity;
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
 
+use crate::error::Error;
 use crate::*;
 
-impl Challenge for Scalar {
-    fn new(bytes: &[u8; 64]) -> Self {
-        Scalar::from_bytes_mod_order_wide(bytes)
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Schnorr {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::serde_helpers::ristretto_point"
+        )
+    )]
     pub pub_key: RistrettoPoint,
 }
 
+/// Statement used to pad a stack of Schnorr clauses to a
+/// power of two.
+///
+/// The public key is a fixed point hashed from a label,
+/// so it is not the identity and nobody knows its discrete
+/// log. Padding clauses are never the binding index: they
+/// only ever get simulated transcripts, which verify like
+/// those of any other clause. A well-known key such as the
+/// basepoint (witness `1`) would not do, as anybody could
+/// then prove the disjunction through a padding clause.
+impl Default for Schnorr {
+    fn default() -> Self {
+        Schnorr {
+            pub_key: RistrettoPoint::hash_from_bytes::<
+                Sha512,
+            >(
+                b"sigmazk schnorr padding statement"
+            ),
+        }
+    }
+}
+
 /// Sigma protocol implementation for Schnorr
 impl SigmaProtocol for Schnorr {
     type Statement = Schnorr;
@@ -56,21 +86,29 @@ impl SigmaProtocol for Schnorr {
         challenge * witness + state
     }
 
-    /// Verification of transcript algorithm
+    /// Verification of transcript algorithm. The first
+    /// message is only decompressed here, and one that is
+    /// not a valid point is rejected.
+    ///
+    /// The final comparison uses [`ConstantTimeEq`] so that
+    /// its timing does not depend on how close a forged
+    /// first message is to the expected one.
     fn verify(
         statement: &Schnorr,
         a: &CompressedRistretto,
         c: &Scalar,
         z: &Scalar,
     ) -> bool {
-        // G * z  =?= a + c * H => G * z - c * H =?= a
-        RISTRETTO_BASEPOINT_TABLE * z
-            - c * statement.pub_key
-            == a.decompress()
-                .unwrap()
+        let Some(a) = a.decompress() else {
+            return false;
+        };
+        Schnorr::verify_point(statement, &a, c, z)
     }
 }
 
+/// Draws the simulated transcript from entropy, so it needs
+/// the `std` feature
+#[cfg(feature = "std")]
 impl HVzk for Schnorr {
     fn simulate(
         statement: &Self::Statement,
@@ -86,6 +124,8 @@ impl HVzk for Schnorr {
     }
 }
 
+impl ThreadSafeProtocol for Schnorr {}
+
 /// Implementation of EHVzk trait for Schnorr
 impl EHVzk for Schnorr {
     fn simulate(
@@ -107,4 +147,361 @@ impl Schnorr {
             pub_key: RISTRETTO_BASEPOINT_POINT * witness,
         }
     }
+
+    /// Whether the public key is not the identity point.
+    /// The identity is the public key of the witness zero,
+    /// which anybody knows, so proofs about it are
+    /// meaningless.
+    pub fn is_valid_statement(&self) -> bool {
+        !self
+            .pub_key
+            .is_identity()
+    }
+
+    /// Statement for the compressed public key `bytes`, e.g.
+    /// received over the network. Fails with
+    /// [`Error::InvalidPoint`] if `bytes` do not decode to a
+    /// Ristretto point.
+    pub fn from_compressed(
+        bytes: &[u8; 32],
+    ) -> Result<Self, Error> {
+        CompressedRistretto(*bytes)
+            .decompress()
+            .map(|pub_key| Schnorr { pub_key })
+            .ok_or(Error::InvalidPoint)
+    }
+
+    /// Compressed public key, the inverse of
+    /// [`Schnorr::from_compressed`]
+    pub fn to_compressed(&self) -> [u8; 32] {
+        self.pub_key
+            .compress()
+            .to_bytes()
+    }
+
+    /// Offset the public key by `offset * G`.
+    ///
+    /// Returns the rerandomized statement together with a
+    /// closure mapping a witness `x` of `self` to the
+    /// witness `x + offset` of the new statement.
+    pub fn rerandomize(
+        &self,
+        offset: Scalar,
+    ) -> (Schnorr, impl Fn(Scalar) -> Scalar) {
+        let statement = Schnorr {
+            pub_key: self.pub_key
+                + RISTRETTO_BASEPOINT_TABLE * &offset,
+        };
+        (statement, move |witness| witness + offset)
+    }
+
+    /// Verify a transcript whose first message is already
+    /// decompressed, e.g. by a caller decompressing many
+    /// first messages up front
+    pub fn verify_point(
+        statement: &Schnorr,
+        a: &RistrettoPoint,
+        c: &Scalar,
+        z: &Scalar,
+    ) -> bool {
+        // G * z  =?= a + c * H => G * z - c * H =?= a
+        (RISTRETTO_BASEPOINT_TABLE * z
+            - c * statement.pub_key)
+            .ct_eq(a)
+            .into()
+    }
+
+    /// Verify a transcript whose challenge and response are
+    /// still encoded, e.g. as received over the network.
+    ///
+    /// Rejects `c` and `z` unless they are canonical (see
+    /// [`ProofScalar`]), so that a proof accepted here has
+    /// no other encoding that is.
+    pub fn verify_strict(
+        statement: &Schnorr,
+        a: &CompressedRistretto,
+        c: &[u8; 32],
+        z: &[u8; 32],
+    ) -> bool {
+        let (Some(c), Some(z)) = (
+            ProofScalar::from_bytes_canonical(*c),
+            ProofScalar::from_bytes_canonical(*z),
+        ) else {
+            return false;
+        };
+        Schnorr::verify(statement, a, &c.0, &z.0)
+    }
+
+    /// Special soundness extractor.
+    ///
+    /// Given two accepting transcripts `(a, c1, z1)` and
+    /// `(a, c2, z2)` sharing the first message, returns the
+    /// witness `(z1 - z2) / (c1 - c2)`, or `None` if the
+    /// challenges are equal.
+    pub fn extract(
+        (c1, z1): (&Scalar, &Scalar),
+        (c2, z2): (&Scalar, &Scalar),
+    ) -> Option<Scalar> {
+        if c1 == c2 {
+            return None;
+        }
+        Some((z1 - z2) * (c1 - c2).invert())
+    }
+}
+
+/// Statement for a compressed public key, see
+/// [`Schnorr::from_compressed`]
+impl TryFrom<&[u8; 32]> for Schnorr {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8; 32]) -> Result<Self, Error> {
+        Schnorr::from_compressed(bytes)
+    }
+}
+
+/// Scalar of a Schnorr proof, i.e. a challenge or a
+/// response, parsed from its 32-byte encoding.
+///
+/// Every scalar has one canonical encoding, its value
+/// below the group order, but any 32 bytes can be reduced
+/// to a scalar. Accepting reduced encodings makes proofs
+/// malleable: the same proof has several encodings that all
+/// verify. Deserializers of Schnorr and CDS proofs, like
+/// [`ReadMessage`](crate::message::ReadMessage) and the
+/// serde helpers, use
+/// [`ProofScalar::from_bytes_canonical`].
+/// [`ProofScalar::from_bytes_reduced`] is for bytes that
+/// are not an encoding, e.g. the output of a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProofScalar(pub Scalar);
+
+impl ProofScalar {
+    /// Reduce `bytes` modulo the group order. Never fails,
+    /// and distinct `bytes` may give the same scalar.
+    pub fn from_bytes_reduced(bytes: [u8; 32]) -> Self {
+        ProofScalar(Scalar::from_bytes_mod_order(bytes))
+    }
+
+    /// Scalar of the canonical encoding `bytes`, or `None`
+    /// if `bytes` are not below the group order
+    pub fn from_bytes_canonical(
+        bytes: [u8; 32],
+    ) -> Option<Self> {
+        Option::from(Scalar::from_canonical_bytes(bytes))
+            .map(ProofScalar)
+    }
+
+    pub fn scalar(&self) -> &Scalar {
+        &self.0
+    }
+}
+
+/// Same as [`ProofScalar::from_bytes_reduced`]
+impl From<[u8; 32]> for ProofScalar {
+    fn from(bytes: [u8; 32]) -> Self {
+        ProofScalar::from_bytes_reduced(bytes)
+    }
+}
+
+/// Same as [`ProofScalar::from_bytes_canonical`], failing
+/// with [`Error::InvalidLength`] unless there are 32 bytes
+/// and [`Error::InvalidScalar`] if they are not canonical
+impl TryFrom<&[u8]> for ProofScalar {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                got: bytes.len(),
+            })?;
+        ProofScalar::from_bytes_canonical(bytes)
+            .ok_or(Error::InvalidScalar)
+    }
+}
+
+impl From<ProofScalar> for Scalar {
+    fn from(scalar: ProofScalar) -> Scalar {
+        scalar.0
+    }
+}
+
+/// Non-interactive proof that the public keys `P1 = x·G`
+/// and `P2 = x·H` of two generators share the secret `x`
+/// (Chaum-Pedersen), made by
+/// [`Schnorr::prove_same_witness`].
+///
+/// Both first messages use the same randomness `r`, and the
+/// one response `z = r + c·x` answers for both keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct SameWitnessProof {
+    /// `r·G`
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::serde_helpers::compressed_ristretto"
+        )
+    )]
+    pub a1: CompressedRistretto,
+    /// `r·H`
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::serde_helpers::compressed_ristretto"
+        )
+    )]
+    pub a2: CompressedRistretto,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serde_helpers::scalar")
+    )]
+    pub z: Scalar,
+}
+
+impl Schnorr {
+    /// Prove that `p1 = x·g` and `p2 = x·h` for the same
+    /// `x`, e.g. that a rotated key under a new generator
+    /// belongs to the same secret.
+    ///
+    /// The challenge is the Fiat-Shamir hash of the
+    /// generators, the public keys and the first messages.
+    /// A proof for keys that do not share `x` does not
+    /// verify.
+    pub fn prove_same_witness<R: CryptoRngCore>(
+        g: &RistrettoPoint,
+        h: &RistrettoPoint,
+        p1: &RistrettoPoint,
+        p2: &RistrettoPoint,
+        x: &Scalar,
+        rng: &mut R,
+    ) -> SameWitnessProof {
+        let r = Scalar::random(rng);
+        let (a1, a2) =
+            ((r * g).compress(), (r * h).compress());
+        let c =
+            same_witness_challenge(g, h, p1, p2, &a1, &a2);
+        SameWitnessProof {
+            a1,
+            a2,
+            z: r + c * x,
+        }
+    }
+
+    /// Verify a proof of [`Schnorr::prove_same_witness`]:
+    /// `z·G = a1 + c·P1` and `z·H = a2 + c·P2`
+    pub fn verify_same_witness(
+        g: &RistrettoPoint,
+        h: &RistrettoPoint,
+        p1: &RistrettoPoint,
+        p2: &RistrettoPoint,
+        proof: &SameWitnessProof,
+    ) -> bool {
+        let (Some(a1), Some(a2)) = (
+            proof
+                .a1
+                .decompress(),
+            proof
+                .a2
+                .decompress(),
+        ) else {
+            return false;
+        };
+        let c = same_witness_challenge(
+            g, h, p1, p2, &proof.a1, &proof.a2,
+        );
+        let z = proof.z;
+        ((z * g - c * p1).ct_eq(&a1)
+            & (z * h - c * p2).ct_eq(&a2))
+        .into()
+    }
+}
+
+/// Fiat-Shamir challenge of a [`SameWitnessProof`]
+fn same_witness_challenge(
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    p1: &RistrettoPoint,
+    p2: &RistrettoPoint,
+    a1: &CompressedRistretto,
+    a2: &CompressedRistretto,
+) -> Scalar {
+    let mut hash = Sha512::new();
+    hash.update(b"sigmazk schnorr same witness");
+    for point in [g, h, p1, p2] {
+        hash.update(
+            point
+                .compress()
+                .as_bytes(),
+        );
+    }
+    hash.update(a1.as_bytes());
+    hash.update(a2.as_bytes());
+    Scalar::from_hash(hash)
+}
+
+/// Verifier side of an interactive run of Schnorr's
+/// protocol, owning the randomness challenges are drawn
+/// from and checking the response against the challenge it
+/// issued last.
+///
+/// [`SchnorrVerifier::new`] seeds from entropy.
+/// [`SchnorrVerifier::with_seed`] makes the challenges
+/// reproducible, e.g. to replay a run in simulations or
+/// fuzzing.
+#[derive(Clone, Debug)]
+pub struct SchnorrVerifier {
+    rng: ChaCha20Rng,
+    challenge: Option<Scalar>,
+}
+
+impl SchnorrVerifier {
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        Self {
+            rng: ChaCha20Rng::from_entropy(),
+            challenge: None,
+        }
+    }
+
+    pub fn with_seed(seed: [u8; 32]) -> Self {
+        Self {
+            rng: ChaCha20Rng::from_seed(seed),
+            challenge: None,
+        }
+    }
+
+    /// Issue the next challenge with [`Schnorr::second`],
+    /// replacing the one issued before
+    pub fn second(&mut self) -> Scalar {
+        let challenge = Schnorr::second(&mut self.rng);
+        self.challenge = Some(challenge);
+        challenge
+    }
+
+    /// Verify the transcript `(a, c, z)` where `c` is the
+    /// challenge this verifier issued last. Rejects if it has
+    /// not issued one.
+    pub fn verify(
+        &self,
+        statement: &Schnorr,
+        a: &CompressedRistretto,
+        z: &Scalar,
+    ) -> bool {
+        self.challenge
+            .is_some_and(|c| {
+                Schnorr::verify(statement, a, &c, z)
+            })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SchnorrVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
 }
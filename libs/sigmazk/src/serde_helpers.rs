@@ -0,0 +1,147 @@
+//! Serde encodings shared by every crate of the workspace,
+//! enabled by the `serde` feature.
+//!
+//! Group elements always use their 32-byte wire encoding:
+//!
+//! - a [`Scalar`] is its canonical little-endian encoding,
+//!   non-canonical scalars are rejected;
+//! - a [`RistrettoPoint`] is its compressed encoding, bytes
+//!   that do not decompress are rejected;
+//! - a [`CompressedRistretto`] is its 32 bytes as they are,
+//!   it is only checked when it is decompressed;
+//! - a `WrappedScalar` is its inner [`Scalar`].
+//!
+//! This is the same encoding curve25519-dalek uses for
+//! these types with its own `serde` feature, which is
+//! enabled alongside ours, so the associated message types
+//! of a protocol need no attributes. The modules below are
+//! meant for `#[serde(with = "...")]` on fields that hold
+//! one of these types directly, so that the convention is
+//! spelled out where it is used.
+use curve25519_dalek::ristretto::{
+    CompressedRistretto, RistrettoPoint,
+};
+use curve25519_dalek::scalar::Scalar;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Canonical 32-byte encoding of a [`Scalar`]
+pub mod scalar {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        scalar: &Scalar,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(
+            &scalar.to_bytes(),
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Scalar, D::Error> {
+        let bytes = <[u8; 32]>::deserialize(deserializer)?;
+        Option::from(Scalar::from_canonical_bytes(bytes))
+            .ok_or_else(|| {
+                D::Error::custom("non-canonical scalar")
+            })
+    }
+}
+
+/// Compressed 32-byte encoding of a [`RistrettoPoint`]
+pub mod ristretto_point {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        point: &RistrettoPoint,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        compressed_ristretto::serialize(
+            &point.compress(),
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RistrettoPoint, D::Error> {
+        compressed_ristretto::deserialize(deserializer)?
+            .decompress()
+            .ok_or_else(|| {
+                D::Error::custom("invalid ristretto point")
+            })
+    }
+}
+
+/// The 32 bytes of a [`CompressedRistretto`]
+pub mod compressed_ristretto {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        point: &CompressedRistretto,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(
+            point.as_bytes(),
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<CompressedRistretto, D::Error> {
+        <[u8; 32]>::deserialize(deserializer)
+            .map(CompressedRistretto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Fields {
+        #[serde(with = "scalar")]
+        scalar: Scalar,
+        #[serde(with = "ristretto_point")]
+        point: RistrettoPoint,
+    }
+
+    #[test]
+    fn helpers_match_dalek_encoding() {
+        let fields = Fields {
+            scalar: Scalar::from(7u64),
+            point: RISTRETTO_BASEPOINT_POINT,
+        };
+        let ours = serde_json::to_value(&fields).unwrap();
+        assert_eq!(
+            ours["scalar"],
+            serde_json::to_value(fields.scalar).unwrap()
+        );
+        assert_eq!(
+            ours["point"],
+            serde_json::to_value(fields.point).unwrap()
+        );
+
+        let back: Fields =
+            serde_json::from_value(ours).unwrap();
+        assert_eq!(back.scalar, fields.scalar);
+        assert_eq!(back.point, fields.point);
+    }
+
+    #[test]
+    fn helpers_reject_invalid_bytes() {
+        let bytes =
+            serde_json::to_string(&[0xffu8; 32]).unwrap();
+        let json = format!(
+            "{{\"scalar\":{bytes},\"point\":{bytes}}}"
+        );
+        assert!(
+            serde_json::from_str::<Fields>(&json).is_err()
+        );
+    }
+}
@@ -1,11 +1,22 @@
 //! This module defines traits related to Sigma protocols
 
+use curve25519_dalek::scalar::Scalar;
 use rand_core::CryptoRngCore;
 
+use crate::challenge_commitment::ChallengeCommitment;
+use crate::error::Error;
+use crate::message::Message;
+
 pub trait Challenge {
     fn new(bytes: &[u8; 64]) -> Self;
 }
 
+impl Challenge for Scalar {
+    fn new(bytes: &[u8; 64]) -> Self {
+        Scalar::from_bytes_mod_order_wide(bytes)
+    }
+}
+
 /// Trait for Sigma protocols
 ///
 /// This trait defines the methods that a general sigma
@@ -53,6 +64,41 @@ pub trait SigmaProtocol {
     where
         Self: Sized;
 
+    /// Second message drawn from `[0, 2^bits)` rather than
+    /// from the whole challenge space: `bits` random bits,
+    /// zero-extended and passed to [`Challenge::new`] as a
+    /// little-endian integer.
+    ///
+    /// A cheating prover succeeds with probability about
+    /// `2^-bits` instead of `1 / |challenge space|`, in
+    /// exchange for a challenge that takes `bits` bits to
+    /// send or store. For Schnorr, 128 bits match the
+    /// security level of the group; the response is still a
+    /// full scalar. Compilers that secret-share the
+    /// challenge, such as CDS94, need challenges from the
+    /// whole field and must not use this.
+    ///
+    /// Panics if `bits > 512`.
+    fn second_bounded<R: CryptoRngCore + Clone>(
+        verifier_rng: &mut R,
+        bits: u32,
+    ) -> Self::Challenge
+    where
+        Self: Sized,
+    {
+        assert!(
+            bits <= 512,
+            "a challenge has at most 512 bits"
+        );
+        let mut buffer = [0u8; 64];
+        let len = bits.div_ceil(8) as usize;
+        verifier_rng.fill_bytes(&mut buffer[..len]);
+        if !bits.is_multiple_of(8) {
+            buffer[len - 1] &= (1u8 << (bits % 8)) - 1;
+        }
+        Challenge::new(&buffer)
+    }
+
     /// The third message in a Sigma protocol (sent by the
     /// Prover).
     fn third<R: CryptoRngCore + Clone>(
@@ -90,4 +136,98 @@ pub trait SigmaProtocol {
     ) -> bool
     where
         Self: Sized;
+
+    /// Draw a challenge and the [`ChallengeCommitment`] the
+    /// verifier sends before the prover's first message.
+    /// The challenge is revealed after the first message is
+    /// received.
+    fn verifier_commit<R: CryptoRngCore + Clone>(
+        verifier_rng: &mut R,
+    ) -> (ChallengeCommitment, Self::Challenge)
+    where
+        Self: Sized,
+        Self::Challenge: Message,
+    {
+        let c = Self::second(verifier_rng);
+        (ChallengeCommitment::commit(&c), c)
+    }
+
+    /// [`SigmaProtocol::verify`] for a run whose challenge
+    /// was committed to with
+    /// [`SigmaProtocol::verifier_commit`].
+    ///
+    /// Fails with [`Error::ChallengeMismatch`] if `c` is not
+    /// the challenge `commitment` was computed from.
+    fn verify_committed(
+        statement: &Self::Statement,
+        commitment: &ChallengeCommitment,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> Result<bool, Error>
+    where
+        Self: Sized,
+        Self::Challenge: Message,
+    {
+        if !commitment.opens(c) {
+            return Err(Error::ChallengeMismatch);
+        }
+        Ok(Self::verify(statement, a, c, z))
+    }
+}
+
+/// Marker for Sigma protocols whose statements, witnesses,
+/// state and messages can be shared across threads, e.g. to
+/// prove or verify many instances in parallel.
+///
+/// [`Schnorr`](crate::Schnorr) qualifies. Protocols built
+/// from `Rc`, such as the stacking compilers whose
+/// commitments hold `Rc`-shared messages and public
+/// parameters, do not.
+pub trait ThreadSafeProtocol:
+    SigmaProtocol<
+        Statement: Send + Sync,
+        Witness: Send + Sync,
+        MessageA: Send + Sync,
+        Challenge: Send + Sync,
+        MessageZ: Send + Sync,
+        State: Send + Sync,
+    > + Send
+    + Sync
+{
+}
+
+/// Sigma protocol whose prover needs inputs besides the
+/// statement, witness and randomness, e.g. the opening of a
+/// commitment the witness is kept in.
+///
+/// The context is private to the prover: verification and
+/// simulation do not take it. Compilers forward it to the
+/// clauses they prove honestly, see
+/// `stacksig_compiler::SelfStacker`.
+pub trait ContextualSigmaProtocol: SigmaProtocol {
+    /// Auxiliary input of the prover
+    type ProverContext;
+
+    /// [`SigmaProtocol::first`] given `prover_context`
+    fn first_with_context<R: CryptoRngCore + Clone>(
+        statement: &Self::Statement,
+        witness: &Self::Witness,
+        prover_rng: &mut R,
+        prover_context: &Self::ProverContext,
+    ) -> (Self::State, Self::MessageA)
+    where
+        Self: Sized;
+
+    /// [`SigmaProtocol::third`] given `prover_context`
+    fn third_with_context<R: CryptoRngCore + Clone>(
+        statement: &Self::Statement,
+        state: Self::State,
+        witness: &Self::Witness,
+        challenge: &Self::Challenge,
+        prover_rng: &mut R,
+        prover_context: &Self::ProverContext,
+    ) -> Self::MessageZ
+    where
+        Self: Sized;
 }
@@ -0,0 +1,49 @@
+//! Stable fingerprints of statements, e.g. to key caches of
+//! precomputed verification data
+
+#[cfg(feature = "schnorr")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "schnorr")]
+use crate::Schnorr;
+
+pub trait StatementDigest {
+    /// 32-byte fingerprint of the statement. Equal
+    /// statements have equal digests.
+    fn digest(&self) -> [u8; 32];
+}
+
+#[cfg(feature = "schnorr")]
+impl StatementDigest for Schnorr {
+    fn digest(&self) -> [u8; 32] {
+        let mut hash = Sha256::new();
+        hash.update(b"sigmazk schnorr");
+        hash.update(
+            self.pub_key
+                .compress()
+                .as_bytes(),
+        );
+        hash.finalize()
+            .into()
+    }
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use curve25519_dalek::Scalar;
+
+    use super::*;
+
+    #[test]
+    fn schnorr_digest() {
+        let one = Schnorr::init(Scalar::ONE);
+        assert_eq!(
+            one.digest(),
+            Schnorr::init(Scalar::ONE).digest()
+        );
+        assert_ne!(
+            one.digest(),
+            Schnorr::init(Scalar::from(2u64)).digest()
+        );
+    }
+}
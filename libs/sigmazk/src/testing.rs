@@ -0,0 +1,425 @@
+//! Statistical checks for tests of protocols and compilers,
+//! enabled by the `testing` feature.
+//!
+//! The OR compilers are only sound to use if a proof does
+//! not reveal which clause the prover knows a witness for,
+//! i.e. if proofs made with different witnesses of the same
+//! statement are identically distributed.
+//! [`assert_wi`] samples proofs under two witnesses and
+//! compares the frequency of every bit of their encodings,
+//! which catches a message that is fixed, biased or shaped
+//! differently depending on the witness.
+//!
+//! [`sigma_conformance_tests!`](crate::sigma_conformance_tests)
+//! generates the tests every new protocol should pass, on
+//! top of the `check_*` functions below.
+
+use std::collections::BTreeMap;
+
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+use crate::message::Message;
+use crate::{EHVzk, SigmaProtocol};
+
+/// Number of standard deviations by which the frequencies
+/// of a bit may differ between the two witnesses. It is
+/// large enough that the thousands of bits compared for a
+/// compiled proof do not fail by chance.
+pub const WI_TOLERANCE: f64 = 6.0;
+
+/// Panics if proofs of `statement` made with `witness_a`
+/// can be told apart from those made with `witness_b`.
+///
+/// `samples` proofs are made under each witness, answering
+/// challenges drawn by [`SigmaProtocol::second`], and every
+/// proof must verify. The proof `(a, z)` is encoded with
+/// [`Message::write`], see [`assert_wi_with`] for protocols
+/// whose messages are encoded otherwise.
+pub fn assert_wi<S: SigmaProtocol>(
+    statement: &S::Statement,
+    witness_a: &S::Witness,
+    witness_b: &S::Witness,
+    samples: usize,
+) where
+    S::MessageA: Message,
+    S::MessageZ: Message,
+{
+    assert_wi_with::<S, _>(
+        statement,
+        witness_a,
+        witness_b,
+        samples,
+        |a, z| {
+            let mut bytes = Vec::new();
+            a.write(&mut bytes);
+            z.write(&mut bytes);
+            bytes
+        },
+    );
+}
+
+/// [`assert_wi`] with the proof `(a, z)` encoded by
+/// `encode`. Only the bytes returned by `encode` are
+/// compared, so it should include every part of the proof
+/// that is sent to the verifier.
+pub fn assert_wi_with<S, E>(
+    statement: &S::Statement,
+    witness_a: &S::Witness,
+    witness_b: &S::Witness,
+    samples: usize,
+    encode: E,
+) where
+    S: SigmaProtocol,
+    E: Fn(&S::MessageA, &S::MessageZ) -> Vec<u8>,
+{
+    assert!(samples > 0, "at least one sample is required");
+    // Fixed seeds keep a failure reproducible
+    let sample = |witness, seed| {
+        Histogram::sample::<S, _>(
+            statement,
+            witness,
+            samples,
+            &encode,
+            &mut ChaCha20Rng::from_seed(seed),
+        )
+    };
+    let a = sample(witness_a, [0u8; 32]);
+    let b = sample(witness_b, [1u8; 32]);
+
+    let lengths = a
+        .lengths
+        .keys()
+        .chain(
+            b.lengths
+                .keys(),
+        );
+    for length in lengths {
+        let count = |h: &Histogram| {
+            h.lengths
+                .get(length)
+                .copied()
+                .unwrap_or(0)
+        };
+        assert!(
+            !distinguishable(count(&a), count(&b), samples),
+            "{} of {} proofs are {} bytes long under \
+             witness_a, {} under witness_b",
+            count(&a),
+            samples,
+            length,
+            count(&b)
+        );
+    }
+
+    let bits = a
+        .ones
+        .len()
+        .max(
+            b.ones
+                .len(),
+        );
+    for bit in 0..bits {
+        let count = |h: &Histogram| {
+            h.ones
+                .get(bit)
+                .copied()
+                .unwrap_or(0)
+        };
+        assert!(
+            !distinguishable(count(&a), count(&b), samples),
+            "bit {} of the proof is set in {} of {} proofs \
+             under witness_a, {} under witness_b",
+            bit,
+            count(&a),
+            samples,
+            count(&b)
+        );
+    }
+}
+
+/// Encoded lengths, and how often every bit of the
+/// encodings is set, over a number of proofs
+struct Histogram {
+    lengths: BTreeMap<usize, usize>,
+    ones: Vec<usize>,
+}
+
+impl Histogram {
+    fn sample<S: SigmaProtocol, E>(
+        statement: &S::Statement,
+        witness: &S::Witness,
+        samples: usize,
+        encode: &E,
+        rng: &mut ChaCha20Rng,
+    ) -> Self
+    where
+        E: Fn(&S::MessageA, &S::MessageZ) -> Vec<u8>,
+    {
+        let mut histogram = Histogram {
+            lengths: BTreeMap::new(),
+            ones: Vec::new(),
+        };
+        for _ in 0..samples {
+            let (state, a) =
+                S::first(statement, witness, rng);
+            let c = S::second(rng);
+            let z = S::third(
+                statement, state, witness, &c, rng,
+            );
+            assert!(
+                S::verify(statement, &a, &c, &z),
+                "a proof made with the given witness does \
+                 not verify"
+            );
+
+            let bytes = encode(&a, &z);
+            *histogram
+                .lengths
+                .entry(bytes.len())
+                .or_default() += 1;
+            if histogram
+                .ones
+                .len()
+                < 8 * bytes.len()
+            {
+                histogram
+                    .ones
+                    .resize(8 * bytes.len(), 0);
+            }
+            for (i, byte) in bytes
+                .iter()
+                .enumerate()
+            {
+                for j in 0..8 {
+                    histogram.ones[8 * i + j] +=
+                        usize::from(byte >> j & 1 == 1);
+                }
+            }
+        }
+        histogram
+    }
+}
+
+/// Two-proportion test: whether `a` and `b` successes out
+/// of `samples` trials each differ by more than
+/// [`WI_TOLERANCE`] standard deviations of the pooled
+/// proportion
+fn distinguishable(
+    a: usize,
+    b: usize,
+    samples: usize,
+) -> bool {
+    let n = samples as f64;
+    let pooled = (a + b) as f64 / (2.0 * n);
+    let deviation =
+        (pooled * (1.0 - pooled) * 2.0 / n).sqrt();
+    (a as f64 - b as f64).abs() / n
+        > WI_TOLERANCE * deviation
+}
+
+/// Proof `(a, c, z)` of `statement` made with `witness`,
+/// drawing every random value from `rng`
+fn prove<S: SigmaProtocol>(
+    statement: &S::Statement,
+    witness: &S::Witness,
+    rng: &mut ChaCha20Rng,
+) -> (S::MessageA, S::Challenge, S::MessageZ) {
+    let (state, a) = S::first(statement, witness, rng);
+    let c = S::second(rng);
+    let z = S::third(statement, state, witness, &c, rng);
+    (a, c, z)
+}
+
+/// Panics unless a proof made with the witness returned by
+/// `generate` verifies against its statement
+pub fn check_completeness<S, G>(generate: G, seed: [u8; 32])
+where
+    S: SigmaProtocol,
+    G: Fn(&mut ChaCha20Rng) -> (S::Statement, S::Witness),
+{
+    let rng = &mut ChaCha20Rng::from_seed(seed);
+    let (statement, witness) = generate(rng);
+    let (a, c, z) = prove::<S>(&statement, &witness, rng);
+    assert!(
+        S::verify(&statement, &a, &c, &z),
+        "an honest proof does not verify"
+    );
+}
+
+/// Panics if a proof made with the witness of another
+/// instance returned by `generate` verifies against the
+/// first statement. This is a smoke test: a sound protocol
+/// only rejects such a proof with overwhelming probability.
+pub fn check_soundness<S, G>(generate: G, seed: [u8; 32])
+where
+    S: SigmaProtocol,
+    G: Fn(&mut ChaCha20Rng) -> (S::Statement, S::Witness),
+{
+    let rng = &mut ChaCha20Rng::from_seed(seed);
+    let (statement, _) = generate(rng);
+    let (_, wrong_witness) = generate(rng);
+    let (a, c, z) =
+        prove::<S>(&statement, &wrong_witness, rng);
+    assert!(
+        !S::verify(&statement, &a, &c, &z),
+        "a proof made with a wrong witness verifies"
+    );
+}
+
+/// Panics unless the first message simulated for the
+/// response of an honest proof and a fresh challenge
+/// verifies, see [`EHVzk::simulate_self_test`]
+pub fn check_hvzk<S, G>(generate: G, seed: [u8; 32])
+where
+    S: EHVzk,
+    G: Fn(&mut ChaCha20Rng) -> (S::Statement, S::Witness),
+{
+    let rng = &mut ChaCha20Rng::from_seed(seed);
+    let (statement, witness) = generate(rng);
+    let (_, _, z) = prove::<S>(&statement, &witness, rng);
+    assert!(
+        S::simulate_self_test(&statement, &z, rng),
+        "a simulated transcript does not verify"
+    );
+}
+
+/// Panics unless two proofs made from the same `seed` have
+/// the same encoding under [`Message::write`]
+pub fn check_determinism<S, G>(generate: G, seed: [u8; 32])
+where
+    S: SigmaProtocol,
+    S::MessageA: Message,
+    S::MessageZ: Message,
+    G: Fn(&mut ChaCha20Rng) -> (S::Statement, S::Witness),
+{
+    let encode = || {
+        let rng = &mut ChaCha20Rng::from_seed(seed);
+        let (statement, witness) = generate(rng);
+        let (a, _, z) =
+            prove::<S>(&statement, &witness, rng);
+        let mut bytes = Vec::new();
+        a.write(&mut bytes);
+        z.write(&mut bytes);
+        bytes
+    };
+    assert!(
+        encode() == encode(),
+        "proofs made from the same seed differ"
+    );
+}
+
+/// Generates the tests every sigma protocol should pass:
+/// completeness, a soundness smoke test, HVZK of the
+/// [`EHVzk`] simulator and determinism under a fixed seed.
+///
+/// The tests are put in a module of the given name, one
+/// `#[test]` function per property, each of which runs the
+/// matching `check_*` function of [`crate::testing`] for
+/// every seed. `generate` is a closure from a
+/// `&mut ChaCha20Rng` to a random statement and a witness
+/// for it. The protocol must implement [`EHVzk`] and its
+/// first and last messages [`Message`].
+///
+/// The expansion calls into this module, so the macro is
+/// only defined with the `testing` feature of `sigmazk`,
+/// which a crate using it enables for its tests, e.g. as a
+/// dev-dependency.
+///
+/// ```ignore
+/// sigma_conformance_tests! {
+///     mod schnorr_conformance;
+///     protocol: Schnorr,
+///     generate: |rng| {
+///         let witness = Scalar::random(rng);
+///         (Schnorr::init(witness), witness)
+///     },
+///     seeds: [[0u8; 32], [1u8; 32]],
+/// }
+/// ```
+#[macro_export]
+macro_rules! sigma_conformance_tests {
+    (
+        mod $name:ident;
+        protocol: $protocol:ty,
+        generate: $generate:expr,
+        seeds: [$($seed:expr),+ $(,)?] $(,)?
+    ) => {
+        mod $name {
+            use super::*;
+
+            #[test]
+            pub fn completeness() {
+                for seed in [$($seed),+] {
+                    $crate::testing::check_completeness::<
+                        $protocol,
+                        _,
+                    >($generate, seed);
+                }
+            }
+
+            #[test]
+            pub fn soundness() {
+                for seed in [$($seed),+] {
+                    $crate::testing::check_soundness::<
+                        $protocol,
+                        _,
+                    >($generate, seed);
+                }
+            }
+
+            #[test]
+            pub fn hvzk() {
+                for seed in [$($seed),+] {
+                    $crate::testing::check_hvzk::<
+                        $protocol,
+                        _,
+                    >($generate, seed);
+                }
+            }
+
+            #[test]
+            pub fn determinism() {
+                for seed in [$($seed),+] {
+                    $crate::testing::check_determinism::<
+                        $protocol,
+                        _,
+                    >($generate, seed);
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distinguishable_within_tolerance() {
+        // Constant bits are never distinguishable
+        assert!(!distinguishable(0, 0, 100));
+        assert!(!distinguishable(100, 100, 100));
+        // Noise of a fair bit
+        assert!(!distinguishable(480, 530, 1000));
+        // A bit that is fixed under one witness only
+        assert!(distinguishable(0, 500, 1000));
+        assert!(distinguishable(1000, 480, 1000));
+    }
+
+    #[test]
+    #[cfg(feature = "schnorr")]
+    #[should_panic(expected = "wrong witness verifies")]
+    fn soundness_check_catches_a_valid_witness() {
+        use crate::Schnorr;
+        use curve25519_dalek::scalar::Scalar;
+
+        // Every instance is the same, so the "wrong" witness
+        // is a valid one
+        let witness = Scalar::from(7u64);
+        check_soundness::<Schnorr, _>(
+            |_| (Schnorr::init(witness), witness),
+            [0u8; 32],
+        );
+    }
+}
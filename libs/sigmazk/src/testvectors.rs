@@ -0,0 +1,131 @@
+//! Deterministic Schnorr test vectors, to check that other
+//! implementations interoperate with this one and to catch
+//! accidental changes to the encodings.
+//!
+//! Every encoding is the one written by [`Message`]: the
+//! statement is the compressed public key, the witness and
+//! challenge are canonical little-endian scalars, and the
+//! proof is the compressed first message followed by the
+//! third message.
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+
+use crate::error::Error;
+use crate::message::{Message, ReadMessage};
+use crate::*;
+
+/// A Schnorr conversation and the witness it was proven
+/// with, encoded as bytes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchnorrTestVector {
+    pub statement: [u8; 32],
+    pub witness: [u8; 32],
+    pub challenge: [u8; 32],
+    pub proof: [u8; 64],
+}
+
+/// Test vector whose witness, nonce and challenge are drawn
+/// in this order from a [`ChaCha20Rng`] seeded with `seed`
+pub fn schnorr_vector(seed: [u8; 32]) -> SchnorrTestVector {
+    let rng = &mut ChaCha20Rng::from_seed(seed);
+    let witness = Scalar::random(rng);
+    let statement = Schnorr::init(witness);
+    let (state, a) =
+        Schnorr::first(&statement, &witness, rng);
+    let challenge = Schnorr::second(rng);
+    let z = Schnorr::third(
+        &statement, state, &witness, &challenge, rng,
+    );
+
+    let mut proof = Vec::with_capacity(64);
+    a.write(&mut proof);
+    z.write(&mut proof);
+    SchnorrTestVector {
+        statement: statement.to_compressed(),
+        witness: witness.to_bytes(),
+        challenge: challenge.to_bytes(),
+        proof: proof
+            .try_into()
+            .unwrap(),
+    }
+}
+
+/// Test vectors for the seeds `[0; 32]` to `[count - 1; 32]`
+pub fn schnorr_vectors(
+    count: u8,
+) -> Vec<SchnorrTestVector> {
+    (0..count)
+        .map(|i| schnorr_vector([i; 32]))
+        .collect()
+}
+
+/// Decodes a test vector and checks that its witness opens
+/// its statement and its proof verifies. Fails with
+/// [`Error::InvalidPoint`] or [`Error::InvalidScalar`] if a
+/// field does not decode.
+pub fn verify_schnorr_vector(
+    vector: &SchnorrTestVector,
+) -> Result<bool, Error> {
+    let statement =
+        Schnorr::from_compressed(&vector.statement)?;
+    let witness = Scalar::read(&vector.witness)?;
+    let challenge = Scalar::read(&vector.challenge)?;
+    let a = CompressedRistretto::read(&vector.proof[..32])?;
+    let z = Scalar::read(&vector.proof[32..])?;
+
+    Ok(Schnorr::init(witness) == statement
+        && Schnorr::verify(&statement, &a, &challenge, &z))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Hex encoding of `schnorr_vector([0; 32])`. Changing
+    /// these breaks interoperability with existing proofs.
+    const EXPECTED_STATEMENT: &str = "9c66a339c8344f922fc3206cb5dae814a594c0177dd3235c254d9c409a65b808";
+    const EXPECTED_WITNESS: &str = "4a53c3fbbc59970ee5f85af813875dffc13a904a2e53ae7e65fa0dea6e62c901";
+    const EXPECTED_CHALLENGE: &str = "ac12af423cc2cb0ac7f960078ef5690783f9f5ccb50340827188de522a16740d";
+    const EXPECTED_PROOF: &str = "e29f549f8d607e862859a946968ce3b14f917abf5afbb30006099c43e793386e1f22be10c1cbeb58c44ca4c0ead313a901c4a653406cf9a81084a1d7fdcae10e";
+
+    #[test]
+    fn schnorr_vectors_match_expected() {
+        let vector = schnorr_vector([0u8; 32]);
+        assert_eq!(
+            hex(&vector.statement),
+            EXPECTED_STATEMENT
+        );
+        assert_eq!(hex(&vector.witness), EXPECTED_WITNESS);
+        assert_eq!(
+            hex(&vector.challenge),
+            EXPECTED_CHALLENGE
+        );
+        assert_eq!(hex(&vector.proof), EXPECTED_PROOF);
+        assert!(verify_schnorr_vector(&vector).unwrap());
+    }
+
+    #[test]
+    fn schnorr_vectors_verify() {
+        for vector in schnorr_vectors(8) {
+            assert!(verify_schnorr_vector(&vector).unwrap());
+        }
+    }
+
+    #[test]
+    fn tampered_schnorr_vector_fails() {
+        let mut vector = schnorr_vector([1u8; 32]);
+        vector.proof[32] ^= 1;
+        assert!(!verify_schnorr_vector(&vector).unwrap());
+
+        let mut vector = schnorr_vector([1u8; 32]);
+        vector.statement = [0xff; 32];
+        assert!(verify_schnorr_vector(&vector).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Messages exchanged so far in a run of a Sigma protocol
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::error::Error;
+use crate::message::Message;
+#[cfg(feature = "schnorr")]
+use crate::Schnorr;
+use crate::SigmaProtocol;
+
+/// Transcript for Schnorr's protocol
+#[cfg(feature = "schnorr")]
+pub type SchnorrTranscript = Transcript<Schnorr>;
+
+/// The messages `(a, c, z)` of a run of a Sigma protocol,
+/// filled in round by round.
+///
+/// A transcript is *new* until the first message is set,
+/// then *commited*, *challenged* and finally *proven*.
+/// Setting a message out of order fails with
+/// [`Error::InvalidTranscriptState`].
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "S::MessageA: serde::Serialize, \
+                     S::Challenge: serde::Serialize, \
+                     S::MessageZ: serde::Serialize",
+        deserialize = "S::MessageA: serde::Deserialize<'de>, \
+                       S::Challenge: serde::Deserialize<'de>, \
+                       S::MessageZ: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Transcript<S: SigmaProtocol> {
+    a: Option<S::MessageA>,
+    c: Option<S::Challenge>,
+    z: Option<S::MessageZ>,
+}
+
+impl<S: SigmaProtocol> Transcript<S> {
+    pub fn new() -> Self {
+        Self {
+            a: None,
+            c: None,
+            z: None,
+        }
+    }
+
+    pub fn is_new(&self) -> bool {
+        self.a
+            .is_none()
+    }
+
+    /// First message has been set
+    pub fn is_commited(&self) -> bool {
+        self.a
+            .is_some()
+            && self
+                .c
+                .is_none()
+    }
+
+    /// Challenge has been set
+    pub fn is_challenged(&self) -> bool {
+        self.c
+            .is_some()
+            && self
+                .z
+                .is_none()
+    }
+
+    /// Third message has been set
+    pub fn is_proven(&self) -> bool {
+        self.z
+            .is_some()
+    }
+
+    /// Set the first message of a new transcript
+    pub fn commit(
+        &mut self,
+        a: S::MessageA,
+    ) -> Result<(), Error> {
+        if !self.is_new() {
+            return Err(Error::InvalidTranscriptState);
+        }
+        self.a = Some(a);
+        Ok(())
+    }
+
+    /// Set the challenge of a commited transcript
+    pub fn challenge(
+        &mut self,
+        c: S::Challenge,
+    ) -> Result<(), Error> {
+        if !self.is_commited() {
+            return Err(Error::InvalidTranscriptState);
+        }
+        self.c = Some(c);
+        Ok(())
+    }
+
+    /// Set the third message of a challenged transcript
+    pub fn prove(
+        &mut self,
+        z: S::MessageZ,
+    ) -> Result<(), Error> {
+        if !self.is_challenged() {
+            return Err(Error::InvalidTranscriptState);
+        }
+        self.z = Some(z);
+        Ok(())
+    }
+
+    pub fn a(&self) -> Result<&S::MessageA, Error> {
+        self.a
+            .as_ref()
+            .ok_or(Error::InvalidTranscriptState)
+    }
+
+    pub fn c(&self) -> Result<&S::Challenge, Error> {
+        self.c
+            .as_ref()
+            .ok_or(Error::InvalidTranscriptState)
+    }
+
+    pub fn z(&self) -> Result<&S::MessageZ, Error> {
+        self.z
+            .as_ref()
+            .ok_or(Error::InvalidTranscriptState)
+    }
+}
+
+/// The state of the transcript, followed once the first
+/// message is set by the first four bytes of its encoding in
+/// hex, e.g. `committed (a = 9c66a339…)`
+impl<S: SigmaProtocol> fmt::Display for Transcript<S>
+where
+    S::MessageA: Message,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.is_new() {
+            "new"
+        } else if self.is_commited() {
+            "committed"
+        } else if self.is_challenged() {
+            "challenged"
+        } else {
+            "proven"
+        };
+        write!(f, "{}", state)?;
+        if let Some(a) = &self.a {
+            let mut bytes = Vec::new();
+            a.write(&mut bytes);
+            write!(f, " (a = ")?;
+            for b in bytes
+                .iter()
+                .take(4)
+            {
+                write!(f, "{:02x}", b)?;
+            }
+            write!(f, "…)")?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: SigmaProtocol> Default for Transcript<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: SigmaProtocol> Clone for Transcript<S> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self
+                .a
+                .clone(),
+            c: self
+                .c
+                .clone(),
+            z: self
+                .z
+                .clone(),
+        }
+    }
+}
+
+impl<S: SigmaProtocol>
+    From<(S::MessageA, S::Challenge, S::MessageZ)>
+    for Transcript<S>
+{
+    fn from(
+        (a, c, z): (S::MessageA, S::Challenge, S::MessageZ),
+    ) -> Self {
+        Self {
+            a: Some(a),
+            c: Some(c),
+            z: Some(z),
+        }
+    }
+}
+
+impl<S: SigmaProtocol> TryFrom<Transcript<S>>
+    for (S::MessageA, S::Challenge, S::MessageZ)
+{
+    type Error = Error;
+
+    /// Take the messages out of a proven transcript
+    fn try_from(
+        transcript: Transcript<S>,
+    ) -> Result<Self, Error> {
+        match transcript {
+            Transcript {
+                a: Some(a),
+                c: Some(c),
+                z: Some(z),
+            } => Ok((a, c, z)),
+            _ => Err(Error::InvalidTranscriptState),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "schnorr"))]
+mod tests {
+    use curve25519_dalek::Scalar;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn transcript_state_transitions() {
+        let witness = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let protocol = Schnorr::init(witness);
+        let prover_rng =
+            &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let verifier_rng =
+            &mut ChaCha20Rng::from_seed([3u8; 32]);
+
+        let mut transcript = SchnorrTranscript::new();
+        assert!(transcript.is_new());
+        assert!(transcript
+            .a()
+            .is_err());
+
+        let (state, a) =
+            Schnorr::first(&protocol, &witness, prover_rng);
+        let c = Schnorr::second(verifier_rng);
+        // Challenge before the first message
+        assert!(transcript
+            .challenge(c)
+            .is_err());
+
+        transcript
+            .commit(a)
+            .unwrap();
+        assert!(transcript.is_commited());
+        assert!(transcript
+            .commit(a)
+            .is_err());
+
+        transcript
+            .challenge(c)
+            .unwrap();
+        assert!(transcript.is_challenged());
+        assert!(!transcript.is_commited());
+
+        let z = Schnorr::third(
+            &protocol, state, &witness, &c, prover_rng,
+        );
+        transcript
+            .prove(z)
+            .unwrap();
+        assert!(transcript.is_proven());
+        assert!(!transcript.is_challenged());
+
+        assert!(Schnorr::verify(
+            &protocol,
+            transcript
+                .a()
+                .unwrap(),
+            transcript
+                .c()
+                .unwrap(),
+            transcript
+                .z()
+                .unwrap(),
+        ));
+        let (a2, c2, z2) =
+            <(_, _, _)>::try_from(transcript).unwrap();
+        assert_eq!((a2, c2, z2), (a, c, z));
+
+        let partial = SchnorrTranscript::new();
+        assert!(<(_, _, _)>::try_from(partial).is_err());
+    }
+
+    #[test]
+    fn transcript_display_follows_state() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let witness = Scalar::random(rng);
+        let protocol = Schnorr::init(witness);
+        let (state, a) =
+            Schnorr::first(&protocol, &witness, rng);
+        let c = Schnorr::second(rng);
+        let z = Schnorr::third(
+            &protocol, state, &witness, &c, rng,
+        );
+        let prefix: String = a.as_bytes()[..4]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let mut transcript = SchnorrTranscript::new();
+        assert_eq!(transcript.to_string(), "new");
+        transcript
+            .commit(a)
+            .unwrap();
+        assert_eq!(
+            transcript.to_string(),
+            format!("committed (a = {}…)", prefix)
+        );
+        transcript
+            .challenge(c)
+            .unwrap();
+        assert_eq!(
+            transcript.to_string(),
+            format!("challenged (a = {}…)", prefix)
+        );
+        transcript
+            .prove(z)
+            .unwrap();
+        assert_eq!(
+            transcript.to_string(),
+            format!("proven (a = {}…)", prefix)
+        );
+    }
+
+    #[test]
+    fn transcript_compressed_round_trip() {
+        use curve25519_dalek::ristretto::CompressedRistretto;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let witness = Scalar::random(rng);
+        let protocol = Schnorr::init(witness);
+        let (state, a) =
+            Schnorr::first(&protocol, &witness, rng);
+        let c = Schnorr::second(rng);
+        let z = Schnorr::third(
+            &protocol, state, &witness, &c, rng,
+        );
+
+        // The first message is stored as its 32-byte encoding
+        // and rebuilt from bytes
+        let transcript = SchnorrTranscript::from((a, c, z));
+        let bytes = transcript
+            .a()
+            .unwrap()
+            .to_bytes();
+        let rebuilt = SchnorrTranscript::from((
+            CompressedRistretto(bytes),
+            c,
+            z,
+        ));
+        let (a2, c2, z2) =
+            <(_, _, _)>::try_from(rebuilt).unwrap();
+        assert!(Schnorr::verify(&protocol, &a2, &c2, &z2));
+
+        // Bytes that do not decompress are rejected, not a panic
+        let garbage = CompressedRistretto([0xff; 32]);
+        assert!(garbage
+            .decompress()
+            .is_none());
+        assert!(!Schnorr::verify(
+            &protocol, &garbage, &c, &z
+        ));
+    }
+}
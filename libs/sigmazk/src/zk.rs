@@ -1,11 +1,26 @@
 //! Traits for Zero Knowledge
 
+use rand_core::CryptoRngCore;
+
 use crate::SigmaProtocol;
 
 pub trait HVzk: SigmaProtocol {
     fn simulate(
         statement: &Self::Statement,
     ) -> (Self::MessageA, Self::Challenge, Self::MessageZ);
+
+    /// Check that a simulated transcript for `statement` is
+    /// accepted by [`SigmaProtocol::verify`], i.e. that
+    /// `simulate` and `verify` are consistent
+    fn simulate_self_test(
+        statement: &Self::Statement,
+    ) -> bool
+    where
+        Self: Sized,
+    {
+        let (a, c, z) = Self::simulate(statement);
+        Self::verify(statement, &a, &c, &z)
+    }
 }
 
 /// Extended Honest-Verifier Zero Knowledge
@@ -15,4 +30,21 @@ pub trait EHVzk: SigmaProtocol {
         challenge: &Self::Challenge,
         z: &Self::MessageZ,
     ) -> Self::MessageA;
+
+    /// Check that the first message simulated for `z` and a
+    /// random challenge is accepted by
+    /// [`SigmaProtocol::verify`], i.e. that `simulate` and
+    /// `verify` are consistent
+    fn simulate_self_test<R: CryptoRngCore + Clone>(
+        statement: &Self::Statement,
+        z: &Self::MessageZ,
+        rng: &mut R,
+    ) -> bool
+    where
+        Self: Sized,
+    {
+        let c = Self::second(rng);
+        let a = Self::simulate(statement, &c, z);
+        Self::verify(statement, &a, &c, z)
+    }
 }
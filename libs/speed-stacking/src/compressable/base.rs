@@ -1,7 +1,8 @@
 use group::ff::{Field, PrimeField};
 use group::prime::PrimeGroup;
-use group::ScalarMul;
+use group::{Group, ScalarMul};
 use rand_core::CryptoRngCore;
+use sigmazk::error::Error;
 use sigmazk::{Challenge, SigmaProtocol};
 
 use crate::homomorphism::Hom;
@@ -26,14 +27,58 @@ where
     }
 }
 
-pub fn multi_exponentiation<G: PrimeGroup>(
-    bases: &[G],
-    exponents: &[G::Scalar],
-) -> G {
-    bases
-        .iter()
-        .zip(exponents.iter())
-        .fold(G::identity(), |acc, (g, r)| acc + *g * r)
+/// Multi-exponentiation $\sum_i x_i g_i$ of `generators`
+/// $g_i$ by `scalars` $x_i$, e.g. borrowed slices or
+/// iterators over them. The scalars need not be those of
+/// `G`, see [`CommonField`](wrapped_ristretto::CommonField).
+///
+/// There must be one scalar per generator, i.e.
+/// `generators.len() == scalars.len()`; the identity is
+/// returned if both are empty.
+///
+/// # Panics
+/// If the lengths differ, see [`try_multi_exponentiation`].
+pub fn multi_exponentiation<'a, G, F, I, J>(
+    generators: I,
+    scalars: J,
+) -> G
+where
+    G: Group + ScalarMul<F> + 'a,
+    F: Field + 'a,
+    I: IntoIterator<Item = &'a G>,
+    I::IntoIter: ExactSizeIterator,
+    J: IntoIterator<Item = &'a F>,
+    J::IntoIter: ExactSizeIterator,
+{
+    try_multi_exponentiation(generators, scalars)
+        .expect("one scalar per generator")
+}
+
+/// [`multi_exponentiation`], or [`Error::InvalidLength`]
+/// if there is not one scalar per generator
+pub fn try_multi_exponentiation<'a, G, F, I, J>(
+    generators: I,
+    scalars: J,
+) -> Result<G, Error>
+where
+    G: Group + ScalarMul<F> + 'a,
+    F: Field + 'a,
+    I: IntoIterator<Item = &'a G>,
+    I::IntoIter: ExactSizeIterator,
+    J: IntoIterator<Item = &'a F>,
+    J::IntoIter: ExactSizeIterator,
+{
+    let (generators, scalars) =
+        (generators.into_iter(), scalars.into_iter());
+    if generators.len() != scalars.len() {
+        return Err(Error::InvalidLength {
+            expected: generators.len(),
+            got: scalars.len(),
+        });
+    }
+    Ok(generators
+        .zip(scalars)
+        .fold(G::identity(), |acc, (g, x)| acc + *g * *x))
 }
 
 pub struct BaseStatement<G1: PrimeGroup, G2: PrimeGroup, L: Hom<G1::Scalar, G2>>
@@ -134,3 +179,51 @@ where
             && gz == a.0 + statement.g1_public_key * c
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use wrapped_ristretto::ristretto::WrappedRistretto;
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    use super::*;
+
+    #[test]
+    fn multi_exponentiation_checks_lengths() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let generators: Vec<WrappedRistretto> = (0..3)
+            .map(|_| WrappedRistretto::random(&mut *rng))
+            .collect();
+        let scalars: Vec<WrappedScalar> = (0..3)
+            .map(|_| WrappedScalar::random(&mut *rng))
+            .collect();
+
+        let expected = generators[0] * scalars[0]
+            + generators[1] * scalars[1]
+            + generators[2] * scalars[2];
+        assert_eq!(
+            multi_exponentiation(&generators, &scalars),
+            expected
+        );
+        assert_eq!(
+            multi_exponentiation::<WrappedRistretto, _, _, _>(
+                &[],
+                &[] as &[WrappedScalar]
+            ),
+            WrappedRistretto::identity()
+        );
+        assert!(matches!(
+            try_multi_exponentiation::<
+                WrappedRistretto,
+                _,
+                _,
+                _,
+            >(&generators, &scalars[..2]),
+            Err(Error::InvalidLength {
+                expected: 3,
+                got: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use group::ff::{Field, PrimeField};
+use group::prime::PrimeGroup;
+use group::Group;
+use sha2::{Digest, Sha512};
+use sigmazk::{Challenge, SigmaProtocol};
+
+use super::cross_terms;
+
+type ScalarOf<C> =
+    <<C as Compressable>::Group as Group>::Scalar;
+
+/// Compressed third message. Instead of the $n$ elements of
+/// the response $z$ the prover sends the cross terms
+/// $(A_i, B_i)$ of the $\log_2 n$ folding rounds and the
+/// single element $z$ is folded down to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedZ<G: PrimeGroup> {
+    pub rounds: Vec<(G, G)>,
+    pub z: G::Scalar,
+}
+
+/// Sigma protocols whose third message $z \in Z_q^n$ is
+/// accepted iff it satisfies a linear relation
+/// $\langle g, z \rangle = P$ for generators $g \in G^n$ and
+/// a target $P \in G$ derived from the statement and the
+/// first two messages.
+///
+/// Rather than sending $z$, the prover proves knowledge of
+/// it by repeatedly halving the relation: every round sends
+/// the cross terms of a [`split`](Compressable::split) and
+/// [`fold`](Compressable::fold)s generators, target and
+/// response with a challenge, so the response shrinks to
+/// $O(\log n)$ group elements. Round challenges hash the
+/// current target, which binds the sigma protocol
+/// conversation, and are chained across rounds.
+pub trait Compressable: SigmaProtocol {
+    type Group: PrimeGroup;
+
+    /// The generators $g$ and target $P$ an honest response
+    /// satisfies $\langle g, z \rangle = P$ for.
+    fn relation(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+    ) -> (Vec<Self::Group>, Self::Group);
+
+    /// First-round split of a folding round. Returns the cross
+    /// terms $A = \langle g_R, z_L \rangle$ and
+    /// $B = \langle g_L, z_R \rangle$, as
+    /// [`CompMechanism`](super::CompMechanism) sends them.
+    fn split(
+        generators: &[Self::Group],
+        z: &[ScalarOf<Self>],
+    ) -> (Self::Group, Self::Group) {
+        cross_terms(generators, z)
+    }
+
+    /// Fold step of the verifier's side of the relation:
+    /// $g' = g_L + c g_R$ and $P' = B + c P + c^2 A$.
+    fn fold(
+        generators: &[Self::Group],
+        target: Self::Group,
+        (big_a, big_b): (Self::Group, Self::Group),
+        challenge: ScalarOf<Self>,
+    ) -> (Vec<Self::Group>, Self::Group) {
+        let midpoint = generators.len() / 2;
+        let (g_left, g_right) =
+            generators.split_at(midpoint);
+        let new_generators = g_left
+            .iter()
+            .zip(g_right.iter())
+            .map(|(l, r)| *l + *r * challenge)
+            .collect();
+        let new_target = big_b
+            + target * challenge
+            + big_a * challenge.square();
+        (new_generators, new_target)
+    }
+
+    /// Fold step of the prover's response:
+    /// $z' = c z_L + z_R$, so that
+    /// $\langle g', z' \rangle = P'$.
+    fn fold_response(
+        z: &[ScalarOf<Self>],
+        challenge: ScalarOf<Self>,
+    ) -> Vec<ScalarOf<Self>> {
+        let (z_left, z_right) = z.split_at(z.len() / 2);
+        z_left
+            .iter()
+            .zip(z_right.iter())
+            .map(|(l, r)| challenge * l + r)
+            .collect()
+    }
+
+    /// Compresses the response `z` to the conversation
+    /// `(a, c)`.
+    ///
+    /// # Panics
+    /// If the length of `z` is not a power of two or does not
+    /// match the number of generators.
+    fn compress(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        z: &[ScalarOf<Self>],
+    ) -> CompressedZ<Self::Group>
+    where
+        ScalarOf<Self>: Challenge,
+    {
+        let (mut generators, mut target) =
+            Self::relation(statement, a, c);
+        assert!(
+            z.len()
+                .is_power_of_two(),
+            "response length must be 2^k"
+        );
+        assert_eq!(
+            z.len(),
+            generators.len(),
+            "one generator per response"
+        );
+
+        let mut z = z.to_vec();
+        let mut challenge =
+            ScalarOf::<Self>::new(&[0u8; 64]);
+        let mut rounds = Vec::with_capacity(
+            z.len()
+                .trailing_zeros() as usize,
+        );
+        while z.len() > 1 {
+            let cross = Self::split(&generators, &z);
+            challenge =
+                round_challenge(challenge, &target, cross);
+            (generators, target) = Self::fold(
+                &generators,
+                target,
+                cross,
+                challenge,
+            );
+            z = Self::fold_response(&z, challenge);
+            rounds.push(cross);
+        }
+
+        CompressedZ { rounds, z: z[0] }
+    }
+
+    /// Verifies a compressed response to the conversation
+    /// `(a, c)`.
+    fn verify_compressed(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        compressed: &CompressedZ<Self::Group>,
+    ) -> bool
+    where
+        ScalarOf<Self>: Challenge,
+    {
+        let (mut generators, mut target) =
+            Self::relation(statement, a, c);
+        if !generators
+            .len()
+            .is_power_of_two()
+            || generators
+                .len()
+                .trailing_zeros() as usize
+                != compressed
+                    .rounds
+                    .len()
+        {
+            return false;
+        }
+
+        let mut challenge =
+            ScalarOf::<Self>::new(&[0u8; 64]);
+        for cross in compressed
+            .rounds
+            .iter()
+        {
+            challenge =
+                round_challenge(challenge, &target, *cross);
+            (generators, target) = Self::fold(
+                &generators,
+                target,
+                *cross,
+                challenge,
+            );
+        }
+
+        generators[0] * compressed.z == target
+    }
+}
+
+/// Challenge of a folding round, bound to the previous round's
+/// challenge, the current target and the round's cross terms
+fn round_challenge<G>(
+    previous: G::Scalar,
+    target: &G,
+    (big_a, big_b): (G, G),
+) -> G::Scalar
+where
+    G: PrimeGroup,
+    G::Scalar: Challenge,
+{
+    let mut hash = Sha512::new();
+    hash.update(previous.to_repr());
+    hash.update(target.to_bytes());
+    hash.update(big_a.to_bytes());
+    hash.update(big_b.to_bytes());
+    G::Scalar::new(
+        &hash
+            .finalize()
+            .into(),
+    )
+}
@@ -2,13 +2,17 @@ use group::ff::{Field, PrimeField};
 use group::prime::PrimeGroup;
 use group::ScalarMul;
 use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
 use sigmazk::{Challenge, SigmaProtocol};
+use std::iter;
 use std::rc::Rc;
 use wrapped_ristretto::CommonField;
 
 use crate::homomorphism::Hom;
 
-use super::{multi_exponentiation, Base};
+use super::{
+    multi_exponentiation, Base, Compressable, CompressedZ,
+};
 
 /// Compressing Mechanism for our base compressable sigma
 /// protocol.
@@ -43,7 +47,84 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+impl<G1, G2, L> CompMechanism<G1, G1::Scalar, G2, L>
+where
+    G1: PrimeGroup,
+    G1::Scalar: Challenge,
+    G2: PrimeGroup,
+    L: Hom<G1::Scalar, G2>,
+{
+    /// Compresses the response `z` of a conversation
+    /// `(a, c)` of the compressable protocol `P` to
+    /// $\log_2 n$ folding rounds, see
+    /// [`Compressable::compress`]. Returns `None` unless
+    /// `z` and the generators of the relation have the `n`
+    /// elements of this mechanism.
+    pub fn compress<P>(
+        &self,
+        statement: &P::Statement,
+        a: &P::MessageA,
+        c: &P::Challenge,
+        z: &[G1::Scalar],
+    ) -> Option<CompressedZ<G1>>
+    where
+        P: Compressable<Group = G1>,
+    {
+        let (generators, _) = P::relation(statement, a, c);
+        (self
+            .n
+            .is_power_of_two()
+            && z.len() == self.n
+            && generators.len() == self.n)
+            .then(|| P::compress(statement, a, c, z))
+    }
+
+    /// Verifies a response compressed by
+    /// [`CompMechanism::compress`], which must have one
+    /// folding round per halving of `n`
+    pub fn verify_compressed<P>(
+        &self,
+        statement: &P::Statement,
+        a: &P::MessageA,
+        c: &P::Challenge,
+        compressed: &CompressedZ<G1>,
+    ) -> bool
+    where
+        P: Compressable<Group = G1>,
+    {
+        self.n
+            .is_power_of_two()
+            && compressed
+                .rounds
+                .len()
+                == self
+                    .n
+                    .trailing_zeros()
+                    as usize
+            && P::verify_compressed(
+                statement, a, c, compressed,
+            )
+    }
+}
+
+/// Cross terms $A = \langle g_R, x_L \rangle$ and
+/// $B = \langle g_L, x_R \rangle$ of splitting
+/// `generators` and `witness` in half, the first message
+/// of a folding round
+pub fn cross_terms<G: PrimeGroup>(
+    generators: &[G],
+    witness: &[G::Scalar],
+) -> (G, G) {
+    let midpoint = witness.len() / 2;
+    let (g_left, g_right) = generators.split_at(midpoint);
+    let (x_left, x_right) = witness.split_at(midpoint);
+    (
+        multi_exponentiation(g_right, x_left),
+        multi_exponentiation(g_left, x_right),
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ComposedHom<G1: PrimeGroup, G2: PrimeGroup> {
     pub challenge_i: G1::Scalar,
     _marker: std::marker::PhantomData<G2>,
@@ -64,20 +145,24 @@ where
     G2: PrimeGroup + CommonField<G1>,
 {
     fn fleft(&self, x: &[G1::Scalar]) -> G2 {
-        x.iter()
-            .fold(G2::identity(), |acc, x| {
-                G2::generator() * self.challenge_i * x + acc
-            })
+        self.fright(x) * self.challenge_i
     }
 
     fn fright(&self, x: &[G1::Scalar]) -> G2 {
-        x.iter()
-            .fold(G2::identity(), |acc, x| G2::generator() * x + acc)
+        let generator = G2::generator();
+        multi_exponentiation(
+            iter::repeat_n(&generator, x.len()),
+            x,
+        )
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct History<G1: PrimeGroup, G2: PrimeGroup, L: Hom<G1::Scalar, G2>> {
+#[derive(Clone, Debug, PartialEq)]
+pub struct History<
+    G1: PrimeGroup,
+    G2: PrimeGroup,
+    L: Hom<G1::Scalar, G2>,
+> {
     pub base_f: Rc<L>,
     pub base_g1: Rc<G1>,
     pub base_g2: Rc<G2>,
@@ -86,7 +171,7 @@ pub struct History<G1: PrimeGroup, G2: PrimeGroup, L: Hom<G1::Scalar, G2>> {
     pub past_c: Vec<G1::Scalar>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ComposedStatement<
     G1: PrimeGroup,
     G2: PrimeGroup,
@@ -100,7 +185,72 @@ pub struct ComposedStatement<
     pub g2_public_key: G2, // y_i
 }
 
-#[derive(Clone, Debug)]
+impl<G1, G2, L> ComposedStatement<G1, G2, L>
+where
+    G1: PrimeGroup,
+    G2: PrimeGroup,
+    L: Hom<G1::Scalar, G2>,
+{
+    /// SHA-256 of the statement and its history, to compare
+    /// a statement rebuilt by the verifier with the prover's
+    /// without comparing every generator. The base
+    /// homomorphism is not hashed as [`Hom`] has no encoding,
+    /// it is the same for every round of a proof.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hash = Sha256::new();
+        hash.update(b"speed-stacking composed statement");
+        hash.update((self.n as u64).to_le_bytes());
+        hash.update(
+            self.history
+                .base_g1
+                .to_bytes(),
+        );
+        hash.update(
+            self.history
+                .base_g2
+                .to_bytes(),
+        );
+        for (a, b) in self
+            .history
+            .past_a
+            .iter()
+            .zip(
+                &self
+                    .history
+                    .past_b,
+            )
+        {
+            hash.update(a.to_bytes());
+            hash.update(b.to_bytes());
+        }
+        for c in &self
+            .history
+            .past_c
+        {
+            hash.update(c.to_repr());
+        }
+        for g in &self.generators {
+            hash.update(g.to_bytes());
+        }
+        hash.update(
+            self.hom_f
+                .challenge_i
+                .to_repr(),
+        );
+        hash.update(
+            self.g1_public_key
+                .to_bytes(),
+        );
+        hash.update(
+            self.g2_public_key
+                .to_bytes(),
+        );
+        hash.finalize()
+            .into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ComposedA<G1: PrimeGroup, G2: PrimeGroup> {
     pub big_a: G1,
     pub big_b: G1,
@@ -108,19 +258,24 @@ pub struct ComposedA<G1: PrimeGroup, G2: PrimeGroup> {
     pub b: G2,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ComposedState<G2: PrimeGroup> {
     pub a: G2,
     pub b: G2,
 }
 
-#[derive(Clone, Debug)]
-pub struct ComposedZ<G1: PrimeGroup, G2: PrimeGroup, L: Hom<G1::Scalar, G2>> {
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComposedZ<
+    G1: PrimeGroup,
+    G2: PrimeGroup,
+    L: Hom<G1::Scalar, G2>,
+> {
     pub new_statement: Option<ComposedStatement<G1, G2, L>>,
     pub new_witnesses: Option<Vec<G1::Scalar>>,
 }
 
-impl<G1, G2, L> SigmaProtocol for CompMechanism<G1, G1::Scalar, G2, L>
+impl<G1, G2, L> SigmaProtocol
+    for CompMechanism<G1, G1::Scalar, G2, L>
 where
     G1: PrimeGroup,
     G1::Scalar: Challenge,
@@ -145,17 +300,12 @@ where
     {
         let midpoint = statement.n / 2;
 
-        let (g_left, g_right) = statement
-            .generators
-            .as_slice()
-            .split_at(midpoint);
-
         let (xleft, xright) = witness
             .as_slice()
             .split_at(midpoint);
 
-        let big_a = multi_exponentiation(g_right, xleft);
-        let big_b = multi_exponentiation(g_left, xright);
+        let (big_a, big_b) =
+            cross_terms(&statement.generators, witness);
 
         // TODO: Fully implement unique_or_rand
         let a = Self::unique_or_rand(
@@ -169,7 +319,10 @@ where
                 .fleft(xright),
         );
 
-        (ComposedState { a, b }, ComposedA { big_a, big_b, a, b })
+        (
+            ComposedState { a, b },
+            ComposedA { big_a, big_b, a, b },
+        )
     }
 
     fn second<R: rand_core::CryptoRngCore>(
@@ -226,7 +379,8 @@ where
                 .collect();
 
             // Create homomorphism for next round
-            let hom_f: ComposedHom<G1, G2> = ComposedHom::new(challenge);
+            let hom_f: ComposedHom<G1, G2> =
+                ComposedHom::new(challenge);
 
             // Create new g2_public_key (y_{i + 1} in the paper)
             // y_{i + 1} = ai + c_i*y_i + b_i * c_i^2
@@ -249,15 +403,18 @@ where
                 .push(challenge);
 
             // Create new statement for next round
-            let new_statement: ComposedStatement<G1, G2, L> =
-                ComposedStatement {
-                    history,
-                    n: midpoint,
-                    generators: new_generators,
-                    hom_f,
-                    g1_public_key: statement.g1_public_key,
-                    g2_public_key,
-                };
+            let new_statement: ComposedStatement<
+                G1,
+                G2,
+                L,
+            > = ComposedStatement {
+                history,
+                n: midpoint,
+                generators: new_generators,
+                hom_f,
+                g1_public_key: statement.g1_public_key,
+                g2_public_key,
+            };
 
             ComposedZ {
                 new_statement: Some(new_statement),
@@ -278,3 +435,221 @@ where
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use group::Group;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use wrapped_ristretto::ristretto::WrappedRistretto;
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    use super::*;
+
+    type Ristretto = WrappedRistretto;
+    type RistrettoHom = ComposedHom<Ristretto, Ristretto>;
+    type Mechanism = CompMechanism<
+        Ristretto,
+        WrappedScalar,
+        Ristretto,
+        RistrettoHom,
+    >;
+
+    type Statement = ComposedStatement<
+        Ristretto,
+        Ristretto,
+        RistrettoHom,
+    >;
+
+    /// A statement of length `n` with random generators and
+    /// witness
+    fn statement_init(
+        n: usize,
+        rng: &mut ChaCha20Rng,
+    ) -> (Statement, Vec<WrappedScalar>) {
+        let generators: Vec<Ristretto> = (0..n)
+            .map(|_| Ristretto::random(&mut *rng))
+            .collect();
+        let witness: Vec<WrappedScalar> = (0..n)
+            .map(|_| WrappedScalar::random(&mut *rng))
+            .collect();
+        let hom_f =
+            RistrettoHom::new(WrappedScalar::from(1u64));
+        let statement = ComposedStatement {
+            history: History {
+                base_f: Rc::new(hom_f),
+                base_g1: Rc::new(Ristretto::generator()),
+                base_g2: Rc::new(Ristretto::generator()),
+                past_a: vec![],
+                past_b: vec![],
+                past_c: vec![],
+            },
+            n,
+            g1_public_key: multi_exponentiation(
+                &generators,
+                &witness,
+            ),
+            g2_public_key: hom_f.f(&witness),
+            generators,
+            hom_f,
+        };
+        (statement, witness)
+    }
+
+    #[test]
+    fn mechanism_over_wrapped_ristretto() {
+        const N: usize = 8;
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let mechanism = Mechanism::new(N);
+        assert_eq!(
+            mechanism
+                .base
+                .n,
+            N
+        );
+
+        let (statement, witness) = statement_init(N, rng);
+
+        let (state, a) =
+            Mechanism::first(&statement, &witness, rng);
+        let (g_left, g_right) = statement
+            .generators
+            .split_at(N / 2);
+        let (x_left, x_right) = witness.split_at(N / 2);
+        assert_eq!(
+            a.big_a,
+            multi_exponentiation(g_right, x_left)
+        );
+        assert_eq!(
+            a.big_b,
+            multi_exponentiation(g_left, x_right)
+        );
+
+        let c = Mechanism::second(rng);
+        let z = Mechanism::third(
+            &statement,
+            state.clone(),
+            &witness,
+            &c,
+            rng,
+        );
+        let next = z
+            .new_statement
+            .unwrap();
+        assert_eq!(next.n, N / 2);
+        assert_eq!(
+            next.generators
+                .len(),
+            N / 2
+        );
+        assert_eq!(
+            z.new_witnesses
+                .unwrap()
+                .len(),
+            N / 2
+        );
+        assert_eq!(
+            next.g2_public_key,
+            state.a
+                + statement.g2_public_key * c
+                + state.b * c.square()
+        );
+        assert_eq!(
+            next.history
+                .past_c,
+            vec![c]
+        );
+    }
+
+    #[test]
+    fn folded_statement_is_reconstructed() {
+        const N: usize = 8;
+        let rng = &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let (statement, witness) = statement_init(N, rng);
+
+        let (state, a) =
+            Mechanism::first(&statement, &witness, rng);
+        let c = Mechanism::second(rng);
+        let z = Mechanism::third(
+            &statement, state, &witness, &c, rng,
+        );
+        let folded = z
+            .new_statement
+            .unwrap();
+
+        // Rebuild the next statement from the public messages only
+        let (g_left, g_right) = statement
+            .generators
+            .split_at(N / 2);
+        let mut history = statement
+            .history
+            .clone();
+        history
+            .past_a
+            .push(a.a);
+        history
+            .past_b
+            .push(a.b);
+        history
+            .past_c
+            .push(c);
+        let rebuilt = ComposedStatement {
+            history,
+            n: N / 2,
+            generators: g_left
+                .iter()
+                .zip(g_right)
+                .map(|(l, r)| *l * c + r)
+                .collect(),
+            hom_f: RistrettoHom::new(c),
+            g1_public_key: statement.g1_public_key,
+            g2_public_key: a.a
+                + statement.g2_public_key * c
+                + a.b * c.square(),
+        };
+        assert_eq!(folded, rebuilt);
+        assert_eq!(
+            folded.fingerprint(),
+            rebuilt.fingerprint()
+        );
+
+        let mut tampered = rebuilt.clone();
+        tampered
+            .history
+            .past_c[0] += WrappedScalar::ONE;
+        assert_ne!(folded, tampered);
+        assert_ne!(
+            folded.fingerprint(),
+            tampered.fingerprint()
+        );
+    }
+
+    #[test]
+    fn hom_matches_fold() {
+        let rng = &mut ChaCha20Rng::from_seed([2u8; 32]);
+        let hom = RistrettoHom::new(WrappedScalar::random(
+            &mut *rng,
+        ));
+        let x: Vec<WrappedScalar> = (0..5)
+            .map(|_| WrappedScalar::random(&mut *rng))
+            .collect();
+
+        // The homomorphism as it was computed before it used
+        // multi_exponentiation
+        let fleft = x
+            .iter()
+            .fold(Ristretto::identity(), |acc, x| {
+                Ristretto::generator() * hom.challenge_i * x
+                    + acc
+            });
+        let fright = x
+            .iter()
+            .fold(Ristretto::identity(), |acc, x| {
+                Ristretto::generator() * x + acc
+            });
+        assert_eq!(hom.fleft(&x), fleft);
+        assert_eq!(hom.fright(&x), fright);
+        assert_eq!(hom.fleft(&[]), Ristretto::identity());
+        assert_eq!(hom.fright(&[]), Ristretto::identity());
+    }
+}
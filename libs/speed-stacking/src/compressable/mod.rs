@@ -1,5 +1,9 @@
 pub mod base;
+pub mod compress;
 pub mod mechanism;
+pub mod schnorr;
 pub use base::*;
+pub use compress::*;
 pub use mechanism::*;
+pub use schnorr::*;
 mod base25519;
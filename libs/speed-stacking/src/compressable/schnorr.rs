@@ -0,0 +1,346 @@
+use group::ff::Field;
+use group::prime::PrimeGroup;
+use rand_core::CryptoRngCore;
+use sigmazk::{Challenge, SigmaProtocol};
+use subtle::ConstantTimeEq;
+
+use super::{multi_exponentiation, Compressable};
+
+/// Schnorr's protocol for a representation
+/// $P = \langle g, x \rangle$ of a public key with respect to
+/// $n$ generators. Its response is the vector $z = r + c x$,
+/// which is what makes it [`Compressable`].
+///
+/// `G` must implement [`ConstantTimeEq`]: verification
+/// compares the recomputed commitment with `ct_eq` rather
+/// than `PartialEq`, which the `group` traits do not require
+/// to be constant-time.
+pub struct SchnorrRepresentation<G: PrimeGroup> {
+    _marker: std::marker::PhantomData<G>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrRepresentationStatement<G: PrimeGroup> {
+    pub generators: Vec<G>,
+    pub public_key: G,
+}
+
+impl<G: PrimeGroup> SchnorrRepresentationStatement<G> {
+    pub fn init(
+        generators: Vec<G>,
+        witness: &[G::Scalar],
+    ) -> Self {
+        let public_key =
+            multi_exponentiation(&generators, witness);
+        Self {
+            generators,
+            public_key,
+        }
+    }
+}
+
+impl<G> SigmaProtocol for SchnorrRepresentation<G>
+where
+    G: PrimeGroup + ConstantTimeEq,
+    G::Scalar: Challenge,
+{
+    type Statement = SchnorrRepresentationStatement<G>;
+    type Witness = Vec<G::Scalar>;
+
+    type MessageA = G;
+
+    type Challenge = G::Scalar;
+
+    type MessageZ = Vec<G::Scalar>;
+
+    type State = Vec<G::Scalar>;
+
+    fn first<R: CryptoRngCore>(
+        statement: &Self::Statement,
+        _witness: &Self::Witness,
+        prover_rng: &mut R,
+    ) -> (Self::State, Self::MessageA)
+    where
+        Self: Sized,
+    {
+        let r: Vec<G::Scalar> = (0..statement
+            .generators
+            .len())
+            .map(|_| {
+                G::Scalar::random(prover_rng.as_rngcore())
+            })
+            .collect();
+        let a =
+            multi_exponentiation(&statement.generators, &r);
+        (r, a)
+    }
+
+    fn second<R: CryptoRngCore>(
+        verifier_rng: &mut R,
+    ) -> Self::Challenge
+    where
+        Self: Sized,
+    {
+        G::Scalar::random(verifier_rng.as_rngcore())
+    }
+
+    fn third<R: CryptoRngCore>(
+        _statement: &Self::Statement,
+        state: Self::State,
+        witness: &Self::Witness,
+        challenge: &Self::Challenge,
+        _prover_rng: &mut R,
+    ) -> Self::MessageZ
+    where
+        Self: Sized,
+    {
+        witness
+            .iter()
+            .zip(state.iter())
+            .map(|(w, r)| *w * challenge + r)
+            .collect()
+    }
+
+    fn verify(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> bool
+    where
+        Self: Sized,
+    {
+        z.len()
+            == statement
+                .generators
+                .len()
+            && multi_exponentiation(
+                &statement.generators,
+                z,
+            )
+            .ct_eq(&(*a + statement.public_key * c))
+            .into()
+    }
+}
+
+impl<G> Compressable for SchnorrRepresentation<G>
+where
+    G: PrimeGroup + ConstantTimeEq,
+    G::Scalar: Challenge,
+{
+    type Group = G;
+
+    /// $\langle g, z \rangle = a + c P$
+    fn relation(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+    ) -> (Vec<G>, G) {
+        (
+            statement
+                .generators
+                .clone(),
+            *a + statement.public_key * c,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::Group;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use sigmazk::SigmaProtocol;
+    use wrapped_ristretto::ristretto::WrappedRistretto;
+    use wrapped_ristretto::scalar::WrappedScalar;
+
+    use super::*;
+    use crate::compressable::{CompMechanism, ComposedHom};
+
+    type Protocol = SchnorrRepresentation<WrappedRistretto>;
+
+    fn statement_and_witness(
+        n: usize,
+    ) -> (
+        SchnorrRepresentationStatement<WrappedRistretto>,
+        Vec<WrappedScalar>,
+    ) {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let generators = (0..n)
+            .map(|_| WrappedRistretto::random(rng))
+            .collect();
+        let witness: Vec<WrappedScalar> = (0..n)
+            .map(|_| WrappedScalar::random(rng))
+            .collect();
+        (
+            SchnorrRepresentationStatement::init(
+                generators, &witness,
+            ),
+            witness,
+        )
+    }
+
+    #[test]
+    fn verify_compares_in_constant_time() {
+        let (statement, witness) = statement_and_witness(4);
+        let prover_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let (state, a) = Protocol::first(
+            &statement, &witness, prover_rng,
+        );
+        let c = Protocol::second(
+            &mut ChaCha20Rng::from_seed([2u8; 32]),
+        );
+        let z = Protocol::third(
+            &statement, state, &witness, &c, prover_rng,
+        );
+
+        let expected = a + statement.public_key * c;
+        assert!(bool::from(expected.ct_eq(&expected)));
+        assert!(!bool::from(expected.ct_eq(
+            &(expected + WrappedRistretto::generator())
+        )));
+        assert!(Protocol::verify(&statement, &a, &c, &z));
+        assert!(!Protocol::verify(
+            &statement,
+            &(a + WrappedRistretto::generator()),
+            &c,
+            &z
+        ));
+    }
+
+    #[test]
+    fn compressed_proof_verifies() {
+        let (statement, witness) = statement_and_witness(8);
+        let prover_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let (state, a) = Protocol::first(
+            &statement, &witness, prover_rng,
+        );
+        let c = Protocol::second(
+            &mut ChaCha20Rng::from_seed([2u8; 32]),
+        );
+        let z = Protocol::third(
+            &statement, state, &witness, &c, prover_rng,
+        );
+        assert!(Protocol::verify(&statement, &a, &c, &z));
+
+        let compressed =
+            Protocol::compress(&statement, &a, &c, &z);
+        assert_eq!(
+            compressed
+                .rounds
+                .len(),
+            3
+        );
+        assert!(Protocol::verify_compressed(
+            &statement,
+            &a,
+            &c,
+            &compressed
+        ));
+    }
+
+    #[test]
+    fn compressed_proof_rejects_tampering() {
+        let (statement, witness) = statement_and_witness(8);
+        let prover_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let (state, a) = Protocol::first(
+            &statement, &witness, prover_rng,
+        );
+        let c = Protocol::second(
+            &mut ChaCha20Rng::from_seed([2u8; 32]),
+        );
+        let z = Protocol::third(
+            &statement, state, &witness, &c, prover_rng,
+        );
+        let compressed =
+            Protocol::compress(&statement, &a, &c, &z);
+
+        let mut wrong_z = compressed.clone();
+        wrong_z.z += WrappedScalar::from(1u64);
+        assert!(!Protocol::verify_compressed(
+            &statement, &a, &c, &wrong_z
+        ));
+
+        let mut wrong_round = compressed.clone();
+        wrong_round.rounds[1].0 +=
+            WrappedRistretto::generator();
+        assert!(!Protocol::verify_compressed(
+            &statement,
+            &a,
+            &c,
+            &wrong_round
+        ));
+
+        let mut missing_round = compressed;
+        missing_round
+            .rounds
+            .pop();
+        assert!(!Protocol::verify_compressed(
+            &statement,
+            &a,
+            &c,
+            &missing_round
+        ));
+    }
+
+    #[test]
+    fn compressed_through_mechanism() {
+        type Mechanism = CompMechanism<
+            WrappedRistretto,
+            WrappedScalar,
+            WrappedRistretto,
+            ComposedHom<WrappedRistretto, WrappedRistretto>,
+        >;
+        let (statement, witness) = statement_and_witness(8);
+        let prover_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let (state, a) = Protocol::first(
+            &statement, &witness, prover_rng,
+        );
+        let c = Protocol::second(
+            &mut ChaCha20Rng::from_seed([2u8; 32]),
+        );
+        let z = Protocol::third(
+            &statement, state, &witness, &c, prover_rng,
+        );
+
+        let mechanism = Mechanism::new(8);
+        let compressed = mechanism
+            .compress::<Protocol>(&statement, &a, &c, &z)
+            .unwrap();
+        assert_eq!(
+            compressed,
+            Protocol::compress(&statement, &a, &c, &z)
+        );
+        assert!(mechanism.verify_compressed::<Protocol>(
+            &statement,
+            &a,
+            &c,
+            &compressed
+        ));
+
+        // A mechanism for another length neither compresses
+        // nor accepts the proof
+        let other = Mechanism::new(4);
+        assert!(other
+            .compress::<Protocol>(&statement, &a, &c, &z)
+            .is_none());
+        assert!(!other.verify_compressed::<Protocol>(
+            &statement,
+            &a,
+            &c,
+            &compressed
+        ));
+        assert!(mechanism
+            .compress::<Protocol>(
+                &statement,
+                &a,
+                &c,
+                &z[..4]
+            )
+            .is_none());
+    }
+}
@@ -12,13 +12,20 @@ use curve25519_dalek::ristretto::{
 use curve25519_dalek::scalar::Scalar;
 use rand_chacha::ChaCha20Rng;
 use rand_core::{CryptoRngCore, SeedableRng};
+use sha2::Sha512;
+use subtle::{Choice, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::stackable::Message;
+use sigmazk::error::Error;
+
+use crate::stackable::{Message, ReadMessage};
 use crate::util::hash;
 
 pub use crate::commitment_scheme::comm::PartialBindingCommScheme;
 
 /// 1 out of 2 commitment scheme
+#[derive(Clone, Copy)]
 pub struct HalfBinding;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -81,25 +88,137 @@ impl fmt::Debug for PublicParams {
     }
 }
 
-// impl Default for PublicParams {
-//     fn default() -> Self {
-//         PublicParams(
-//
-// RistrettoBasepointTable::create(&RistrettoPoint::random(
-//                 &mut ChaCha20Rng::from_entropy(),
-//             )),
-//
-// RistrettoBasepointTable::create(&RistrettoPoint::random(
-//                 &mut ChaCha20Rng::from_entropy(),
-//             )),
-//         )
-//     }
-// }
+impl PublicParams {
+    /// Write the compressed generators (g0, h) of the
+    /// public parameters
+    pub fn write<W: Write>(&self, writer: &mut W) {
+        for table in [&self.0, &self.1] {
+            writer
+                .write_all(
+                    table
+                        .basepoint()
+                        .compress()
+                        .as_bytes(),
+                )
+                .unwrap();
+        }
+    }
+
+    /// Read public parameters written by
+    /// [`PublicParams::write`]
+    pub fn read(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 64 {
+            return Err(Error::InvalidLength {
+                expected: 64,
+                got: bytes.len(),
+            });
+        }
+        let (g0, h) = bytes.split_at(32);
+        // Each half is 32 bytes, so only the decompression
+        // can fail
+        let table = |bytes: &[u8]| -> Result<_, Error> {
+            let point =
+                CompressedRistretto::from_slice(bytes)
+                    .ok()
+                    .and_then(|point| point.decompress())
+                    .ok_or(Error::InvalidPoint)?;
+            Ok(Rc::new(RistrettoBasepointTable::create(
+                &point,
+            )))
+        };
+        Ok(PublicParams(table(g0)?, table(h)?))
+    }
+}
+
+/// Serialized as the compressed generators `(g0, h)`, the
+/// basepoint tables are rebuilt when deserializing
+#[cfg(feature = "serde")]
+impl serde::Serialize for PublicParams {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (
+            self.0
+                .basepoint()
+                .compress(),
+            self.1
+                .basepoint()
+                .compress(),
+        )
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PublicParams {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let (g0, h) = <([u8; 32], [u8; 32])>::deserialize(
+            deserializer,
+        )?;
+        PublicParams::read(&[g0, h].concat()).map_err(|e| {
+            D::Error::custom(format!(
+                "invalid public parameters: {e:?}"
+            ))
+        })
+    }
+}
+
+/// Deterministic generator derived by hashing `label` to
+/// the curve, so that nobody knows its discrete log with
+/// respect to any other generator
+pub fn generator_from_label(
+    label: &[u8],
+) -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(label)
+}
+
+/// Canonical public parameters with generators derived from
+/// fixed labels, so they are reproducible without a trusted
+/// setup
+impl Default for PublicParams {
+    fn default() -> Self {
+        let table = |label| {
+            Rc::new(RistrettoBasepointTable::create(
+                &generator_from_label(label),
+            ))
+        };
+        PublicParams(
+            table(b"halfbinding-g0"),
+            table(b"halfbinding-h"),
+        )
+    }
+}
 
 #[derive(
     Copy, Clone, Debug, PartialEq, Eq, Hash, Default,
 )]
-pub struct CommitKey(pub CompressedRistretto);
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct CommitKey(
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "sigmazk::serde_helpers::compressed_ristretto"
+        )
+    )]
+    pub CompressedRistretto,
+);
+
+impl CommitKey {
+    /// Decompress the key into `g1`. Returns `None` if the
+    /// key is not a valid point.
+    pub fn decompress(&self) -> Option<RistrettoPoint> {
+        self.0
+            .decompress()
+    }
+}
 
 impl Message for CommitKey {
     fn write<W: Write>(&self, writer: &mut W) {
@@ -112,7 +231,17 @@ impl Message for CommitKey {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Hash)]
+impl ReadMessage for CommitKey {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(CommitKey(CompressedRistretto::read(bytes)?))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Commitment(pub [u8; 32], pub [u8; 32]);
 
 impl Default for Commitment {
@@ -121,6 +250,48 @@ impl Default for Commitment {
     }
 }
 
+impl Commitment {
+    /// Add two commitments half by half.
+    ///
+    /// Each half is a Pedersen commitment `r·h + hash(m)·gi`,
+    /// so for commitments under the same public parameters
+    /// and commit key the sum commits to the sum of the
+    /// message hashes with the sum of the randomness. Note
+    /// that this is *not* a commitment to any message `m`
+    /// in general, since `hash` is not additive.
+    ///
+    /// Returns `None` if either commitment does not
+    /// decompress to a pair of points.
+    pub fn add(
+        &self,
+        other: &Commitment,
+    ) -> Option<Commitment> {
+        let add = |a: &[u8; 32], b: &[u8; 32]| {
+            let a = CompressedRistretto(*a).decompress()?;
+            let b = CompressedRistretto(*b).decompress()?;
+            Some(
+                *(a + b)
+                    .compress()
+                    .as_bytes(),
+            )
+        };
+        Some(Commitment(
+            add(&self.0, &other.0)?,
+            add(&self.1, &other.1)?,
+        ))
+    }
+}
+
+impl ConstantTimeEq for Commitment {
+    fn ct_eq(&self, other: &Commitment) -> Choice {
+        self.0
+            .ct_eq(&other.0)
+            & self
+                .1
+                .ct_eq(&other.1)
+    }
+}
+
 impl Message for Commitment {
     fn write<W: Write>(&self, writer: &mut W) {
         let mut a = [0u8; 64];
@@ -132,15 +303,74 @@ impl Message for Commitment {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Equivocation key of the halfbinding commitment scheme.
+///
+/// The trapdoor allows its holder to open commitments on the
+/// equivocable side to any message, so `EquivKey` is
+/// deliberately not `Copy`. With the `zeroize` feature the
+/// trapdoor is wiped when the key is dropped, and it is
+/// redacted from the `Debug` output.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct EquivKey {
     binding_side: Side,
     trapdoor: Scalar,
-    pub commit_key: CommitKey,
+    commit_key: CommitKey,
+}
+
+impl fmt::Debug for EquivKey {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("EquivKey")
+            .field("binding_side", &self.binding_side)
+            .field("trapdoor", &"<redacted>")
+            .field("commit_key", &self.commit_key)
+            .finish()
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, Default,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Randomness(
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "sigmazk::serde_helpers::scalar")
+    )]
+    pub Scalar,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "sigmazk::serde_helpers::scalar")
+    )]
+    pub Scalar,
+);
+
+impl Message for Randomness {
+    fn write<W: Write>(&self, writer: &mut W) {
+        self.0
+            .write(writer);
+        self.1
+            .write(writer);
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct Randomness(pub Scalar, pub Scalar);
+impl ReadMessage for Randomness {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 64 {
+            return Err(Error::InvalidLength {
+                expected: 64,
+                got: bytes.len(),
+            });
+        }
+        let (r1, r2) = bytes.split_at(32);
+        Ok(Randomness(Scalar::read(r1)?, Scalar::read(r2)?))
+    }
+}
 
 impl Randomness {
     pub fn random<R: CryptoRngCore>(rng: &mut R) -> Self {
@@ -160,9 +390,89 @@ impl EquivKey {
             commit_key,
         }
     }
+
+    /// Commit key generated together with this equivocation
+    /// key
+    pub fn commit_key(&self) -> &CommitKey {
+        &self.commit_key
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for EquivKey {
+    fn zeroize(&mut self) {
+        self.trapdoor
+            .zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for EquivKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for EquivKey {}
+
 impl HalfBinding {
+    /// Commit like [`PartialBindingCommScheme::bind`] but
+    /// return `None` instead of panicking if the commit key
+    /// is not a valid point, e.g. when it comes from an
+    /// untrusted proof.
+    pub fn try_bind<M: Message + ?Sized>(
+        &self,
+        pp: &PublicParams,
+        ck: &CommitKey,
+        msg: &(Rc<M>, Rc<M>),
+        randomness: &Randomness,
+    ) -> Option<Commitment> {
+        let g1 = ck.decompress()?;
+        Some(self.bind_point(pp, &g1, msg, randomness))
+    }
+
+    /// Whether `msg` and `randomness` open `commitment`,
+    /// i.e. [`HalfBinding::bind`] recomputes it. Compares
+    /// in constant time and returns `false` if the commit
+    /// key is not a valid point.
+    pub fn open<M: Message>(
+        &self,
+        pp: &PublicParams,
+        ck: &CommitKey,
+        commitment: &Commitment,
+        msg: &(Rc<M>, Rc<M>),
+        randomness: &Randomness,
+    ) -> bool {
+        self.try_bind(pp, ck, msg, randomness)
+            .is_some_and(|comm| {
+                comm.ct_eq(commitment)
+                    .into()
+            })
+    }
+
+    /// Commit like [`HalfBinding::bind`] with the commit key
+    /// already decompressed into `g1`
+    pub fn bind_point<M: Message>(
+        &self,
+        pp: &PublicParams,
+        g1: &RistrettoPoint,
+        msg: &(Rc<M>, Rc<M>),
+        randomness: &Randomness,
+    ) -> Commitment {
+        let PublicParams(g0, h) = pp;
+        let g2 = Self::g2_from_g1(g1, g0);
+
+        let (m1, m2) = msg;
+        let Randomness(r1, r2) = randomness;
+        // We hash the message so that we can commit to longer
+        // strings
+        let comm1 = Self::commitment(g1, h, m1.clone(), r1);
+        let comm2 =
+            Self::commitment(&g2, h, m2.clone(), r2);
+        Commitment(*comm1.as_bytes(), *comm2.as_bytes())
+    }
+
     fn g2_from_g1(
         g1: &RistrettoPoint,
         g0: &RistrettoBasepointTable,
@@ -195,17 +505,7 @@ impl HalfBinding {
         rng: &mut R,
         binding_side: Side,
     ) -> (PublicParams, CommitKey, EquivKey) {
-        let h = RistrettoBasepointTable::create(
-            &RistrettoPoint::random(
-                &mut ChaCha20Rng::from_entropy(),
-            ),
-        );
-        let g0 = RistrettoBasepointTable::create(
-            &RistrettoPoint::random(
-                &mut ChaCha20Rng::from_entropy(),
-            ),
-        );
-        let pp = PublicParams(Rc::new(g0), Rc::new(h));
+        let pp = self.setup(rng);
         let (ck, ek) = self.gen(&pp, binding_side, rng);
         (pp, ck, ek)
     }
@@ -301,22 +601,8 @@ impl PartialBindingCommScheme for HalfBinding {
         msg: &(Rc<M>, Rc<M>),
         randomness: &Randomness,
     ) -> Commitment {
-        let PublicParams(g0, h) = pp;
-        let CommitKey(g1) = ck;
-        let g1 = g1
-            .decompress()
-            .unwrap();
-        let g2 = Self::g2_from_g1(&g1, g0);
-
-        let (m1, m2) = msg;
-        let Randomness(r1, r2) = randomness;
-        // We hash the message so that we can commit to longer
-        // strings
-        let comm1 =
-            Self::commitment(&g1, h, m1.clone(), &r1);
-        let comm2 =
-            Self::commitment(&g2, h, m2.clone(), &r2);
-        Commitment(*comm1.as_bytes(), *comm2.as_bytes())
+        self.try_bind(pp, ck, msg, randomness)
+            .expect("commit key is not a valid point")
     }
 
     /// Commit with access to the equivocation key. The
@@ -438,6 +724,49 @@ mod tests {
     use rand_chacha::ChaCha20Rng;
     use rand_core::SeedableRng;
 
+    #[test]
+    fn test_equivkey_debug_redacts_trapdoor() {
+        let (.., ek) = HalfBinding.setupgen(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+            Side::One,
+        );
+        let debug = format!("{:?}", ek);
+        assert!(debug.contains("<redacted>"));
+        assert!(
+            !debug.contains(&format!("{:?}", ek.trapdoor))
+        );
+        assert!(
+            debug.contains(&format!("{:?}", ek.commit_key))
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_equivkey_zeroize() {
+        // `Drop` zeroizes the key, see the impl above
+        fn zeroized_on_drop<T: ZeroizeOnDrop>() {}
+        zeroized_on_drop::<EquivKey>();
+
+        let (.., mut ek) = HalfBinding.setupgen(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+            Side::One,
+        );
+        assert_ne!(ek.trapdoor, Scalar::ZERO);
+
+        ek.zeroize();
+        assert_eq!(ek.trapdoor, Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_equivkey_commit_key_matches_gen() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let pp = HalfBinding.setup(rng);
+        for side in [Side::One, Side::Two] {
+            let (ck, ek) = HalfBinding.gen(&pp, side, rng);
+            assert_eq!(ek.commit_key(), &ck);
+        }
+    }
+
     #[test]
     fn test_g1g2() {
         let (pp, ck, ..) = HalfBinding.setupgen(
@@ -498,6 +827,178 @@ mod tests {
         assert_eq!(comm_equivcom, comm_bind);
     }
 
+    #[test]
+    fn test_half_binding_open() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let m = (
+            Rc::new("hello world".as_bytes()),
+            Rc::new("goodbye world".as_bytes()),
+        );
+        let pp = HalfBinding.setup(rng);
+        let (ck, _) = HalfBinding.gen(&pp, Side::Two, rng);
+        let aux = Randomness::random(rng);
+        let comm = HalfBinding.bind(&pp, &ck, &m, &aux);
+        assert!(HalfBinding.open(&pp, &ck, &comm, &m, &aux));
+
+        let tampered = (
+            m.0.clone(),
+            Rc::new("hello world".as_bytes()),
+        );
+        assert!(!HalfBinding
+            .open(&pp, &ck, &comm, &tampered, &aux));
+        let other_aux = Randomness::random(rng);
+        assert!(!HalfBinding
+            .open(&pp, &ck, &comm, &m, &other_aux));
+        let garbage =
+            CommitKey(CompressedRistretto([0xff; 32]));
+        assert!(!HalfBinding
+            .open(&pp, &garbage, &comm, &m, &aux));
+    }
+
+    #[test]
+    fn test_try_bind_rejects_invalid_commit_key() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let aux = Randomness::random(rng);
+        let m = (
+            Rc::new("hello world".as_bytes()),
+            Rc::new(<&[u8]>::default()),
+        );
+        let pp = HalfBinding.setup(rng);
+        let (ck, ek) = HalfBinding.gen(&pp, Side::One, rng);
+        let (comm, aux) =
+            HalfBinding.equivcom(&pp, &ek, &m, Some(aux));
+        assert_eq!(
+            HalfBinding.try_bind(&pp, &ck, &m, &aux),
+            Some(comm)
+        );
+
+        // A corrupted key fails to open rather than panicking,
+        // and is rejected when read from bytes
+        let garbage =
+            CommitKey(CompressedRistretto([0xff; 32]));
+        assert_eq!(
+            HalfBinding.try_bind(&pp, &garbage, &m, &aux),
+            None
+        );
+        assert!(matches!(
+            CommitKey::read(
+                garbage
+                    .0
+                    .as_bytes()
+            ),
+            Err(Error::InvalidPoint)
+        ));
+        assert!(matches!(
+            Randomness::read(&[0u8; 63]),
+            Err(Error::InvalidLength {
+                expected: 64,
+                got: 63
+            })
+        ));
+    }
+
+    #[test]
+    fn test_commitment_add() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let pp = HalfBinding.setup(rng);
+        let (ck, _) = HalfBinding.gen(&pp, Side::One, rng);
+        let m1 = (
+            Rc::new("hello".as_bytes()),
+            Rc::new("world".as_bytes()),
+        );
+        let m2 = (
+            Rc::new("goodbye".as_bytes()),
+            Rc::new("moon".as_bytes()),
+        );
+        let r1 = Randomness::random(rng);
+        let r2 = Randomness::random(rng);
+
+        let sum = HalfBinding
+            .bind(&pp, &ck, &m1, &r1)
+            .add(&HalfBinding.bind(&pp, &ck, &m2, &r2))
+            .unwrap();
+
+        // Commitment to the sum of the message hashes with
+        // the sum of the randomness
+        let PublicParams(g0, h) = &pp;
+        let g1 = ck
+            .decompress()
+            .unwrap();
+        let g2 = HalfBinding::g2_from_g1(&g1, g0);
+        let expected = |g: &RistrettoPoint,
+                        a: &[u8],
+                        b: &[u8],
+                        r: Scalar| {
+            *(h.as_ref() * &r + g * (hash(&a) + hash(&b)))
+                .compress()
+                .as_bytes()
+        };
+        assert_eq!(
+            sum,
+            Commitment(
+                expected(
+                    &g1,
+                    m1.0.as_ref(),
+                    m2.0.as_ref(),
+                    r1.0 + r2.0
+                ),
+                expected(
+                    &g2,
+                    m1.1.as_ref(),
+                    m2.1.as_ref(),
+                    r1.1 + r2.1
+                ),
+            )
+        );
+
+        // Not a valid point
+        let garbage = Commitment([0xff; 32], [0xff; 32]);
+        assert!(sum
+            .add(&garbage)
+            .is_none());
+    }
+
+    #[test]
+    fn test_default_public_params() {
+        let pp = PublicParams::default();
+        assert_eq!(pp, PublicParams::default());
+        assert_ne!(pp.0.basepoint(), pp.1.basepoint());
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let aux = Randomness::random(rng);
+        let m = (
+            Rc::new("hello world".as_bytes()),
+            Rc::new(<&[u8]>::default()),
+        );
+        let m_equiv = (
+            Rc::new("hello world".as_bytes()),
+            Rc::new("goodbye world".as_bytes()),
+        );
+        let (ck, ek) = HalfBinding.gen(&pp, Side::One, rng);
+
+        let (comm_equivcom, aux_old) =
+            HalfBinding.equivcom(&pp, &ek, &m, Some(aux));
+        let aux_new = HalfBinding
+            .equiv(&pp, &ek, &m, &m_equiv, &aux_old);
+        let comm_bind =
+            HalfBinding.bind(&pp, &ck, &m_equiv, &aux_new);
+        assert_eq!(comm_equivcom, comm_bind);
+    }
+
+    #[test]
+    fn test_public_params_read_rejects_invalid_points() {
+        let pp = PublicParams::default();
+        let mut bytes = Vec::new();
+        pp.write(&mut bytes);
+        assert_eq!(PublicParams::read(&bytes).unwrap(), pp);
+
+        bytes[32..].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            PublicParams::read(&bytes),
+            Err(Error::InvalidPoint)
+        ));
+    }
+
     #[test]
     fn test_half_binding_fails() {
         let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
@@ -1,3 +1,4 @@
 pub mod comm;
 pub mod halfbinding;
 pub mod qbinding;
+pub mod widebinding;
@@ -1,6 +1,10 @@
 use std::fmt::Debug;
 
 #[derive(Clone, Debug, PartialEq, Hash, Default, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Inner<T: Clone>(pub Vec<T>);
 
 impl<T: Clone> Inner<T> {
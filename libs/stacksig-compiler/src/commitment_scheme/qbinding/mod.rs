@@ -8,10 +8,15 @@ use std::io::Write;
 use inner_outer::*;
 pub use qbinding::*;
 use rand_core::CryptoRngCore;
+use sigmazk::error::Error;
 
 use crate::commitment_scheme::halfbinding::{self, Side};
-use crate::stackable::Message;
+use crate::stackable::{Message, ReadMessage};
+use crate::util::read_chunks;
 
+/// Smallest q supported by [`QBinding`]. The 1-of-2^1
+/// scheme is a single halfbinding commitment, available
+/// over the same `Vec<Rc<M>>` messages as [`VecHalfBinding`].
 pub const MIN_Q: usize = 2;
 /// Defines the binding index for a 1-of-2^q
 /// partially-binding commitment scheme
@@ -69,8 +74,18 @@ impl BindingIndex {
         self.index % (self.length >> 1)
     }
 
-    pub fn get_inner(&self) -> Self {
-        BindingIndex::new(self.q - 1, self.get_inner_raw())
+    /// Binding index of the inner 1-of-2^(q-1) scheme, or
+    /// [`Error::BaseBindingIndex`] at the base level, whose
+    /// inner scheme is a single halfbinding commitment (see
+    /// [`BindingIndex::base_inner`])
+    pub fn get_inner(&self) -> Result<Self, Error> {
+        if self.is_base() {
+            return Err(Error::BaseBindingIndex);
+        }
+        Ok(BindingIndex::new(
+            self.q - 1,
+            self.get_inner_raw(),
+        ))
     }
 
     pub fn base_inner(&self) -> Option<Side> {
@@ -94,8 +109,10 @@ impl BindingIndex {
         }
     }
 
-    pub fn get_inner_outer(&self) -> (BindingIndex, Side) {
-        (self.get_inner(), self.get_outer())
+    pub fn get_inner_outer(
+        &self,
+    ) -> Result<(BindingIndex, Side), Error> {
+        Ok((self.get_inner()?, self.get_outer()))
     }
 
     pub fn base_inner_outer(&self) -> (Side, Side) {
@@ -113,6 +130,87 @@ pub struct PublicParams {
     outer: halfbinding::PublicParams,
 }
 
+impl PublicParams {
+    /// Number of halfbinding layers, i.e. the q of the
+    /// 1-of-2^q scheme these parameters were set up for
+    pub fn q(&self) -> usize {
+        self.inner
+            .0
+            .len()
+            + 1
+    }
+
+    /// Write the generators of every halfbinding layer,
+    /// from the innermost to the outermost
+    pub fn write<W: Write>(&self, writer: &mut W) {
+        for pp in self
+            .compose()
+            .as_vec()
+        {
+            pp.write(writer);
+        }
+    }
+
+    /// Read public parameters written by
+    /// [`PublicParams::write`]
+    pub fn read(bytes: &[u8]) -> Result<Self, Error> {
+        if !bytes
+            .len()
+            .is_multiple_of(64)
+            || bytes.len() < 64 * MIN_Q
+        {
+            return Err(Error::InvalidEncoding);
+        }
+        let composed = bytes
+            .chunks(64)
+            .map(halfbinding::PublicParams::read)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (inner, outer) = Inner::init(composed)
+            .uncap()
+            .unwrap();
+        Ok(Self { inner, outer })
+    }
+}
+
+/// Serialized as the halfbinding parameters of every layer,
+/// from the innermost to the outermost, like
+/// [`PublicParams::write`]
+#[cfg(feature = "serde")]
+impl serde::Serialize for PublicParams {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.compose()
+            .as_vec()
+            .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PublicParams {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let composed =
+            Vec::<halfbinding::PublicParams>::deserialize(
+                deserializer,
+            )?;
+        if composed.len() < MIN_Q {
+            return Err(D::Error::invalid_length(
+                composed.len(),
+                &"at least MIN_Q layers",
+            ));
+        }
+        let (inner, outer) = Inner::init(composed)
+            .uncap()
+            .unwrap();
+        Ok(Self { inner, outer })
+    }
+}
+
 impl InnerOuter<halfbinding::PublicParams>
     for PublicParams
 {
@@ -141,6 +239,10 @@ impl InnerOuter<halfbinding::PublicParams>
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct CommitKey {
     pub inner_ck: Inner<halfbinding::CommitKey>,
     pub outer_ck: halfbinding::CommitKey,
@@ -197,7 +299,39 @@ impl Message for CommitKey {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Read a composed vector of `size`-byte components and
+/// split it into its inner and outer parts
+fn read_inner_outer<T: ReadMessage>(
+    bytes: &[u8],
+    size: usize,
+) -> Result<(Inner<T>, T), Error> {
+    let composed = Inner::init(read_chunks(bytes, size)?);
+    // A 1-of-2^q scheme has q - 1 inner components
+    if composed
+        .as_vec()
+        .len()
+        < MIN_Q
+    {
+        return Err(Error::InvalidEncoding);
+    }
+    Ok(composed
+        .uncap()
+        .unwrap())
+}
+
+impl ReadMessage for CommitKey {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let (inner_ck, outer_ck) =
+            read_inner_outer(bytes, 32)?;
+        Ok(Self { inner_ck, outer_ck })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Randomness {
     pub inner: Inner<halfbinding::Randomness>,
     pub outer: halfbinding::Randomness,
@@ -226,28 +360,69 @@ impl InnerOuter<halfbinding::Randomness> for Randomness {
     }
 }
 
+impl Message for Inner<halfbinding::Randomness> {
+    fn write<W: Write>(&self, writer: &mut W) {
+        for r in &self.0 {
+            r.write(writer);
+        }
+    }
+}
+
+impl Message for Randomness {
+    fn write<W: Write>(&self, writer: &mut W) {
+        self.inner
+            .write(writer);
+        self.outer
+            .write(writer);
+    }
+}
+
+impl ReadMessage for Randomness {
+    fn read(bytes: &[u8]) -> Result<Self, Error> {
+        let (inner, outer) = read_inner_outer(bytes, 64)?;
+        Ok(Self { inner, outer })
+    }
+}
+
 impl Inner<halfbinding::Randomness> {
+    /// Randomness of the `q - 1` inner layers of a
+    /// 1-of-2^q scheme
     pub fn random<R: CryptoRngCore>(
         rng: &mut R,
         q: usize,
     ) -> Self {
-        Self(vec![halfbinding::Randomness::random(rng); q])
+        Self(
+            (1..q)
+                .map(|_| {
+                    halfbinding::Randomness::random(rng)
+                })
+                .collect(),
+        )
     }
 }
 
 impl Randomness {
+    /// Randomness of every layer of a 1-of-2^q scheme
     pub fn random<R: CryptoRngCore>(
         rng: &mut R,
         q: usize,
     ) -> Self {
         Randomness {
             inner: Inner::<halfbinding::Randomness>::random(
-                rng,
-                q - 1,
+                rng, q,
             ),
             outer: halfbinding::Randomness::random(rng),
         }
     }
+
+    /// Number of halfbinding layers this randomness is for,
+    /// i.e. the q of the 1-of-2^q scheme
+    pub fn q(&self) -> usize {
+        self.inner
+            .0
+            .len()
+            + 1
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -267,7 +442,9 @@ impl EquivKey {
         &self.ck
     }
 
-    pub fn extract(&self) -> Self
+    /// Equivocation key of the inner 1-of-2^(q-1) scheme, or
+    /// [`Error::BaseBindingIndex`] at the base level
+    pub fn extract(&self) -> Result<Self, Error>
     where
         Self: Sized,
     {
@@ -275,12 +452,12 @@ impl EquivKey {
             self.ck
                 .extract(()),
             self.binding_index
-                .get_inner(),
+                .get_inner()?,
         );
 
-        <Self as InnerOuter<halfbinding::EquivKey>>::extract(
+        Ok(<Self as InnerOuter<halfbinding::EquivKey>>::extract(
             &self, initial,
-        )
+        ))
     }
 }
 
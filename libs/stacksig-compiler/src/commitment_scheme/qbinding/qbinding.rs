@@ -3,6 +3,8 @@
 //! schemes
 use std::rc::Rc;
 
+use subtle::ConstantTimeEq;
+
 pub use crate::commitment_scheme::comm::PartialBindingCommScheme;
 use crate::commitment_scheme::halfbinding::{
     Commitment, HalfBinding,
@@ -10,16 +12,48 @@ use crate::commitment_scheme::halfbinding::{
 
 use super::*;
 
+#[derive(Clone, Copy)]
 pub struct QBinding {
     pub q: usize,
 }
 
 impl QBinding {
+    /// Largest supported `q`. Every operation recurses once
+    /// per layer and commits to `2^q` messages, so deeper
+    /// schemes would exhaust the stack or overflow the
+    /// message count long before they are usable.
+    pub const MAX_Q: usize = 32;
+
+    /// Panics unless `MIN_Q <= q <= QBinding::MAX_Q`; use
+    /// [`VecHalfBinding`] for `q = 1`
     pub fn new(q: usize) -> Self {
-        assert!(q >= MIN_Q);
+        assert!(
+            q >= MIN_Q,
+            "QBinding requires q >= {}, got {}; use \
+             VecHalfBinding for q = 1",
+            MIN_Q,
+            q
+        );
+        assert!(
+            q <= Self::MAX_Q,
+            "QBinding requires q <= {}, got {}",
+            Self::MAX_Q,
+            q
+        );
         QBinding { q }
     }
 
+    /// Like [`QBinding::new`], but fails with
+    /// [`Error::UnsupportedDepth`] instead of panicking if
+    /// `q` is out of range
+    pub fn try_new(q: usize) -> Result<Self, Error> {
+        if (MIN_Q..=Self::MAX_Q).contains(&q) {
+            Ok(QBinding { q })
+        } else {
+            Err(Error::UnsupportedDepth)
+        }
+    }
+
     pub fn fold<A, F>(&self, init: A, fold: F) -> A
     where
         F: FnMut(A, usize) -> A,
@@ -51,87 +85,129 @@ impl QBinding {
     pub fn inner_length(&self) -> usize {
         1 << (self.q - 1)
     }
-}
-/// Implementation of 1-of-2^2 partially-binding vector
-/// commitment from discrete log using 2 halfbinding
-/// comitment schemes.
-///
-/// We can obtain a 1-of-2^q partially-binding vector
-/// commitment by using this iteratively.
-impl PartialBindingCommScheme for QBinding {
-    type PublicParams = PublicParams;
-    type BindingIndex = BindingIndex;
-    type CommitKey = CommitKey;
-    type EquivKey = EquivKey;
-    type Commitment = Commitment;
-    type Randomness = Randomness;
-    type Msg<'a, M: Message + 'a> = Vec<Rc<M>>;
 
-    /// Setup public parameters
-    fn setup<R: CryptoRngCore>(
-        &self,
-        rng: &mut R,
-    ) -> PublicParams {
-        let base = PublicParams {
-            inner: Inner::new(HalfBinding.setup(rng)),
-            outer: HalfBinding.setup(rng),
-        };
-        self.fold(base, |inner, _| {
-            let outer = HalfBinding.setup(rng);
-            PublicParams {
-                inner: inner.compose(),
-                outer,
-            }
-        })
+    /// Number of messages the scheme commits to, `2^q`
+    pub fn expected_msg_len(&self) -> usize {
+        1 << self.q
     }
 
-    /// Generate commitment key and equivocation key
-    fn gen<R: CryptoRngCore>(
+    /// Commit like [`PartialBindingCommScheme::bind`] but
+    /// return `None` instead of panicking if a component of
+    /// the commit key is not a valid point or `msg` does
+    /// not have [`QBinding::expected_msg_len`] messages.
+    ///
+    /// Every component of the commit key is decompressed once
+    /// up front rather than in each halfbinding commitment.
+    pub fn try_bind<M: Message>(
         &self,
         pp: &PublicParams,
-        binding_index: BindingIndex,
-        rng: &mut R,
-    ) -> (CommitKey, EquivKey) {
-        // Base case
-        let (inner_ck, inner_ek) =
-            if self.is_base() {
-                let (inner_ck, inner_ek) = HalfBinding.gen(
-                    pp.base_inner(),
-                    binding_index
-                        .base_inner()
-                        .unwrap(),
-                    rng,
-                );
-
-                (Inner::new(inner_ck), Inner::new(inner_ek))
-            } else {
-                // Recursive case
-                let inner_side = binding_index.get_inner();
-                let (inner_ck, inner_ek) = QBinding::new(
-                    self.q - 1,
-                )
-                .gen(&pp.extract(()), inner_side, rng);
+        ck: &CommitKey,
+        msg: &[Rc<M>],
+        r: &Randomness,
+    ) -> Option<Commitment> {
+        self.try_bind_iter(
+            pp,
+            ck,
+            msg.iter()
+                .cloned(),
+            r,
+        )
+    }
 
-                (inner_ck.compose(), inner_ek.compose())
-            };
+    /// Whether `msg` and `r` open `commitment`, i.e.
+    /// [`QBinding::try_bind`] recomputes it. Compares in
+    /// constant time and returns `false` where `try_bind`
+    /// returns `None`.
+    pub fn open<M: Message>(
+        &self,
+        pp: &PublicParams,
+        ck: &CommitKey,
+        commitment: &Commitment,
+        msg: &[Rc<M>],
+        r: &Randomness,
+    ) -> bool {
+        self.try_bind(pp, ck, msg, r)
+            .is_some_and(|comm| {
+                comm.ct_eq(commitment)
+                    .into()
+            })
+    }
 
-        let outer_side = binding_index.get_outer();
-        let (outer_ck, outer_ek) = HalfBinding.gen(
-            pp.get_outer(),
-            outer_side,
-            rng,
-        );
-        let ck = CommitKey { inner_ck, outer_ck };
+    /// Commit to the messages yielded by `msg` in a single
+    /// pass, without collecting them.
+    ///
+    /// The commitment tree is built from the leaves up while
+    /// the messages are consumed, keeping only one pending
+    /// commitment per layer. Returns `None` if the commit key
+    /// is invalid or if `msg` does not yield exactly `2^q`
+    /// messages.
+    pub fn try_bind_iter<M, I>(
+        &self,
+        pp: &PublicParams,
+        ck: &CommitKey,
+        msg: I,
+        r: &Randomness,
+    ) -> Option<Commitment>
+    where
+        M: Message,
+        I: IntoIterator<Item = Rc<M>>,
+    {
+        // Every layer from the innermost to the outermost
+        let g1s = ck
+            .compose()
+            .as_vec()
+            .iter()
+            .map(|ck| ck.decompress())
+            .collect::<Option<Vec<_>>>()?;
+        let (pps, rs) = (pp.compose(), r.compose());
+        let (pps, rs) = (pps.as_vec(), rs.as_vec());
+        if g1s.len() != self.q
+            || pps.len() != self.q
+            || rs.len() != self.q
+        {
+            return None;
+        }
 
-        (
-            ck.clone(),
-            EquivKey {
-                inner_ek,
-                ck,
-                outer_ek,
-                binding_index,
-            },
-        )
+        // pending[i] is the left child of the next node of
+        // layer i, waiting for its sibling
+        let mut pending: Vec<Option<Commitment>> =
+            vec![None; self.q];
+        let mut root = None;
+        let mut msg = msg.into_iter();
+        while let Some(left) = msg.next() {
+            if root.is_some() {
+                return None;
+            }
+            let right = msg.next()?;
+            let mut comm = HalfBinding.bind_point(
+                &pps[0],
+                &g1s[0],
+                &(left, right),
+                &rs[0],
+            );
+            let mut i = 1;
+            while i < self.q {
+                match pending[i].take() {
+                    Some(left) => {
+                        comm = HalfBinding.bind_point(
+                            &pps[i],
+                            &g1s[i],
+                            &(Rc::new(left), Rc::new(comm)),
+                            &rs[i],
+                        );
+                        i += 1;
+                    }
+                    None => {
+                        pending[i] = Some(comm);
+                        break;
+                    }
+                }
+            }
+            if i == self.q {
+                root = Some(comm);
+            }
+        }
+        root
     }
 
     /// Given equivocation key, commit to a vector of
@@ -141,14 +217,22 @@ impl PartialBindingCommScheme for QBinding {
     /// `msg`: Message to commit to
     ///
     /// ## Returns
-    /// Commitment and randomness
-    fn equivcom<'a, M: Message + ?Sized>(
+    /// Commitment and randomness, or
+    /// [`Error::InvalidLength`] if `msg` does not have
+    /// [`QBinding::expected_msg_len`] messages and
+    /// [`Error::RandomnessDepthMismatch`] if `aux` is not
+    /// the randomness of a 1-of-2^q scheme
+    pub fn try_equivcom<M: Message>(
         &self,
         pp: &PublicParams,
         ek: &EquivKey,
-        msg: &Vec<Rc<M>>,
+        msg: &[Rc<M>],
         aux: Option<Randomness>,
-    ) -> (Commitment, Randomness) {
+    ) -> Result<(Commitment, Randomness), Error> {
+        self.check_msg_len(msg)?;
+        if let Some(aux) = &aux {
+            self.check_depth(aux)?;
+        }
         let bound_index = ek
             .binding_index
             .index();
@@ -221,7 +305,7 @@ impl PartialBindingCommScheme for QBinding {
             let (inner_comm, inner_aux) =
                 QBinding::new(self.q - 1).equivcom(
                     &pp.extract(()),
-                    &ek.extract(),
+                    &ek.extract()?,
                     &message,
                     inner_rand,
                 );
@@ -247,96 +331,32 @@ impl PartialBindingCommScheme for QBinding {
             outer_rand,
         );
 
-        (
+        Ok((
             outer_comm,
             Randomness {
                 inner: inner_aux,
                 outer: outer_aux,
             },
-        )
+        ))
     }
 
-    /// Commit to a 4-tuple message which make up 2 chunks
-    /// of 2-tuple messages We use the inner commitment
-    /// scheme to commit to each of the 2 chunks and the
-    /// outer commitment scheme to commit to the 2 inner
-    /// commitments
-    ///
-    /// ## Parameters
-    /// `pp`: Public parameters
-    /// `ck`: Commitment key
-    /// `msg`: Messages to commit to
-    /// `r`: Randomness (auxiliary variables needed for
-    /// commitment)
-    fn bind<'a, M: Message + ?Sized>(
-        &self,
-        pp: &PublicParams,
-        ck: &CommitKey,
-        msg: &Vec<Rc<M>>,
-        r: &Randomness,
-    ) -> Commitment {
-        let (comm1, comm2) = if self.is_base() {
-            // Base case
-            let (pp, ck, r) = (
-                pp.base_inner(),
-                ck.base_inner(),
-                r.base_inner(),
-            );
-            let comm1 = HalfBinding.bind(
-                pp,
-                ck,
-                &(msg[0].clone(), msg[1].clone()),
-                r,
-            );
-            let comm2 = HalfBinding.bind(
-                pp,
-                ck,
-                &(msg[2].clone(), msg[3].clone()),
-                r,
-            );
-
-            (comm1, comm2)
-        } else {
-            // Recursive case
-            let inner_q = QBinding::new(self.q - 1);
-            let (pp, ck, r) = (
-                &pp.extract(()),
-                &ck.extract(()),
-                &r.extract(()),
-            );
-
-            let comm1 = inner_q.bind(
-                pp,
-                ck,
-                &msg[0..self.inner_length()].to_vec(),
-                r,
-            );
-            let comm2 = inner_q.bind(
-                pp,
-                ck,
-                &msg[self.inner_length()..].to_vec(),
-                r,
-            );
-
-            (comm1, comm2)
-        };
-
-        return HalfBinding.bind(
-            pp.get_outer(),
-            ck.get_outer(),
-            &(Rc::new(comm1), Rc::new(comm2)),
-            r.get_outer(),
-        );
-    }
-
-    fn equiv<'a, M: Message + ?Sized>(
+    /// Equivocate like [`PartialBindingCommScheme::equiv`]
+    /// but return [`Error::InvalidLength`] if `old` or
+    /// `new` does not have [`QBinding::expected_msg_len`]
+    /// messages and [`Error::RandomnessDepthMismatch`] if
+    /// `old_aux` is not the randomness of a 1-of-2^q
+    /// scheme, instead of panicking
+    pub fn try_equiv<M: Message>(
         &self,
         pp: &PublicParams,
         ek: &EquivKey,
-        old: &Vec<Rc<M>>,
-        new: &Vec<Rc<M>>,
+        old: &[Rc<M>],
+        new: &[Rc<M>],
         old_aux: &Randomness,
-    ) -> Randomness {
+    ) -> Result<Randomness, Error> {
+        self.check_msg_len(old)?;
+        self.check_msg_len(new)?;
+        self.check_depth(old_aux)?;
         let bound_index = ek
             .binding_index
             .index();
@@ -370,8 +390,8 @@ impl PartialBindingCommScheme for QBinding {
             };
             let (pp, ck, ek, old_aux) = (
                 pp.base_inner(),
-                &ek.base_inner()
-                    .commit_key,
+                ek.base_inner()
+                    .commit_key(),
                 ek.base_inner(),
                 old_aux.base_inner(),
             );
@@ -456,19 +476,19 @@ impl PartialBindingCommScheme for QBinding {
                 &pp.extract(()),
                 &ek.ck()
                     .extract(()),
-                &ek.extract(),
+                &ek.extract()?,
                 &old_aux.extract(()),
             );
 
             // Here, we equivocate our old inner message with our new
             // inner message
-            let new_inner_aux = inner_q.equiv(
+            let new_inner_aux = inner_q.try_equiv(
                 pp,
                 ek,
                 &old_inner_message,
                 new_inner_message,
                 old_aux,
-            );
+            )?;
 
             // Recompute the inner commitment with new inner aux so that
             // we can recompute our outer level vector later
@@ -524,10 +544,343 @@ impl PartialBindingCommScheme for QBinding {
             old_aux.get_outer(),
         );
 
-        Randomness {
+        Ok(Randomness {
             inner: new_inner_aux,
             outer: new_outer_aux,
+        })
+    }
+
+    /// Check that `r` holds one halfbinding randomness per
+    /// layer of the scheme
+    fn check_depth(
+        &self,
+        r: &Randomness,
+    ) -> Result<(), Error> {
+        if r.q() != self.q {
+            return Err(Error::RandomnessDepthMismatch);
+        }
+        Ok(())
+    }
+
+    /// Check that `msg` has one message per leaf of the
+    /// commitment tree
+    fn check_msg_len<T>(
+        &self,
+        msg: &[T],
+    ) -> Result<(), Error> {
+        if msg.len() != self.expected_msg_len() {
+            return Err(Error::InvalidLength {
+                expected: self.expected_msg_len(),
+                got: msg.len(),
+            });
         }
+        Ok(())
+    }
+
+    /// Self-check of [`PartialBindingCommScheme::equiv`] in
+    /// debug builds: binding `new` with `new_aux` must give
+    /// the commitment that `equivcom` made to `old` with
+    /// `old_aux`. Otherwise the caller equivocated the bound
+    /// message or passed the wrong randomness, which would
+    /// only show up as a failed verification much later.
+    #[cfg(debug_assertions)]
+    fn assert_equiv_opens<M: Message>(
+        &self,
+        pp: &PublicParams,
+        ek: &EquivKey,
+        old: &[Rc<M>],
+        new: &[Rc<M>],
+        old_aux: &Randomness,
+        new_aux: &Randomness,
+    ) {
+        // equivcom is deterministic given its randomness
+        let committed = self
+            .try_equivcom(
+                pp,
+                ek,
+                old,
+                Some(old_aux.clone()),
+            )
+            .map(|(c, _)| c)
+            .ok();
+        let bound_index = ek
+            .binding_index
+            .index();
+        assert!(
+            committed
+                == self.try_bind(pp, ek.ck(), new, new_aux),
+            "equiv does not open the equivcom commitment to \
+             the new message: the message at binding index \
+             {} differs from the committed one, or the \
+             randomness is not the one returned by equivcom",
+            bound_index
+        );
+    }
+}
+/// Implementation of 1-of-2^2 partially-binding vector
+/// commitment from discrete log using 2 halfbinding
+/// comitment schemes.
+///
+/// We can obtain a 1-of-2^q partially-binding vector
+/// commitment by using this iteratively.
+impl PartialBindingCommScheme for QBinding {
+    type PublicParams = PublicParams;
+    type BindingIndex = BindingIndex;
+    type CommitKey = CommitKey;
+    type EquivKey = EquivKey;
+    type Commitment = Commitment;
+    type Randomness = Randomness;
+    type Msg<'a, M: Message + 'a> = Vec<Rc<M>>;
+
+    /// Setup public parameters
+    fn setup<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+    ) -> PublicParams {
+        let base = PublicParams {
+            inner: Inner::new(HalfBinding.setup(rng)),
+            outer: HalfBinding.setup(rng),
+        };
+        self.fold(base, |inner, _| {
+            let outer = HalfBinding.setup(rng);
+            PublicParams {
+                inner: inner.compose(),
+                outer,
+            }
+        })
+    }
+
+    /// Generate commitment key and equivocation key
+    fn gen<R: CryptoRngCore>(
+        &self,
+        pp: &PublicParams,
+        binding_index: BindingIndex,
+        rng: &mut R,
+    ) -> (CommitKey, EquivKey) {
+        assert_eq!(
+            binding_index.q(),
+            self.q,
+            "binding index is not for a 1-of-2^{} scheme",
+            self.q
+        );
+        let (inner_ck, inner_ek) = match binding_index
+            .get_inner()
+        {
+            // Base case
+            Err(_) => {
+                let (inner_ck, inner_ek) = HalfBinding.gen(
+                    pp.base_inner(),
+                    binding_index
+                        .base_inner()
+                        .unwrap(),
+                    rng,
+                );
+
+                (Inner::new(inner_ck), Inner::new(inner_ek))
+            }
+            // Recursive case
+            Ok(inner_side) => {
+                let (inner_ck, inner_ek) = QBinding::new(
+                    self.q - 1,
+                )
+                .gen(&pp.extract(()), inner_side, rng);
+
+                (inner_ck.compose(), inner_ek.compose())
+            }
+        };
+
+        let outer_side = binding_index.get_outer();
+        let (outer_ck, outer_ek) = HalfBinding.gen(
+            pp.get_outer(),
+            outer_side,
+            rng,
+        );
+        let ck = CommitKey { inner_ck, outer_ck };
+
+        (
+            ck.clone(),
+            EquivKey {
+                inner_ek,
+                ck,
+                outer_ek,
+                binding_index,
+            },
+        )
+    }
+
+    /// Given equivocation key, commit to a vector of
+    /// messages of length 2^q.
+    ///
+    /// Panics if `msg` does not have
+    /// [`QBinding::expected_msg_len`] messages or `aux` is
+    /// not the randomness of a 1-of-2^q scheme; see
+    /// [`QBinding::try_equivcom`].
+    fn equivcom<'a, M: Message + ?Sized>(
+        &self,
+        pp: &PublicParams,
+        ek: &EquivKey,
+        msg: &Vec<Rc<M>>,
+        aux: Option<Randomness>,
+    ) -> (Commitment, Randomness) {
+        self.try_equivcom(pp, ek, msg, aux)
+            .unwrap_or_else(|e| {
+                panic!("equivcom failed: {:?}", e)
+            })
+    }
+
+    /// Commit to a 4-tuple message which make up 2 chunks
+    /// of 2-tuple messages We use the inner commitment
+    /// scheme to commit to each of the 2 chunks and the
+    /// outer commitment scheme to commit to the 2 inner
+    /// commitments
+    ///
+    /// ## Parameters
+    /// `pp`: Public parameters
+    /// `ck`: Commitment key
+    /// `msg`: Messages to commit to
+    /// `r`: Randomness (auxiliary variables needed for
+    /// commitment)
+    fn bind<'a, M: Message + ?Sized>(
+        &self,
+        pp: &PublicParams,
+        ck: &CommitKey,
+        msg: &Vec<Rc<M>>,
+        r: &Randomness,
+    ) -> Commitment {
+        assert_eq!(
+            msg.len(),
+            self.expected_msg_len(),
+            "a 1-of-2^{} commitment takes {} messages",
+            self.q,
+            self.expected_msg_len()
+        );
+        self.try_bind(pp, ck, msg, r)
+            .expect("commit key is not a valid point")
+    }
+
+    /// Panics if `old` or `new` does not have
+    /// [`QBinding::expected_msg_len`] messages or `old_aux`
+    /// is not the randomness of a 1-of-2^q scheme; see
+    /// [`QBinding::try_equiv`]. In
+    /// debug builds it also panics if the result does not
+    /// open the commitment of `old` to `new`.
+    fn equiv<'a, M: Message + ?Sized>(
+        &self,
+        pp: &PublicParams,
+        ek: &EquivKey,
+        old: &Vec<Rc<M>>,
+        new: &Vec<Rc<M>>,
+        old_aux: &Randomness,
+    ) -> Randomness {
+        let new_aux = self
+            .try_equiv(pp, ek, old, new, old_aux)
+            .unwrap_or_else(|e| {
+                panic!("equiv failed: {:?}", e)
+            });
+        #[cfg(debug_assertions)]
+        self.assert_equiv_opens(
+            pp, ek, old, new, old_aux, &new_aux,
+        );
+        new_aux
+    }
+}
+
+/// The 1-of-2^1 partially-binding commitment, i.e. a
+/// single [`HalfBinding`], over the `Vec<Rc<M>>` messages
+/// of [`QBinding`] so that callers can handle every
+/// `q >= 1` alike. Messages must have length 2.
+#[derive(Clone, Copy)]
+pub struct VecHalfBinding;
+
+impl VecHalfBinding {
+    /// Initialise the scheme together with the side bound
+    /// at the 0-indexed position `index`, like
+    /// [`QBinding::init`] with `q = 1`.
+    ///
+    /// Panics unless `index < 2`.
+    pub fn init(index: usize) -> (Self, Side) {
+        let side = match index {
+            0 => Side::One,
+            1 => Side::Two,
+            _ => panic!(
+                "binding index {} out of range for q = 1",
+                index
+            ),
+        };
+        (VecHalfBinding, side)
+    }
+}
+
+/// View a message of length 2 as a halfbinding message
+fn as_pair<M: ?Sized>(msg: &[Rc<M>]) -> (Rc<M>, Rc<M>) {
+    assert_eq!(
+        msg.len(),
+        2,
+        "a 1-of-2 commitment takes 2 messages"
+    );
+    (msg[0].clone(), msg[1].clone())
+}
+
+impl PartialBindingCommScheme for VecHalfBinding {
+    type PublicParams = halfbinding::PublicParams;
+    type BindingIndex = Side;
+    type CommitKey = halfbinding::CommitKey;
+    type EquivKey = halfbinding::EquivKey;
+    type Commitment = Commitment;
+    type Randomness = halfbinding::Randomness;
+    type Msg<'a, M: Message + 'a> = Vec<Rc<M>>;
+
+    fn setup<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+    ) -> Self::PublicParams {
+        HalfBinding.setup(rng)
+    }
+
+    fn gen<R: CryptoRngCore>(
+        &self,
+        pp: &Self::PublicParams,
+        binding_index: Side,
+        rng: &mut R,
+    ) -> (Self::CommitKey, Self::EquivKey) {
+        HalfBinding.gen(pp, binding_index, rng)
+    }
+
+    fn bind<'a, M: Message + ?Sized>(
+        &self,
+        pp: &Self::PublicParams,
+        ck: &Self::CommitKey,
+        msg: &Vec<Rc<M>>,
+        r: &Self::Randomness,
+    ) -> Commitment {
+        HalfBinding.bind(pp, ck, &as_pair(msg), r)
+    }
+
+    fn equiv<'a, M: Message + ?Sized>(
+        &self,
+        pp: &Self::PublicParams,
+        ek: &Self::EquivKey,
+        old: &Vec<Rc<M>>,
+        new: &Vec<Rc<M>>,
+        old_aux: &Self::Randomness,
+    ) -> Self::Randomness {
+        HalfBinding.equiv(
+            pp,
+            ek,
+            &as_pair(old),
+            &as_pair(new),
+            old_aux,
+        )
+    }
+
+    fn equivcom<'a, M: Message + ?Sized>(
+        &self,
+        pp: &Self::PublicParams,
+        ek: &Self::EquivKey,
+        msg: &Vec<Rc<M>>,
+        aux: Option<Self::Randomness>,
+    ) -> (Commitment, Self::Randomness) {
+        HalfBinding.equivcom(pp, ek, &as_pair(msg), aux)
     }
 }
 
@@ -598,7 +951,11 @@ mod tests {
             &mut rng2.clone(),
         );
         assert!(ck1 == ck2.extract(()));
-        assert!(ek1 == ek2.extract());
+        assert!(
+            ek1 == ek2
+                .extract()
+                .unwrap()
+        );
         let (comm_equivcom1, aux_old1) = qbinding1
             .equivcom(&pp1, &ek1, &msg1, Some(aux1));
         let (comm_equivcom2, aux_old2) = qbinding2
@@ -635,6 +992,146 @@ mod tests {
         assert_eq!(comm_equivcom2, comm_bind2);
     }
 
+    #[test]
+    fn test_try_new_checks_depth() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let qbinding =
+            QBinding::try_new(QBinding::MAX_Q).unwrap();
+        let pp = qbinding.setup(rng);
+        let binding_index =
+            BindingIndex::new(QBinding::MAX_Q, 5);
+        let (ck, _) = qbinding.gen(&pp, binding_index, rng);
+        assert_eq!(
+            ck.compose()
+                .as_vec()
+                .len(),
+            QBinding::MAX_Q
+        );
+
+        assert!(matches!(
+            QBinding::try_new(QBinding::MAX_Q + 1),
+            Err(Error::UnsupportedDepth)
+        ));
+        assert!(matches!(
+            QBinding::try_new(1),
+            Err(Error::UnsupportedDepth)
+        ));
+    }
+
+    #[test]
+    fn test_qbinding_clone_same_setup() {
+        let qbinding = QBinding::new(4);
+        // QBinding is Copy so the original remains usable
+        let copy = qbinding;
+        assert_eq!(copy.q, qbinding.q);
+        let pp1 = qbinding
+            .setup(&mut ChaCha20Rng::from_seed([0u8; 32]));
+        let pp2 = copy
+            .setup(&mut ChaCha20Rng::from_seed([0u8; 32]));
+        assert!(pp1 == pp2);
+    }
+
+    #[test]
+    fn test_try_bind_iter_checks_length() {
+        use curve25519_dalek::Scalar;
+
+        const Q: usize = 3;
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let qbinding = QBinding::new(Q);
+        let pp = qbinding.setup(rng);
+        let (ck, _) =
+            qbinding.gen(&pp, BindingIndex::new(Q, 5), rng);
+        let r = Randomness::random(rng, Q);
+        let msg = |n: usize| {
+            (0..n).map(|i| Rc::new(Scalar::from(i as u64)))
+        };
+
+        let msg_vec: Vec<_> = msg(1 << Q).collect();
+        assert_eq!(
+            qbinding.try_bind_iter(
+                &pp,
+                &ck,
+                msg(1 << Q),
+                &r
+            ),
+            Some(qbinding.bind(&pp, &ck, &msg_vec, &r))
+        );
+        for n in [0, 1, 7, 9, 16] {
+            assert!(qbinding
+                .try_bind_iter(&pp, &ck, msg(n), &r)
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_try_bind_decompresses_up_front() {
+        use std::cell::Cell;
+
+        use curve25519_dalek::ristretto::CompressedRistretto;
+
+        const Q: usize = 5;
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let qbinding = QBinding::new(Q);
+        let pp = qbinding.setup(rng);
+        let (ck, _) =
+            qbinding.gen(&pp, BindingIndex::new(Q, 3), rng);
+        let msg: Vec<_> = (0..1 << Q)
+            .map(|_| Rc::new("hello".as_bytes()))
+            .collect();
+        let r = Randomness::random(rng, Q);
+
+        let comm = qbinding
+            .try_bind(&pp, &ck, &msg, &r)
+            .unwrap();
+        assert_eq!(comm, qbinding.bind(&pp, &ck, &msg, &r));
+
+        // Every layer of the key is decompressed before the
+        // first halfbinding commitment, so an invalid outer
+        // key is rejected without consuming a message
+        let mut garbage = ck.clone();
+        garbage.outer_ck = halfbinding::CommitKey(
+            CompressedRistretto([0xff; 32]),
+        );
+        let consumed = Cell::new(0);
+        let counted = msg
+            .iter()
+            .inspect(|_| consumed.set(consumed.get() + 1))
+            .cloned();
+        assert!(qbinding
+            .try_bind_iter(&pp, &garbage, counted, &r)
+            .is_none());
+        assert_eq!(consumed.get(), 0);
+        assert!(qbinding
+            .try_bind(&pp, &garbage, &msg, &r)
+            .is_none());
+    }
+
+    #[test]
+    fn test_public_params_write_read() {
+        let qbinding = QBinding::new(4);
+        let pp1 = qbinding
+            .setup(&mut ChaCha20Rng::from_seed([0u8; 32]));
+        let pp2 = qbinding
+            .setup(&mut ChaCha20Rng::from_seed([0u8; 32]));
+        let other = qbinding
+            .setup(&mut ChaCha20Rng::from_seed([1u8; 32]));
+        assert_eq!(pp1, pp2);
+        assert_ne!(pp1, other);
+
+        let mut bytes = Vec::new();
+        pp1.write(&mut bytes);
+        assert_eq!(bytes.len(), 4 * 64);
+        assert_eq!(
+            PublicParams::read(&bytes).unwrap(),
+            pp1
+        );
+        assert!(PublicParams::read(
+            &bytes[..bytes.len() - 1]
+        )
+        .is_err());
+        assert!(PublicParams::read(&bytes[..64]).is_err());
+    }
+
     #[test]
     fn test_qbinding_recursive_works() {
         let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
@@ -674,6 +1171,121 @@ mod tests {
         assert_eq!(comm_equivcom, comm_bind);
     }
 
+    #[test]
+    fn test_qbinding_scalar_messages() {
+        use curve25519_dalek::Scalar;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+
+        const Q: usize = 3;
+        const B: usize = 5;
+        let (qbinding, binding_index) =
+            QBinding::init(Q, B);
+        // e.g. the nonces of the first round of Schnorr's
+        // protocol, one per clause
+        let msg: Vec<Rc<Scalar>> = (0..1 << Q)
+            .map(|_| Rc::new(Scalar::random(rng)))
+            .collect();
+        let mut msg_equiv = msg.clone();
+        msg_equiv[0] = Rc::new(Scalar::random(rng));
+
+        let aux = Randomness::random(rng, Q);
+        let pp = qbinding.setup(rng);
+        let (ck, ek) =
+            qbinding.gen(&pp, binding_index, rng);
+
+        let (comm_equivcom, aux_old) =
+            qbinding.equivcom(&pp, &ek, &msg, Some(aux));
+        let aux_new = qbinding
+            .equiv(&pp, &ek, &msg, &msg_equiv, &aux_old);
+        assert_eq!(
+            comm_equivcom,
+            qbinding.bind(&pp, &ck, &msg_equiv, &aux_new)
+        );
+
+        // The bound slot cannot be equivocated; in debug
+        // builds equiv itself catches it, see
+        // test_equiv_asserts_bound_msg_unchanged
+        if !cfg!(debug_assertions) {
+            msg_equiv[B] = Rc::new(Scalar::random(rng));
+            let aux_new = qbinding.equiv(
+                &pp, &ek, &msg, &msg_equiv, &aux_old,
+            );
+            assert_ne!(
+                comm_equivcom,
+                qbinding
+                    .bind(&pp, &ck, &msg_equiv, &aux_new)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(
+        expected = "the message at binding index 5 differs"
+    )]
+    fn test_equiv_asserts_bound_msg_unchanged() {
+        use curve25519_dalek::Scalar;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+
+        const Q: usize = 3;
+        const B: usize = 5;
+        let (qbinding, binding_index) =
+            QBinding::init(Q, B);
+        let msg: Vec<Rc<Scalar>> = (0..1 << Q)
+            .map(|_| Rc::new(Scalar::random(rng)))
+            .collect();
+        let mut msg_equiv = msg.clone();
+        msg_equiv[B] = Rc::new(Scalar::random(rng));
+
+        let pp = qbinding.setup(rng);
+        let (_, ek) = qbinding.gen(&pp, binding_index, rng);
+        let (_, aux_old) =
+            qbinding.equivcom(&pp, &ek, &msg, None);
+        qbinding
+            .equiv(&pp, &ek, &msg, &msg_equiv, &aux_old);
+    }
+
+    #[test]
+    fn test_vec_halfbinding_works() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let m1 = Rc::new("hello".as_bytes());
+        let m2 = Rc::new("world".as_bytes());
+        let none = Rc::new("".as_bytes());
+        let msg = vec![none, m2.clone()];
+        let msg_equiv = vec![m1, m2];
+
+        let (scheme, side) = VecHalfBinding::init(1);
+        let pp = scheme.setup(rng);
+        let (ck, ek) = scheme.gen(&pp, side, rng);
+
+        let (comm_equivcom, aux) =
+            scheme.equivcom(&pp, &ek, &msg, None);
+        let aux_new =
+            scheme.equiv(&pp, &ek, &msg, &msg_equiv, &aux);
+        let comm_bind =
+            scheme.bind(&pp, &ck, &msg_equiv, &aux_new);
+        assert_eq!(comm_equivcom, comm_bind);
+
+        // Same commitment as the halfbinding it wraps
+        assert_eq!(
+            comm_bind,
+            HalfBinding.bind(
+                &pp,
+                &ck,
+                &as_pair(&msg_equiv),
+                &aux_new
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_qbinding_rejects_q_one() {
+        QBinding::new(1);
+    }
+
     #[test]
     fn test_qbinding_base_works() {
         let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
@@ -795,4 +1407,162 @@ mod tests {
 
         assert_ne!(comm1_bind, comm2_bind);
     }
+    #[test]
+    fn test_qbinding_rejects_wrong_depth_randomness() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let msg: Vec<_> = (0..8)
+            .map(|_| Rc::new("hello".as_bytes()))
+            .collect();
+
+        const Q: usize = 3;
+        let (qbinding, binding_index) =
+            QBinding::init(Q, 5);
+        let pp = qbinding.setup(rng);
+        let (_, ek) = qbinding.gen(&pp, binding_index, rng);
+
+        for q in [1, 2, 4] {
+            let aux = Randomness::random(rng, q);
+            assert_eq!(aux.q(), q);
+            assert!(matches!(
+                qbinding.try_equivcom(
+                    &pp,
+                    &ek,
+                    &msg,
+                    Some(aux.clone())
+                ),
+                Err(Error::RandomnessDepthMismatch)
+            ));
+            assert!(matches!(
+                qbinding
+                    .try_equiv(&pp, &ek, &msg, &msg, &aux),
+                Err(Error::RandomnessDepthMismatch)
+            ));
+        }
+
+        let aux = Randomness::random(rng, Q);
+        assert!(qbinding
+            .try_equivcom(&pp, &ek, &msg, Some(aux))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_qbinding_open() {
+        use curve25519_dalek::Scalar;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let msg: Vec<_> = (0..8u64)
+            .map(|i| Rc::new(Scalar::from(i)))
+            .collect();
+
+        const Q: usize = 3;
+        let qbinding = QBinding::new(Q);
+        let pp = qbinding.setup(rng);
+        let (ck, _) =
+            qbinding.gen(&pp, BindingIndex::new(Q, 5), rng);
+        let aux = Randomness::random(rng, Q);
+        let comm = qbinding.bind(&pp, &ck, &msg, &aux);
+        assert!(qbinding.open(&pp, &ck, &comm, &msg, &aux));
+
+        let mut tampered = msg.clone();
+        tampered[2] = Rc::new(Scalar::from(100u64));
+        assert!(!qbinding
+            .open(&pp, &ck, &comm, &tampered, &aux));
+        assert!(!qbinding.open(
+            &pp,
+            &ck,
+            &comm,
+            &msg[..4],
+            &aux
+        ));
+        let other_aux = Randomness::random(rng, Q);
+        assert!(!qbinding
+            .open(&pp, &ck, &comm, &msg, &other_aux));
+    }
+
+    #[test]
+    fn test_qbinding_checks_msg_len() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let msg = |n: usize| -> Vec<_> {
+            (0..n)
+                .map(|_| Rc::new("hello".as_bytes()))
+                .collect()
+        };
+
+        const Q: usize = 3;
+        let (qbinding, binding_index) =
+            QBinding::init(Q, 5);
+        assert_eq!(qbinding.expected_msg_len(), 8);
+        let pp = qbinding.setup(rng);
+        let (ck, ek) =
+            qbinding.gen(&pp, binding_index, rng);
+
+        let (comm, aux) = qbinding
+            .try_equivcom(&pp, &ek, &msg(8), None)
+            .unwrap();
+        let new_aux = qbinding
+            .try_equiv(&pp, &ek, &msg(8), &msg(8), &aux)
+            .unwrap();
+        assert_eq!(
+            qbinding.try_bind(&pp, &ck, &msg(8), &new_aux),
+            Some(comm)
+        );
+
+        for n in [4, 7, 9, 16] {
+            let wrong_len = |result: Result<_, Error>| {
+                matches!(
+                    result,
+                    Err(Error::InvalidLength {
+                        expected: 8,
+                        got
+                    }) if got == n
+                )
+            };
+            assert!(wrong_len(
+                qbinding
+                    .try_equivcom(&pp, &ek, &msg(n), None)
+                    .map(|_| ())
+            ));
+            assert!(wrong_len(
+                qbinding
+                    .try_equiv(
+                        &pp,
+                        &ek,
+                        &msg(8),
+                        &msg(n),
+                        &aux
+                    )
+                    .map(|_| ())
+            ));
+            assert!(wrong_len(
+                qbinding
+                    .try_equiv(
+                        &pp,
+                        &ek,
+                        &msg(n),
+                        &msg(8),
+                        &aux
+                    )
+                    .map(|_| ())
+            ));
+            assert!(qbinding
+                .try_bind(&pp, &ck, &msg(n), &aux)
+                .is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "takes 8 messages")]
+    fn test_qbinding_bind_panics_on_msg_len() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let msg: Vec<_> = (0..4)
+            .map(|_| Rc::new("hello".as_bytes()))
+            .collect();
+
+        let (qbinding, binding_index) =
+            QBinding::init(3, 5);
+        let pp = qbinding.setup(rng);
+        let (ck, _) = qbinding.gen(&pp, binding_index, rng);
+        let aux = Randomness::random(rng, 3);
+        qbinding.bind(&pp, &ck, &msg, &aux);
+    }
 }
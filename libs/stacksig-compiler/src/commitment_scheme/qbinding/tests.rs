@@ -13,7 +13,9 @@ mod test_binding_index {
         assert_eq!(bi.length(), 16);
         assert_eq!(bi.index(), index);
         assert_eq!(bi.get_inner_raw(), index);
-        let inner_3 = bi.get_inner();
+        let inner_3 = bi
+            .get_inner()
+            .unwrap();
         assert_eq!(inner_3.q(), 3);
         assert_eq!(inner_3.length(), 8);
         assert_eq!(inner_3.index(), index);
@@ -21,7 +23,9 @@ mod test_binding_index {
         assert_eq!(inner_3.base_inner(), None);
         assert_eq!(bi.get_outer(), Side::One);
         assert_eq!(inner_3.get_outer(), Side::Two);
-        let inner_base = inner_3.get_inner();
+        let inner_base = inner_3
+            .get_inner()
+            .unwrap();
         assert_eq!(inner_base.q(), 2);
         assert_eq!(inner_base.length(), 4);
         assert_eq!(inner_base.index(), 1);
@@ -29,6 +33,23 @@ mod test_binding_index {
         assert_eq!(inner_base.base_inner(), Some(Side::Two));
         assert_eq!(inner_base.get_outer(), Side::One);
     }
+
+    #[test]
+    fn test_base_binding_index_has_no_inner() {
+        let base = BindingIndex::new(MIN_Q, 3);
+        assert!(matches!(
+            base.get_inner(),
+            Err(Error::BaseBindingIndex)
+        ));
+        assert!(matches!(
+            base.get_inner_outer(),
+            Err(Error::BaseBindingIndex)
+        ));
+        assert_eq!(
+            base.base_inner_outer(),
+            (Side::Two, Side::Two)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,468 @@
+//! Implementation of 1-of-n partial-binding vector
+//! commitment scheme from discrete log, committing to all
+//! n messages in a single Pedersen vector commitment
+
+use core::fmt;
+use std::io::Write;
+use std::rc::Rc;
+
+use curve25519_dalek::ristretto::{
+    CompressedRistretto, RistrettoPoint,
+};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::MultiscalarMul;
+use rand_core::CryptoRngCore;
+
+use crate::stackable::Message;
+use crate::util::hash;
+
+pub use crate::commitment_scheme::comm::PartialBindingCommScheme;
+
+/// 1 out of n commitment scheme, the generalisation of
+/// [`HalfBinding`](super::halfbinding::HalfBinding) to `n`
+/// messages per layer
+#[derive(Clone, Copy, Debug)]
+pub struct WideBinding {
+    n: usize,
+}
+
+impl WideBinding {
+    /// Panics unless `n >= 2`
+    pub fn new(n: usize) -> Self {
+        assert!(n >= 2, "a 1-of-n scheme needs n >= 2");
+        Self { n }
+    }
+
+    pub fn width(&self) -> usize {
+        self.n
+    }
+}
+
+/// Public parameters: `n` message generators `G_i` and the
+/// blinding generator `h`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicParamsN {
+    generators: Vec<RistrettoPoint>,
+    h: RistrettoPoint,
+}
+
+impl PublicParamsN {
+    pub fn width(&self) -> usize {
+        self.generators
+            .len()
+    }
+
+    fn generator_sum(&self) -> RistrettoPoint {
+        self.generators
+            .iter()
+            .sum()
+    }
+}
+
+/// The `n` generators `g_i` a commitment is computed with.
+///
+/// The generators of a well-formed key sum to the sum of the
+/// public parameters' `G_i`, so nobody can know the discrete
+/// log of all of them with respect to `h`; see
+/// [`CommitKeyN::is_valid`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct CommitKeyN(pub Vec<CompressedRistretto>);
+
+impl CommitKeyN {
+    /// Decompress the generators. Returns `None` if one of
+    /// them is not a valid point.
+    pub fn decompress(
+        &self,
+    ) -> Option<Vec<RistrettoPoint>> {
+        self.0
+            .iter()
+            .map(CompressedRistretto::decompress)
+            .collect()
+    }
+
+    /// Whether the key has one valid generator per message
+    /// and they sum to the sum of the `G_i` of `pp`. A key
+    /// received from an untrusted party must be checked
+    /// before committing with it, as otherwise every index
+    /// may be equivocable.
+    pub fn is_valid(&self, pp: &PublicParamsN) -> bool {
+        match self.decompress() {
+            Some(gs) => {
+                gs.len() == pp.width()
+                    && gs
+                        .iter()
+                        .sum::<RistrettoPoint>()
+                        == pp.generator_sum()
+            }
+            None => false,
+        }
+    }
+}
+
+impl Message for CommitKeyN {
+    fn write<W: Write>(&self, writer: &mut W) {
+        for g in &self.0 {
+            writer
+                .write_all(g.as_bytes())
+                .unwrap();
+        }
+    }
+}
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, Hash, Default,
+)]
+pub struct CommitmentN(pub [u8; 32]);
+
+impl Message for CommitmentN {
+    fn write<W: Write>(&self, writer: &mut W) {
+        writer
+            .write_all(&self.0)
+            .unwrap();
+    }
+}
+
+/// Equivocation key of the 1-of-n scheme. Holds the
+/// trapdoor `t_i = log_h g_i` of every index but the binding
+/// one, which are redacted from the `Debug` output.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct EquivKeyN {
+    binding_index: usize,
+    trapdoors: Vec<Option<Scalar>>,
+    commit_key: CommitKeyN,
+}
+
+impl EquivKeyN {
+    pub fn binding_index(&self) -> usize {
+        self.binding_index
+    }
+
+    /// Commit key generated together with this equivocation
+    /// key
+    pub fn commit_key(&self) -> &CommitKeyN {
+        &self.commit_key
+    }
+}
+
+impl fmt::Debug for EquivKeyN {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("EquivKeyN")
+            .field("binding_index", &self.binding_index)
+            .field("trapdoors", &"<redacted>")
+            .field("commit_key", &self.commit_key)
+            .finish()
+    }
+}
+
+impl WideBinding {
+    fn commitment<M: Message>(
+        pp: &PublicParamsN,
+        generators: &[RistrettoPoint],
+        msg: &[Rc<M>],
+        randomness: &Scalar,
+    ) -> CommitmentN {
+        let scalars = msg
+            .iter()
+            .map(|m| hash(m.as_ref()))
+            .chain([*randomness]);
+        let points = generators
+            .iter()
+            .chain([&pp.h]);
+        CommitmentN(
+            RistrettoPoint::multiscalar_mul(
+                scalars, points,
+            )
+            .compress()
+            .to_bytes(),
+        )
+    }
+
+    /// Commit to `n` messages with `ck`. Returns `None`
+    /// instead of panicking if `msg` does not have `n`
+    /// messages or a generator of `ck` is not a valid
+    /// point, e.g. for a key from an untrusted party.
+    pub fn try_bind<M: Message>(
+        &self,
+        pp: &PublicParamsN,
+        ck: &CommitKeyN,
+        msg: &[Rc<M>],
+        randomness: &Scalar,
+    ) -> Option<CommitmentN> {
+        let generators = ck.decompress()?;
+        if msg.len() != self.n || generators.len() != self.n
+        {
+            return None;
+        }
+        Some(Self::commitment(
+            pp,
+            &generators,
+            msg,
+            randomness,
+        ))
+    }
+
+    /// Commit with the commit key of the equivocation key,
+    /// drawing the randomness from `rng`
+    pub fn equivcom_with_rng<
+        M: Message,
+        R: CryptoRngCore,
+    >(
+        &self,
+        pp: &PublicParamsN,
+        ek: &EquivKeyN,
+        msg: &Vec<Rc<M>>,
+        rng: &mut R,
+    ) -> (CommitmentN, Scalar) {
+        self.equivcom(
+            pp,
+            ek,
+            msg,
+            Some(Scalar::random(rng)),
+        )
+    }
+}
+
+/// Implementation of 1-of-n partially-binding vector
+/// commitment scheme
+impl PartialBindingCommScheme for WideBinding {
+    type PublicParams = PublicParamsN;
+    type BindingIndex = usize;
+    type CommitKey = CommitKeyN;
+    type EquivKey = EquivKeyN;
+    type Commitment = CommitmentN;
+    type Randomness = Scalar;
+    type Msg<'a, M: Message + 'a> = Vec<Rc<M>>;
+
+    /// Generate public parameters for `n` messages
+    fn setup<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+    ) -> PublicParamsN {
+        PublicParamsN {
+            generators: (0..self.n)
+                .map(|_| RistrettoPoint::random(rng))
+                .collect(),
+            h: RistrettoPoint::random(rng),
+        }
+    }
+
+    /// Generate a commitment key and equivocation key pair.
+    ///
+    /// Every index but `binding_index` gets a generator
+    /// `g_i = t_i·h` with a fresh trapdoor `t_i`. The
+    /// generator of the binding index is fixed by
+    /// `Σ g_i = Σ G_i`, so its discrete log is unknown.
+    fn gen<R: CryptoRngCore>(
+        &self,
+        pp: &PublicParamsN,
+        binding_index: usize,
+        rng: &mut R,
+    ) -> (CommitKeyN, EquivKeyN) {
+        assert_eq!(pp.width(), self.n);
+        assert!(binding_index < self.n);
+
+        let trapdoors: Vec<Option<Scalar>> = (0..self.n)
+            .map(|i| {
+                (i != binding_index)
+                    .then(|| Scalar::random(rng))
+            })
+            .collect();
+        let mut generators: Vec<RistrettoPoint> = trapdoors
+            .iter()
+            .map(|t| match t {
+                Some(t) => pp.h * t,
+                None => RistrettoPoint::default(),
+            })
+            .collect();
+        generators[binding_index] = pp.generator_sum()
+            - generators
+                .iter()
+                .sum::<RistrettoPoint>();
+
+        let commit_key = CommitKeyN(
+            generators
+                .iter()
+                .map(RistrettoPoint::compress)
+                .collect(),
+        );
+        (
+            commit_key.clone(),
+            EquivKeyN {
+                binding_index,
+                trapdoors,
+                commit_key,
+            },
+        )
+    }
+
+    /// Commit to `n` messages as
+    /// `Σ hash(m_i)·g_i + r·h`
+    fn bind<M: Message>(
+        &self,
+        pp: &PublicParamsN,
+        ck: &CommitKeyN,
+        msg: &Vec<Rc<M>>,
+        randomness: &Scalar,
+    ) -> CommitmentN {
+        self.try_bind(pp, ck, msg, randomness)
+            .expect(
+                "message length or commit key is invalid",
+            )
+    }
+
+    /// Commit with the commit key of the equivocation key.
+    ///
+    /// Panics if no randomness is given, as the scheme does
+    /// not draw entropy itself; see
+    /// [`WideBinding::equivcom_with_rng`].
+    fn equivcom<M: Message>(
+        &self,
+        pp: &PublicParamsN,
+        ek: &EquivKeyN,
+        msg: &Vec<Rc<M>>,
+        randomness: Option<Scalar>,
+    ) -> (CommitmentN, Scalar) {
+        let rand = randomness.expect(
+            "equivcom needs randomness, use equivcom_with_rng",
+        );
+        (self.bind(pp, ek.commit_key(), msg, &rand), rand)
+    }
+
+    /// Equivocates the messages at every index but the
+    /// binding one, whose message must not change.
+    ///
+    /// ## Returns
+    /// The randomness `r - Σ t_i·(hash(new_i) - hash(old_i))`
+    /// opening the commitment to `new`
+    fn equiv<M: Message>(
+        &self,
+        _pp: &PublicParamsN,
+        ek: &EquivKeyN,
+        old: &Vec<Rc<M>>,
+        new: &Vec<Rc<M>>,
+        old_aux: &Scalar,
+    ) -> Scalar {
+        assert_eq!(old.len(), self.n);
+        assert_eq!(new.len(), self.n);
+        ek.trapdoors
+            .iter()
+            .zip(old.iter())
+            .zip(new.iter())
+            .fold(*old_aux, |r, ((t, old), new)| match t {
+                Some(t) => {
+                    r - t
+                        * (hash(new.as_ref())
+                            - hash(old.as_ref()))
+                }
+                None => r,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+
+    macro_rules! messages {
+        ($($m:expr),*) => {
+            vec![$(Rc::new($m.as_bytes())),*]
+        };
+    }
+
+    #[test]
+    fn test_wide_binding_works() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let scheme = WideBinding::new(4);
+        let pp = scheme.setup(rng);
+        let (ck, ek) = scheme.gen(&pp, 2, rng);
+        assert!(ck.is_valid(&pp));
+
+        let m = messages!["", "", "bound", ""];
+        let m_equiv = messages!["a", "b", "bound", "d"];
+        let (comm, aux) =
+            scheme.equivcom_with_rng(&pp, &ek, &m, rng);
+        let aux_new =
+            scheme.equiv(&pp, &ek, &m, &m_equiv, &aux);
+        assert_eq!(
+            comm,
+            scheme.bind(&pp, &ck, &m_equiv, &aux_new)
+        );
+    }
+
+    #[test]
+    fn test_wide_binding_binds_on_bound_index() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let scheme = WideBinding::new(4);
+        let pp = scheme.setup(rng);
+        let (ck, ek) = scheme.gen(&pp, 2, rng);
+
+        let m = messages!["", "", "bound", ""];
+        let m_bound = messages!["", "", "changed", ""];
+        let (comm, aux) =
+            scheme.equivcom_with_rng(&pp, &ek, &m, rng);
+        let aux_new =
+            scheme.equiv(&pp, &ek, &m, &m_bound, &aux);
+        assert_ne!(
+            comm,
+            scheme.bind(&pp, &ck, &m_bound, &aux_new)
+        );
+    }
+
+    #[test]
+    fn test_commit_key_validity() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let scheme = WideBinding::new(4);
+        let pp = scheme.setup(rng);
+        let (mut ck, _) = scheme.gen(&pp, 0, rng);
+        assert!(ck.is_valid(&pp));
+
+        // A key where every generator has a known discrete
+        // log does not sum to the public generators
+        ck.0[0] = (pp.h * Scalar::random(rng)).compress();
+        assert!(!ck.is_valid(&pp));
+        ck.0.pop();
+        assert!(!ck.is_valid(&pp));
+    }
+
+    #[test]
+    fn test_equivkey_debug_redacts_trapdoors() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let scheme = WideBinding::new(3);
+        let pp = scheme.setup(rng);
+        let (_, ek) = scheme.gen(&pp, 1, rng);
+        let debug = format!("{:?}", ek);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!(
+            "{:?}",
+            ek.trapdoors[0].unwrap()
+        )));
+    }
+
+    #[test]
+    fn test_try_bind_rejects_invalid_input() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let scheme = WideBinding::new(3);
+        let pp = scheme.setup(rng);
+        let (mut ck, _) = scheme.gen(&pp, 0, rng);
+        let m = messages!["a", "b", "c"];
+        let r = Scalar::random(rng);
+        assert_eq!(
+            scheme.try_bind(&pp, &ck, &m, &r),
+            Some(scheme.bind(&pp, &ck, &m, &r))
+        );
+
+        assert!(scheme
+            .try_bind(&pp, &ck, &m[..2], &r)
+            .is_none());
+        ck.0[1] = CompressedRistretto([0xff; 32]);
+        assert!(scheme
+            .try_bind(&pp, &ck, &m, &r)
+            .is_none());
+    }
+}
@@ -261,4 +261,11 @@ impl<S: Stackable> Stackable for Compiled<S> {
         );
         z.ck.commit(&z.rd, (Some(&left), Some(&right)))
     }
+
+    fn is_valid_statement(
+        statement: &Self::Statement,
+    ) -> bool {
+        S::is_valid_statement(statement.left())
+            && S::is_valid_statement(statement.right())
+    }
 }
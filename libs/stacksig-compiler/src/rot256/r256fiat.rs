@@ -10,6 +10,7 @@ use std::io::Write;
 use std::marker::PhantomData;
 
 use rand_core::{CryptoRng, RngCore};
+use sigmazk::error::Error;
 
 use super::r256stack::{Challenge, Message, Stackable};
 
@@ -45,7 +46,12 @@ impl<S: Stackable> SignatureScheme<S> {
         sk: &S::Witness,
         pk: &S::Statement,
         msg: &[u8],
-    ) -> Signature<S> {
+    ) -> Result<Signature<S>, Error> {
+        // refuse to sign for a meaningless public key
+        if !S::is_valid_statement(pk) {
+            return Err(Error::InvalidStatement);
+        }
+
         // create first message
         let (st, a) = S::sigma_a(rng, sk);
 
@@ -68,6 +74,69 @@ impl<S: Stackable> SignatureScheme<S> {
 
         // finish last message
         let (_, z) = S::sigma_z(pk, sk, &st, &challenge);
-        Signature { a, z }
+        Ok(Signature { a, z })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::scalar::Scalar;
+    use curve25519_dalek::traits::Identity;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    use super::*;
+    use crate::rot256::r256compiler::{
+        Compiled, CompiledStatement, CompiledWitness,
+    };
+    use crate::rot256::r256schnorr::Schnorr;
+    use crate::rot256::Side;
+
+    type S2 = Compiled<Schnorr>;
+
+    #[test]
+    fn test_sign_rejects_identity_public_key() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let sk = Scalar::random(rng);
+        let pk = &sk * RISTRETTO_BASEPOINT_TABLE;
+        assert!(SignatureScheme::<Schnorr>::sign(
+            rng, &sk, &pk, b"msg"
+        )
+        .is_ok());
+        assert!(matches!(
+            SignatureScheme::<Schnorr>::sign(
+                rng,
+                &sk,
+                &RistrettoPoint::identity(),
+                b"msg"
+            ),
+            Err(Error::InvalidStatement)
+        ));
+
+        // An identity key in any clause of a disjunction
+        let other = &Scalar::random(rng)
+            * RISTRETTO_BASEPOINT_TABLE;
+        let sk = CompiledWitness::new(sk, Side::Left);
+        assert!(SignatureScheme::<S2>::sign(
+            rng,
+            &sk,
+            &CompiledStatement::new(pk, other),
+            b"msg"
+        )
+        .is_ok());
+        assert!(matches!(
+            SignatureScheme::<S2>::sign(
+                rng,
+                &sk,
+                &CompiledStatement::new(
+                    pk,
+                    RistrettoPoint::identity()
+                ),
+                b"msg"
+            ),
+            Err(Error::InvalidStatement)
+        ));
     }
 }
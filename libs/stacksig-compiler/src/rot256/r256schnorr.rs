@@ -10,6 +10,7 @@ use curve25519_dalek::ristretto::{
     CompressedRistretto, RistrettoPoint,
 };
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
 
 #[derive(Debug)]
 pub struct Schnorr();
@@ -80,4 +81,10 @@ impl Stackable for Schnorr {
         (precomp.0 + precomp.1 * statement).compress()
         // (z * &RISTRETTO_BASEPOINT_TABLE + -challenge * statement).compress()
     }
+
+    fn is_valid_statement(
+        statement: &Self::Statement,
+    ) -> bool {
+        !statement.is_identity()
+    }
 }
@@ -64,4 +64,12 @@ pub trait Stackable {
         challenge: &Self::Challenge,
         z: &Self::MessageZ,
     ) -> Self::MessageA;
+
+    // whether proofs about the statement are meaningful,
+    // e.g. no public key is the identity
+    fn is_valid_statement(
+        _statement: &Self::Statement,
+    ) -> bool {
+        true
+    }
 }
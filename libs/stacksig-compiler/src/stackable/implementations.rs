@@ -1,36 +1,47 @@
-use std::io::Write;
-
-use curve25519_dalek::ristretto::CompressedRistretto;
-use curve25519_dalek::Scalar;
 use sigmazk::Schnorr;
 
-use super::{Message, Stackable};
-
-impl Stackable for Schnorr {}
+use super::Stackable;
 
-impl Message for CompressedRistretto {
-    fn write<W: Write>(&self, writer: &mut W) {
-        writer
-            .write_all(self.as_bytes())
-            .unwrap();
+impl Stackable for Schnorr {
+    fn is_valid_statement(statement: &Schnorr) -> bool {
+        statement.is_valid_statement()
     }
 }
 
-impl Message for Scalar {
-    fn write<W: Write>(&self, writer: &mut W) {
-        writer
-            .write_all(self.as_bytes())
-            .unwrap();
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+    use sigmazk::error::Error;
+
+    use crate::stackable::{Message, ReadMessage};
+
+    #[test]
+    fn test_write_scalar() {
+        let mut buf = Vec::new();
+        let scalar = Scalar::from(1u64);
+        scalar.write(&mut buf);
+        assert_eq!(scalar.size(), 32);
+        let mut expected = vec![0u8; 32];
+        expected[0] = 1;
+        assert_eq!(buf, expected);
     }
-}
 
-#[test]
-fn test_write_scalar() {
-    let mut buf = Vec::new();
-    let scalar = Scalar::from(1u64);
-    scalar.write(&mut buf);
-    assert_eq!(scalar.size(), 32);
-    let mut expected = vec![0u8; 32];
-    expected[0] = 1;
-    assert_eq!(buf, expected);
+    #[test]
+    fn test_read_scalar() {
+        let scalar = Scalar::from(1u64);
+        let mut buf = Vec::new();
+        scalar.write(&mut buf);
+        assert_eq!(Scalar::read(&buf).unwrap(), scalar);
+        assert!(matches!(
+            Scalar::read(&buf[..31]),
+            Err(Error::InvalidLength {
+                expected: 32,
+                got: 31
+            })
+        ));
+        assert!(matches!(
+            Scalar::read(&[0xffu8; 32]),
+            Err(Error::InvalidScalar)
+        ));
+    }
 }
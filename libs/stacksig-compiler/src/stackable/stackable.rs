@@ -1,29 +1,7 @@
-use std::fmt::Debug;
-use std::io::Write;
-
 use rand_core::CryptoRngCore;
+pub use sigmazk::message::{Message, ReadMessage};
 use sigmazk::{EHVzk, SigmaProtocol};
 
-pub trait Message: Debug + Default + Clone {
-    fn write<W: Write>(&self, writer: &mut W)
-    where
-        Self: Sized;
-
-    fn size(&self) -> usize {
-        let mut v: Vec<u8> = Vec::new();
-        self.write(&mut v);
-        v.len()
-    }
-}
-
-impl Message for &[u8] {
-    fn write<W: Write>(&self, writer: &mut W) {
-        writer
-            .write_all(self)
-            .unwrap();
-    }
-}
-
 pub trait Randomizable {
     fn randomize<R: CryptoRngCore>(&mut self, rng: &mut R);
 }
@@ -33,4 +11,12 @@ pub trait Stackable:
     + EHVzk
     + Clone
 {
+    /// Whether proofs about `statement` are meaningful, e.g.
+    /// a public key is not the identity. Signing and
+    /// non-interactive proving reject invalid statements.
+    fn is_valid_statement(
+        _statement: &Self::Statement,
+    ) -> bool {
+        true
+    }
 }
@@ -1,4 +1,6 @@
+pub mod report;
 pub mod selfstacker;
 #[cfg(test)]
 mod tests;
+pub use report::*;
 pub use selfstacker::*;
@@ -0,0 +1,235 @@
+//! Level-by-level verification reports for nested stacks
+//!
+//! [`SigmaProtocol::verify`] of a stack such as
+//! `SelfStacker<SelfStacker<Schnorr>>` only says whether
+//! the proof is accepted. [`VerifyReport::verify_tree`]
+//! checks the same conditions but records the outcome of
+//! every level, so that a broken proof can be traced to the
+//! level it was broken at.
+
+use std::rc::Rc;
+
+use sigmazk::{EHVzk, Schnorr, SigmaProtocol};
+
+use super::selfstacker::*;
+use crate::stackable::Stackable;
+
+/// Outcome of verifying a proof, one node per level of
+/// stacking
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyTree {
+    /// [`SigmaProtocol::verify`] of the base protocol, over
+    /// every clause
+    Base(bool),
+    /// One level of [`SelfStacker`]
+    Stack {
+        /// Whether the commitment key of the response is the
+        /// one of the first message, the randomness has the
+        /// depth of the stack and the first message commits
+        /// to the first messages simulated for every clause
+        commitment_ok: bool,
+        /// Report of the level below, over every clause
+        nested_report: Box<VerifyTree>,
+    },
+}
+
+impl VerifyTree {
+    /// Whether every level verified, i.e. the proof is
+    /// accepted by [`SigmaProtocol::verify`]
+    pub fn is_ok(&self) -> bool {
+        match self {
+            VerifyTree::Base(ok) => *ok,
+            VerifyTree::Stack {
+                commitment_ok,
+                nested_report,
+            } => *commitment_ok && nested_report.is_ok(),
+        }
+    }
+
+    /// Depth, counted from 0 at the outermost stack, of the
+    /// innermost level that failed, or `None` if every level
+    /// verified.
+    ///
+    /// The commitment of a level is to the first messages
+    /// simulated at the level below, so a broken level also
+    /// fails every level above it. The innermost failure is
+    /// where the proof was broken.
+    pub fn failed_depth(&self) -> Option<usize> {
+        match self {
+            VerifyTree::Base(ok) => (!ok).then_some(0),
+            VerifyTree::Stack {
+                commitment_ok,
+                nested_report,
+            } => match nested_report.failed_depth() {
+                Some(depth) => Some(depth + 1),
+                None => (!commitment_ok).then_some(0),
+            },
+        }
+    }
+
+    /// Report for two clauses of the same level, failing at
+    /// every level either of them fails at
+    fn and(self, other: VerifyTree) -> VerifyTree {
+        match (self, other) {
+            (VerifyTree::Base(a), VerifyTree::Base(b)) => {
+                VerifyTree::Base(a && b)
+            }
+            (
+                VerifyTree::Stack {
+                    commitment_ok: a,
+                    nested_report: nested_a,
+                },
+                VerifyTree::Stack {
+                    commitment_ok: b,
+                    nested_report: nested_b,
+                },
+            ) => VerifyTree::Stack {
+                commitment_ok: a && b,
+                nested_report: Box::new(
+                    nested_a.and(*nested_b),
+                ),
+            },
+            // Clauses of one level are of the same protocol
+            (a, b) => {
+                VerifyTree::Base(a.is_ok() && b.is_ok())
+            }
+        }
+    }
+}
+
+/// Sigma protocols that can report which level of a stack
+/// a proof failed at
+pub trait VerifyReport: Stackable {
+    /// [`SigmaProtocol::verify`], recording the outcome of
+    /// every level
+    fn verify_tree(
+        statement: &Self::Statement,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> VerifyTree;
+
+    /// [`EHVzk::simulate`], or `None` if `z` is malformed
+    /// and no first message can be simulated for it
+    fn try_simulate(
+        statement: &Self::Statement,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> Option<Self::MessageA>;
+}
+
+impl VerifyReport for Schnorr {
+    fn verify_tree(
+        statement: &Schnorr,
+        a: &Self::MessageA,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> VerifyTree {
+        VerifyTree::Base(Schnorr::verify(
+            statement, a, c, z,
+        ))
+    }
+
+    fn try_simulate(
+        statement: &Schnorr,
+        c: &Self::Challenge,
+        z: &Self::MessageZ,
+    ) -> Option<Self::MessageA> {
+        Some(<Schnorr as EHVzk>::simulate(statement, c, z))
+    }
+}
+
+impl<S: VerifyReport> VerifyReport for SelfStacker<S> {
+    fn verify_tree(
+        statement: &StackedStatement<S>,
+        a: &StackedA,
+        c: &S::Challenge,
+        z: &StackedZ<S>,
+    ) -> VerifyTree {
+        let simulated: Vec<Option<S::MessageA>> = statement
+            .statements()
+            .iter()
+            .map(|s| S::try_simulate(s, c, z.message()))
+            .collect();
+
+        // A clause whose first message cannot be simulated
+        // is reported against the default one, which fails
+        // where the nested response is malformed
+        let default = S::MessageA::default();
+        let nested_report = statement
+            .statements()
+            .iter()
+            .zip(&simulated)
+            .map(|(s, m)| {
+                S::verify_tree(
+                    s,
+                    m.as_ref()
+                        .unwrap_or(&default),
+                    c,
+                    z.message(),
+                )
+            })
+            .reduce(VerifyTree::and)
+            // A statement without clauses proves nothing
+            .unwrap_or(VerifyTree::Base(false));
+
+        let commitment_ok = a.ck() == z.ck()
+            && z.aux()
+                .q()
+                == statement.height()
+            && simulated
+                .into_iter()
+                .map(|m| m.map(Rc::new))
+                .collect::<Option<Vec<_>>>()
+                .and_then(|v| {
+                    QBinding::new(statement.height())
+                        .try_bind(
+                            statement.pp(),
+                            a.ck(),
+                            &v,
+                            z.aux(),
+                        )
+                })
+                .is_some_and(|comm| {
+                    comm == *a.commitment()
+                });
+
+        VerifyTree::Stack {
+            commitment_ok,
+            nested_report: Box::new(nested_report),
+        }
+    }
+
+    fn try_simulate(
+        statement: &StackedStatement<S>,
+        c: &S::Challenge,
+        z: &StackedZ<S>,
+    ) -> Option<StackedA> {
+        if z.aux()
+            .q()
+            != statement.height()
+        {
+            return None;
+        }
+        let v = statement
+            .statements()
+            .iter()
+            .map(|s| {
+                S::try_simulate(s, c, z.message())
+                    .map(Rc::new)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let comm = QBinding::new(statement.height())
+            .try_bind(
+                statement.pp(),
+                z.ck(),
+                &v,
+                z.aux(),
+            )?;
+        Some(StackedA::new(
+            z.ck()
+                .clone(),
+            comm,
+        ))
+    }
+}
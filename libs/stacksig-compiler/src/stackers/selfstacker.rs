@@ -1,22 +1,56 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use rand::SeedableRng;
-use rand_chacha::ChaCha20Rng;
+use digest::Digest;
 use rand_core::CryptoRngCore;
-use sigmazk::{Challenge, EHVzk, SigmaProtocol};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use sha2::{Sha256, Sha512};
+use sigmazk::error::Error;
+#[cfg(feature = "parallel")]
+use sigmazk::ThreadSafeProtocol;
+use sigmazk::{
+    Challenge, ContextualSigmaProtocol, EHVzk,
+    SigmaProtocol, StatementDigest,
+};
 
 use crate::commitment_scheme::halfbinding::Commitment;
 pub use crate::commitment_scheme::qbinding::*;
-use crate::stackable::{Message, Stackable};
+use crate::stackable::{Message, ReadMessage, Stackable};
+use crate::util::{
+    challenge_from_hash, read_framed, write_framed,
+};
 
+/// Deserializing checks the same invariants as
+/// [`StackedStatement::new`] and
+/// [`StackedStatement::with_labels`], failing instead of
+/// panicking
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "StackedStatementFields<S>",
+        bound(
+            serialize = "S::Statement: serde::Serialize",
+            deserialize = "S::Statement: \
+                           serde::Deserialize<'de>"
+        )
+    )
+)]
 pub struct StackedStatement<S: Stackable> {
     pp: PublicParams,
     height: usize,
     clauses: usize,
-    statements: Vec<S::Statement>,
+    /// Shared so that cloning a statement does not copy
+    /// its clauses
+    statements: Arc<[S::Statement]>,
+    /// Application labels of the clauses, empty if none
+    /// were attached. They are not bound into proofs.
+    labels: Arc<[Box<[u8]>]>,
 }
 
 impl<S: Stackable> fmt::Display for StackedStatement<S> {
@@ -32,6 +66,67 @@ impl<S: Stackable> fmt::Display for StackedStatement<S> {
     }
 }
 
+/// Unchecked fields of a deserialized [`StackedStatement`]
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(
+    deserialize = "S::Statement: serde::Deserialize<'de>"
+))]
+struct StackedStatementFields<S: Stackable> {
+    pp: PublicParams,
+    height: usize,
+    clauses: usize,
+    statements: Arc<[S::Statement]>,
+    labels: Arc<[Box<[u8]>]>,
+}
+
+#[cfg(feature = "serde")]
+impl<S: Stackable> TryFrom<StackedStatementFields<S>>
+    for StackedStatement<S>
+{
+    type Error = &'static str;
+
+    fn try_from(
+        fields: StackedStatementFields<S>,
+    ) -> Result<Self, Self::Error> {
+        if fields.height >= usize::BITS as usize
+            || fields.clauses != 1 << fields.height
+        {
+            return Err("clauses must be 2^height");
+        }
+        if fields
+            .statements
+            .len()
+            != fields.clauses
+        {
+            return Err(
+                "one statement per clause is required",
+            );
+        }
+        if !fields
+            .labels
+            .is_empty()
+            && fields
+                .labels
+                .len()
+                != fields
+                    .statements
+                    .len()
+        {
+            return Err(
+                "one label per statement is required",
+            );
+        }
+        Ok(Self {
+            pp: fields.pp,
+            height: fields.height,
+            clauses: fields.clauses,
+            statements: fields.statements,
+            labels: fields.labels,
+        })
+    }
+}
+
 impl<S: Stackable> StackedStatement<S> {
     /// Creates a new stacked statement from a list of
     /// statements.
@@ -45,17 +140,41 @@ impl<S: Stackable> StackedStatement<S> {
     /// scheme tree. This is also the `q` in 1-out-of-2^q.
     ///
     /// `statements`: A list of statements to be stacked.
+    ///
+    /// Panics unless there are `2^height` statements.
     pub fn new(
         pp: PublicParams,
         height: usize,
         statements: Vec<S::Statement>,
     ) -> Self {
+        assert_eq!(
+            statements.len(),
+            1 << height,
+            "one statement per clause is required"
+        );
         StackedStatement {
             pp,
             height,
             clauses: 1 << height,
-            statements,
+            statements: statements.into(),
+            labels: Arc::new([]),
+        }
+    }
+
+    /// Like [`StackedStatement::new`], but fails with
+    /// [`Error::StatementLengthMismatch`] instead of
+    /// panicking
+    pub fn try_new(
+        pp: PublicParams,
+        height: usize,
+        statements: Vec<S::Statement>,
+    ) -> Result<Self, Error> {
+        if height >= usize::BITS as usize
+            || statements.len() != 1 << height
+        {
+            return Err(Error::StatementLengthMismatch);
         }
+        Ok(Self::new(pp, height, statements))
     }
 
     pub fn pp(&self) -> &PublicParams {
@@ -70,10 +189,45 @@ impl<S: Stackable> StackedStatement<S> {
         self.height
     }
 
-    pub fn statements(&self) -> &Vec<S::Statement> {
+    pub fn statements(&self) -> &[S::Statement] {
         &self.statements
     }
 
+    /// Whether every clause is a valid statement, see
+    /// [`Stackable::is_valid_statement`]
+    pub fn is_valid(&self) -> bool {
+        self.statements
+            .iter()
+            .all(S::is_valid_statement)
+    }
+
+    /// Attach a label to every clause, e.g. a party name or
+    /// public key fingerprint, for application bookkeeping.
+    /// Labels are not bound into proofs.
+    ///
+    /// Panics unless there is one label per statement.
+    pub fn with_labels(
+        mut self,
+        labels: Vec<Box<[u8]>>,
+    ) -> Self {
+        assert_eq!(
+            labels.len(),
+            self.statements
+                .len(),
+            "one label per statement is required"
+        );
+        self.labels = labels.into();
+        self
+    }
+
+    /// Label of the clause at `index`, or `None` if no labels
+    /// were attached or `index` is out of range
+    pub fn label_at(&self, index: usize) -> Option<&[u8]> {
+        self.labels
+            .get(index)
+            .map(AsRef::as_ref)
+    }
+
     pub fn bound_statement(
         &self,
         binding: &BindingIndex,
@@ -88,14 +242,100 @@ impl<S: Stackable> StackedStatement<S> {
         self.statements
             .get(index)
     }
+
+    /// Precompute the data that can be reused when
+    /// verifying many proofs against this statement
+    pub fn precompute(&self) -> VerifyPrecompute<'_, S> {
+        VerifyPrecompute {
+            statement: self,
+            binding: QBinding::new(self.height),
+        }
+    }
+}
+
+impl<S: Stackable> StatementDigest for StackedStatement<S>
+where
+    S::Statement: StatementDigest,
+{
+    fn digest(&self) -> [u8; 32] {
+        let mut hash = Sha256::new();
+        hash.update(b"stacksig-compiler stacked statement");
+        self.pp
+            .write(&mut hash);
+        hash.update((self.height as u64).to_le_bytes());
+        for statement in self
+            .statements
+            .iter()
+        {
+            hash.update(statement.digest());
+        }
+        hash.finalize()
+            .into()
+    }
+}
+
+/// Verification data cached for a fixed
+/// [`StackedStatement`]. See
+/// [`SelfStacker::verify_with`].
+#[derive(Clone, Copy)]
+pub struct VerifyPrecompute<'a, S: Stackable> {
+    statement: &'a StackedStatement<S>,
+    binding: QBinding,
+}
+
+impl<'a, S: Stackable> VerifyPrecompute<'a, S> {
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn statement(&self) -> &'a StackedStatement<S> {
+        self.statement
+    }
+
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn binding(&self) -> &QBinding {
+        &self.binding
+    }
+}
+
+impl<S: Stackable> StackedStatement<S>
+where
+    S::Statement: Message,
+{
+    /// Write the public parameters, the height and every
+    /// clause statement so that the stacked statement can
+    /// be bound to a transcript.
+    pub fn write<W: Write>(&self, writer: &mut W) {
+        self.pp
+            .write(writer);
+        writer
+            .write_all(&(self.height as u64).to_le_bytes())
+            .unwrap();
+        for statement in self
+            .statements
+            .iter()
+        {
+            statement.write(writer);
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct StackedWitness<W> {
     nested_witness: W,
     binding: BindingIndex,
 }
 
+/// The nested witness is redacted
+impl<W> fmt::Debug for StackedWitness<W> {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.debug_struct("StackedWitness")
+            .field("nested_witness", &"<redacted>")
+            .field("binding", &self.binding)
+            .finish()
+    }
+}
+
 impl<W: fmt::Display> fmt::Display for StackedWitness<W> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -119,9 +359,17 @@ impl<W> StackedWitness<W> {
             binding,
         }
     }
+
+    pub fn binding(&self) -> &BindingIndex {
+        &self.binding
+    }
 }
 
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct StackedZ<S: Stackable> {
     ck: CommitKey,
     message: S::MessageZ,
@@ -154,6 +402,41 @@ impl<S: Stackable> StackedZ<S> {
     }
 }
 
+impl<S: Stackable> StackedZ<S>
+where
+    S::MessageZ: ReadMessage,
+{
+    /// Encode the commit key, the inner third message and
+    /// the randomness, each prefixed with its length as a
+    /// little-endian `u32`.
+    pub fn to_framed_bytes(
+        &self,
+    ) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        write_framed(&self.ck, &mut bytes)?;
+        write_framed(&self.message, &mut bytes)?;
+        write_framed(&self.aux, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by
+    /// [`StackedZ::to_framed_bytes`]. Fails if a segment is
+    /// truncated or malformed, or if bytes are left over.
+    pub fn from_framed_bytes(
+        mut bytes: &[u8],
+    ) -> Result<Self, Error> {
+        let ck = CommitKey::read(read_framed(&mut bytes)?)?;
+        let message =
+            S::MessageZ::read(read_framed(&mut bytes)?)?;
+        let aux =
+            Randomness::read(read_framed(&mut bytes)?)?;
+        if !bytes.is_empty() {
+            return Err(Error::InvalidEncoding);
+        }
+        Ok(StackedZ { ck, message, aux })
+    }
+}
+
 impl<S: Stackable> fmt::Debug for StackedZ<S> {
     fn fmt(
         &self,
@@ -167,15 +450,59 @@ impl<S: Stackable> fmt::Debug for StackedZ<S> {
     }
 }
 
+impl<S: Stackable> PartialEq for StackedZ<S>
+where
+    S::MessageZ: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ck == other.ck
+            && self.message == other.message
+            && self.aux == other.aux
+    }
+}
+
+impl<S: Stackable> Eq for StackedZ<S> where S::MessageZ: Eq {}
+
+/// Hashes the commit key, the inner third message in its
+/// [`Message`] encoding and the randomness, e.g. to detect
+/// replayed proofs
+impl<S: Stackable> Hash for StackedZ<S>
+where
+    S::MessageZ: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ck
+            .hash(state);
+        let mut message = Vec::new();
+        self.message
+            .write(&mut message);
+        message.hash(state);
+        self.aux
+            .hash(state);
+    }
+}
+
+impl<S: Stackable> StackedZ<S> {
+    /// Like [`StackedZ::default`], with the randomness drawn
+    /// from `rng`
+    pub fn default_with_rng<R: CryptoRngCore>(
+        rng: &mut R,
+    ) -> Self {
+        StackedZ {
+            aux: Randomness::random(rng, 1),
+            ..Self::default()
+        }
+    }
+}
+
+/// Placeholder message with zero randomness. Use
+/// [`StackedZ::default_with_rng`] for random randomness.
 impl<S: Stackable> Default for StackedZ<S> {
     fn default() -> Self {
         StackedZ {
             ck: CommitKey::default(),
             message: S::MessageZ::default(),
-            aux: Randomness::random(
-                &mut ChaCha20Rng::from_entropy(),
-                1,
-            ),
+            aux: Randomness::default(),
         }
     }
 }
@@ -199,9 +526,32 @@ pub struct StackedState<S: Stackable> {
     aux: Randomness,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct StackedA(CommitKey, Commitment);
 
+impl StackedA {
+    pub fn new(
+        ck: CommitKey,
+        commitment: Commitment,
+    ) -> Self {
+        StackedA(ck, commitment)
+    }
+
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn ck(&self) -> &CommitKey {
+        &self.0
+    }
+
+    #[cfg_attr(coverage_nightly, no_coverage)]
+    pub fn commitment(&self) -> &Commitment {
+        &self.1
+    }
+}
+
 impl Message for StackedA {
     fn write<W: Write>(&self, writer: &mut W) {
         self.0
@@ -213,6 +563,9 @@ impl Message for StackedA {
 
 #[test]
 fn test_traits_stacked_a() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
     // Test message for stacked A
     let rng = ChaCha20Rng::from_seed([0u8; 32]);
     let ck = CommitKey::default();
@@ -232,23 +585,73 @@ pub struct SelfStacker<S: Stackable> {
     clauses: usize, // number of clauses being composed
     base: S,
     q: usize,
+    pp: Option<PublicParams>,
 }
 
 impl<S: Stackable> SelfStacker<S> {
     pub fn new(clauses: usize, base: S) -> Self {
         assert!(clauses > 1);
-        let q = (clauses as f64)
-            .log2()
-            .ceil() as usize;
-        dbg!(q);
+        // ceil(log2(clauses)) in integer arithmetic
+        let q = clauses
+            .next_power_of_two()
+            .ilog2() as usize;
 
         SelfStacker {
             clauses: 1 << q,
             base,
             q,
+            pp: None,
         }
     }
 
+    /// Like [`SelfStacker::new`], but keeps public
+    /// parameters to build statements with
+    /// [`SelfStacker::statement`].
+    ///
+    /// Panics if `pp` was not set up for the `q` computed
+    /// from `clauses`.
+    pub fn new_with_params(
+        clauses: usize,
+        base: S,
+        pp: PublicParams,
+    ) -> Self {
+        let stacker = Self::new(clauses, base);
+        assert_eq!(pp.q(), stacker.q);
+        SelfStacker {
+            pp: Some(pp),
+            ..stacker
+        }
+    }
+
+    /// Set up public parameters of the partially binding
+    /// commitment scheme for this stacker's `q`
+    pub fn setup<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+    ) -> PublicParams {
+        QBinding::new(self.q).setup(rng)
+    }
+
+    pub fn pp(&self) -> Option<&PublicParams> {
+        self.pp
+            .as_ref()
+    }
+
+    /// Stack `statements` under the public parameters the
+    /// stacker was constructed with, or `None` if it was
+    /// constructed without any
+    pub fn statement(
+        &self,
+        statements: Vec<S::Statement>,
+    ) -> Option<StackedStatement<S>> {
+        Some(StackedStatement::new(
+            self.pp
+                .clone()?,
+            self.q,
+            statements,
+        ))
+    }
+
     pub fn clauses(&self) -> usize {
         self.clauses
     }
@@ -260,6 +663,215 @@ impl<S: Stackable> SelfStacker<S> {
     pub fn q(&self) -> usize {
         self.q
     }
+
+    /// Verify a transcript using data precomputed with
+    /// [`StackedStatement::precompute`]
+    pub fn verify_with(
+        precompute: &VerifyPrecompute<S>,
+        a: &StackedA,
+        c: &S::Challenge,
+        z: &StackedZ<S>,
+    ) -> bool {
+        let VerifyPrecompute { statement, binding } =
+            precompute;
+        // Deconstruct variables from structs
+        // Here we get the commitment key, and commitment from first round of stacking protocol
+        let StackedA(ck_a, comm) = a;
+        // Here we get the commitment key, messages, and aux variable from the third round of stacker
+        let StackedZ {
+            ck: ck_z,
+            message,
+            aux,
+        } = z;
+        // Randomness of the wrong depth comes from a malformed
+        // proof; reject it before simulating any clause
+        if aux.q() != statement.height() {
+            return false;
+        }
+
+        // Now we go through every statement and simulate with the recyclable third round message
+        // and challenge from 2nd round
+        let v: Vec<Rc<S::MessageA>> = statement
+            .statements()
+            .iter()
+            .map(|s| Rc::new(S::simulate(s, c, message)))
+            .collect();
+
+        // Using bindcom algorithm, we compute the commitment to this vector of messages.
+        // An invalid commitment key is rejected rather than panicking
+        let comm_check = match binding.try_bind(
+            &statement.pp,
+            ck_a,
+            &v,
+            aux,
+        ) {
+            Some(comm_check) => comm_check,
+            None => return false,
+        };
+
+        // Now we want to verify that the messages are valid for every clause
+        let nested_check = statement
+            .statements()
+            .iter()
+            .zip(v.iter())
+            .all(|(s, m)| S::verify(s, m, c, message));
+
+        ck_a == ck_z && *comm == comm_check && nested_check
+    }
+
+    /// Verify like [`SigmaProtocol::verify`] without
+    /// collecting the simulated first messages of every
+    /// clause: each one is simulated lazily, checked against
+    /// its clause and fed into the commitment in one pass.
+    pub fn verify_streaming(
+        statement: &StackedStatement<S>,
+        a: &StackedA,
+        c: &S::Challenge,
+        z: &StackedZ<S>,
+    ) -> bool {
+        let StackedA(ck_a, comm) = a;
+        let StackedZ {
+            ck: ck_z,
+            message,
+            aux,
+        } = z;
+        if ck_a != ck_z || aux.q() != statement.height() {
+            return false;
+        }
+
+        let mut nested_check = true;
+        let v = statement
+            .statements()
+            .iter()
+            .map(|s| {
+                let m = S::simulate(s, c, message);
+                nested_check &=
+                    S::verify(s, &m, c, message);
+                Rc::new(m)
+            });
+        let comm_check = QBinding::new(statement.height())
+            .try_bind_iter(&statement.pp, ck_a, v, aux);
+
+        comm_check == Some(*comm) && nested_check
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<S: Stackable + ThreadSafeProtocol> SelfStacker<S> {
+    /// Verify like [`SigmaProtocol::verify`], simulating
+    /// and checking the clauses in parallel with rayon.
+    ///
+    /// The simulated first messages are collected in clause
+    /// order before they are committed to, so the
+    /// commitment is the one the serial verifier computes.
+    pub fn verify_parallel(
+        statement: &StackedStatement<S>,
+        a: &StackedA,
+        c: &S::Challenge,
+        z: &StackedZ<S>,
+    ) -> bool {
+        let StackedA(ck_a, comm) = a;
+        let StackedZ {
+            ck: ck_z,
+            message,
+            aux,
+        } = z;
+        if ck_a != ck_z || aux.q() != statement.height() {
+            return false;
+        }
+
+        let statements = statement.statements();
+        let v: Vec<S::MessageA> = statements
+            .par_iter()
+            .map(|s| S::simulate(s, c, message))
+            .collect();
+        let nested_check = statements
+            .par_iter()
+            .zip(v.par_iter())
+            .all(|(s, m)| S::verify(s, m, c, message));
+        if !nested_check {
+            return false;
+        }
+
+        let comm_check = QBinding::new(statement.height())
+            .try_bind_iter(
+                &statement.pp,
+                ck_a,
+                v.into_iter()
+                    .map(Rc::new),
+                aux,
+            );
+        comm_check == Some(*comm)
+    }
+}
+
+impl<S: Stackable> SelfStacker<S>
+where
+    S::Statement: Message,
+{
+    /// Derive the challenge non-interactively from the hash
+    /// of `statement || a` (Fiat-Shamir)
+    pub fn fiat_shamir(
+        statement: &StackedStatement<S>,
+        a: &StackedA,
+    ) -> S::Challenge {
+        let mut hash = Sha512::new();
+        statement.write(&mut hash);
+        a.write(&mut hash);
+        challenge_from_hash(hash)
+    }
+
+    /// Non-interactive prover. The challenge is derived
+    /// with [`SelfStacker::fiat_shamir`] instead of being
+    /// sent by the verifier.
+    ///
+    /// Fails with [`Error::InvalidStatement`] if a clause
+    /// is not a valid statement, see
+    /// [`StackedStatement::is_valid`].
+    pub fn prove_ni<R: CryptoRngCore + Clone>(
+        statement: &StackedStatement<S>,
+        witness: &StackedWitness<S::Witness>,
+        prover_rng: &mut R,
+    ) -> Result<(StackedA, StackedZ<S>), Error> {
+        if !statement.is_valid() {
+            return Err(Error::InvalidStatement);
+        }
+        let (state, a) =
+            Self::first(statement, witness, prover_rng);
+        let c = Self::fiat_shamir(statement, &a);
+        let z = Self::third(
+            statement, state, witness, &c, prover_rng,
+        );
+        Ok((a, z))
+    }
+
+    /// Canonical encoding of a non-interactive proof: the
+    /// first message framed by its length, followed by
+    /// [`StackedZ::to_framed_bytes`]
+    pub fn encode_proof(
+        a: &StackedA,
+        z: &StackedZ<S>,
+    ) -> Result<Vec<u8>, Error>
+    where
+        S::MessageZ: ReadMessage,
+    {
+        let mut bytes = Vec::new();
+        write_framed(a, &mut bytes)?;
+        bytes.extend(z.to_framed_bytes()?);
+        Ok(bytes)
+    }
+
+    /// Non-interactive verifier. Recomputes the challenge
+    /// from `statement || a` so that a proof cannot be
+    /// replayed against a different statement.
+    pub fn verify_ni(
+        statement: &StackedStatement<S>,
+        a: &StackedA,
+        z: &StackedZ<S>,
+    ) -> bool {
+        let c = Self::fiat_shamir(statement, a);
+        Self::verify(statement, a, &c, z)
+    }
 }
 
 impl<S: Stackable> Stackable for SelfStacker<S> {}
@@ -297,21 +909,23 @@ impl<S: Stackable> EHVzk for SelfStacker<S> {
     }
 }
 
-/// Sigma protocol implementation for self-stacking compiler
-impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
-    type Statement = StackedStatement<S>;
-    type Witness = StackedWitness<S::Witness>;
-    type State = StackedState<S>;
-    type MessageA = StackedA;
-    type Challenge = S::Challenge;
-    type MessageZ = StackedZ<S>;
-
-    /// First round of the protocol
-    fn first<R: CryptoRngCore + Clone>(
+impl<S: Stackable> SelfStacker<S> {
+    /// First round with the bound clause's first message
+    /// computed by `nested_first`
+    fn first_with<R, F>(
         statement: &StackedStatement<S>,
         witness: &StackedWitness<S::Witness>,
         prover_rng: &mut R,
-    ) -> (Self::State, Self::MessageA) {
+        nested_first: F,
+    ) -> (StackedState<S>, StackedA)
+    where
+        R: CryptoRngCore + Clone,
+        F: FnOnce(
+            &S::Statement,
+            &S::Witness,
+            &mut R,
+        ) -> (S::State, S::MessageA),
+    {
         // Deconstruct witness
         let StackedWitness {
             nested_witness,
@@ -319,7 +933,7 @@ impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
         } = witness;
 
         // First call the underlying protocol with the statement at the active clause
-        let (nested_state, bound_message) = S::first(
+        let (nested_state, bound_message) = nested_first(
             statement.bound_statement(binding),
             nested_witness,
             prover_rng,
@@ -376,26 +990,26 @@ impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
         )
     }
 
-    /// Second round of the protocol. Random challenge.
-    fn second<R: CryptoRngCore>(
-        verifier_rng: &mut R,
-    ) -> Self::Challenge
+    /// Third round with the bound clause's response
+    /// computed by `nested_third`
+    fn third_with<R, F>(
+        statement: &StackedStatement<S>,
+        state: StackedState<S>,
+        witness: &StackedWitness<S::Witness>,
+        challenge: &S::Challenge,
+        prover_rng: &mut R,
+        nested_third: F,
+    ) -> StackedZ<S>
     where
-        Self: Sized,
+        R: CryptoRngCore + Clone,
+        F: FnOnce(
+            &S::Statement,
+            S::State,
+            &S::Witness,
+            &S::Challenge,
+            &mut R,
+        ) -> S::MessageZ,
     {
-        let mut buffer = [0u8; 64];
-        verifier_rng.fill_bytes(&mut buffer);
-        Challenge::new(&buffer)
-    }
-
-    /// Third round of the protocol.
-    fn third<R: CryptoRngCore + Clone>(
-        statement: &Self::Statement,
-        state: Self::State,
-        witness: &Self::Witness,
-        challenge: &Self::Challenge,
-        prover_rng: &mut R,
-    ) -> Self::MessageZ {
         // Deconstruct the state struct
         let StackedState {
             nested_state,
@@ -414,7 +1028,7 @@ impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
         let qbinding = QBinding::new(statement.height());
 
         // Call third round algorithm of underlying protocol
-        let nested_z = S::third(
+        let nested_z = nested_third(
             statement.bound_statement(binding),
             nested_state,
             nested_witness,
@@ -457,6 +1071,118 @@ impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
             aux: aux_new,
         }
     }
+}
+
+/// Forwards the prover context to the base protocol of the
+/// bound clause; the other clauses are simulated and do not
+/// take it
+impl<S> ContextualSigmaProtocol for SelfStacker<S>
+where
+    S: Stackable + ContextualSigmaProtocol,
+{
+    type ProverContext = S::ProverContext;
+
+    fn first_with_context<R: CryptoRngCore + Clone>(
+        statement: &StackedStatement<S>,
+        witness: &StackedWitness<S::Witness>,
+        prover_rng: &mut R,
+        prover_context: &S::ProverContext,
+    ) -> (Self::State, Self::MessageA) {
+        Self::first_with(
+            statement,
+            witness,
+            prover_rng,
+            |statement, witness, prover_rng| {
+                S::first_with_context(
+                    statement,
+                    witness,
+                    prover_rng,
+                    prover_context,
+                )
+            },
+        )
+    }
+
+    fn third_with_context<R: CryptoRngCore + Clone>(
+        statement: &Self::Statement,
+        state: Self::State,
+        witness: &Self::Witness,
+        challenge: &Self::Challenge,
+        prover_rng: &mut R,
+        prover_context: &S::ProverContext,
+    ) -> Self::MessageZ {
+        Self::third_with(
+            statement,
+            state,
+            witness,
+            challenge,
+            prover_rng,
+            |statement, state, witness, c, prover_rng| {
+                S::third_with_context(
+                    statement,
+                    state,
+                    witness,
+                    c,
+                    prover_rng,
+                    prover_context,
+                )
+            },
+        )
+    }
+}
+
+/// Sigma protocol implementation for self-stacking compiler
+impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
+    type Statement = StackedStatement<S>;
+    type Witness = StackedWitness<S::Witness>;
+    type State = StackedState<S>;
+    type MessageA = StackedA;
+    type Challenge = S::Challenge;
+    type MessageZ = StackedZ<S>;
+
+    /// First round of the protocol
+    fn first<R: CryptoRngCore + Clone>(
+        statement: &StackedStatement<S>,
+        witness: &StackedWitness<S::Witness>,
+        prover_rng: &mut R,
+    ) -> (Self::State, Self::MessageA) {
+        Self::first_with(
+            statement,
+            witness,
+            prover_rng,
+            S::first,
+        )
+    }
+
+    /// Second round of the protocol. Random challenge.
+    fn second<R: CryptoRngCore>(
+        verifier_rng: &mut R,
+    ) -> Self::Challenge
+    where
+        Self: Sized,
+    {
+        let mut buffer = [0u8; 64];
+        verifier_rng.fill_bytes(&mut buffer);
+        Challenge::new(&buffer)
+    }
+
+    /// Third round of the protocol.
+    fn third<R: CryptoRngCore + Clone>(
+        statement: &Self::Statement,
+        state: Self::State,
+        witness: &Self::Witness,
+        challenge: &Self::Challenge,
+        prover_rng: &mut R,
+    ) -> Self::MessageZ {
+        Self::third_with(
+            statement,
+            state,
+            witness,
+            challenge,
+            prover_rng,
+            S::third,
+        )
+    }
 
     /// Verification algorithm for the protocol
     fn verify(
@@ -468,35 +1194,6 @@ impl<S: Stackable> SigmaProtocol for SelfStacker<S> {
     where
         Self: Sized,
     {
-        // Deconstruct variables from structs
-        // Here we get the commitment key, and commitment from first round of stacking protocol
-        let StackedA(ck_a, comm) = a;
-        // Here we get the commitment key, messages, and aux variable from the third round of stacker
-        let StackedZ {
-            ck: ck_z,
-            message,
-            aux,
-        } = z;
-
-        // Now we go through every statement and simulate with the recyclable third round message
-        // and challenge from 2nd round
-        let v: Vec<Rc<S::MessageA>> = statement
-            .statements()
-            .iter()
-            .map(|s| Rc::new(S::simulate(s, c, &message)))
-            .collect();
-
-        // Using bindcom algorithm, we compute the commitment to this vector of messages
-        let comm_check = QBinding::new(statement.height())
-            .bind(&statement.pp, ck_a, &v, aux);
-
-        // Now we want to verify that the messages are valid for every clause
-        let nested_check = statement
-            .statements()
-            .iter()
-            .zip(v.iter())
-            .all(|(s, m)| S::verify(s, m, c, &message));
-
-        ck_a == ck_z && *comm == comm_check && nested_check
+        Self::verify_with(&statement.precompute(), a, c, z)
     }
 }
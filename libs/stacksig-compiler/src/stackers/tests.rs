@@ -37,8 +37,8 @@ mod test_selfstacker {
         );
 
         // Handle witness
-        let actual_witness = Scalar::from_bits([0u8; 32]);
-        let provers_witness = Scalar::from_bits([0u8; 32]);
+        let actual_witness = Scalar::random(rng);
+        let provers_witness = actual_witness;
         // Initialise base sigma + remaining sigma instances
         let base_schnorr = Schnorr::init(actual_witness);
         let dummy_schnorr = Schnorr::init(Scalar::random(
@@ -190,6 +190,987 @@ mod test_selfstacker {
         ));
     }
 
+    #[test]
+    fn framed_z_round_trip() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 8, 3);
+
+        let (message_a, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        let bytes = message_z
+            .to_framed_bytes()
+            .unwrap();
+        let decoded: StackedZ<Schnorr> =
+            StackedZ::from_framed_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.ck(), message_z.ck());
+        assert_eq!(decoded.message(), message_z.message());
+        assert_eq!(decoded.aux(), message_z.aux());
+        assert!(SelfStacker::verify_ni(
+            &s2_statement,
+            &message_a,
+            &decoded
+        ));
+    }
+
+    #[test]
+    fn framed_z_rejects_truncation() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 8, 3);
+
+        let (_, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        let bytes = message_z
+            .to_framed_bytes()
+            .unwrap();
+
+        for len in [0, 3, 4, 36, bytes.len() - 1] {
+            assert!(
+                StackedZ::<Schnorr>::from_framed_bytes(
+                    &bytes[..len]
+                )
+                .is_err()
+            );
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(StackedZ::<Schnorr>::from_framed_bytes(
+            &extended
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn verify_with_agrees_with_verify() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let verifier_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let StackerTest {
+            s2_statement,
+            s2_witness,
+            ..
+        } = testinit(rng, 8, 3);
+        let precompute = s2_statement.precompute();
+
+        for i in 0..10 {
+            let (state, message_a) = SelfStacker::first(
+                &s2_statement,
+                &s2_witness,
+                &mut rng.clone(),
+            );
+            let challenge = SelfStacker::<Schnorr>::second(
+                verifier_rng,
+            );
+            // Every other proof answers a different challenge
+            // and must be rejected by both verifiers
+            let answered = if i % 2 == 0 {
+                challenge
+            } else {
+                SelfStacker::<Schnorr>::second(verifier_rng)
+            };
+            let message_z = SelfStacker::third(
+                &s2_statement,
+                state,
+                &s2_witness,
+                &answered,
+                rng,
+            );
+
+            let expected = SelfStacker::verify(
+                &s2_statement,
+                &message_a,
+                &challenge,
+                &message_z,
+            );
+            assert_eq!(expected, i % 2 == 0);
+            assert_eq!(
+                SelfStacker::verify_with(
+                    &precompute,
+                    &message_a,
+                    &challenge,
+                    &message_z
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn verify_streaming_agrees_with_verify() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let verifier_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let StackerTest {
+            s2_statement,
+            s2_witness,
+            ..
+        } = testinit(rng, 64, 37);
+
+        for i in 0..4 {
+            let (state, message_a) = SelfStacker::first(
+                &s2_statement,
+                &s2_witness,
+                rng,
+            );
+            let challenge = SelfStacker::<Schnorr>::second(
+                verifier_rng,
+            );
+            // Every other proof answers a different challenge
+            let answered = if i % 2 == 0 {
+                challenge
+            } else {
+                SelfStacker::<Schnorr>::second(verifier_rng)
+            };
+            let message_z = SelfStacker::third(
+                &s2_statement,
+                state,
+                &s2_witness,
+                &answered,
+                rng,
+            );
+
+            let expected = SelfStacker::verify(
+                &s2_statement,
+                &message_a,
+                &challenge,
+                &message_z,
+            );
+            assert_eq!(expected, i % 2 == 0);
+            assert_eq!(
+                SelfStacker::verify_streaming(
+                    &s2_statement,
+                    &message_a,
+                    &challenge,
+                    &message_z
+                ),
+                expected
+            );
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn verify_parallel_agrees_with_verify() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let verifier_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let StackerTest {
+            s2_statement,
+            s2_witness,
+            ..
+        } = testinit(rng, 256, 101);
+
+        for i in 0..2 {
+            let (state, message_a) = SelfStacker::first(
+                &s2_statement,
+                &s2_witness,
+                rng,
+            );
+            let challenge = SelfStacker::<Schnorr>::second(
+                verifier_rng,
+            );
+            // The second proof answers a different challenge
+            let answered = if i == 0 {
+                challenge
+            } else {
+                SelfStacker::<Schnorr>::second(verifier_rng)
+            };
+            let message_z = SelfStacker::third(
+                &s2_statement,
+                state,
+                &s2_witness,
+                &answered,
+                rng,
+            );
+
+            let expected = SelfStacker::verify(
+                &s2_statement,
+                &message_a,
+                &challenge,
+                &message_z,
+            );
+            assert_eq!(expected, i == 0);
+            assert_eq!(
+                SelfStacker::verify_parallel(
+                    &s2_statement,
+                    &message_a,
+                    &challenge,
+                    &message_z
+                ),
+                expected
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use sigmazk::error::Error;
+        use sigmazk::Conversation;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let verifier_rng =
+            &mut ChaCha20Rng::from_seed([1u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 8, 5);
+
+        let conversation =
+            Conversation::<SelfStacker<Schnorr>>::record(
+                s2_statement,
+                &valid_witness,
+                rng,
+                verifier_rng,
+            );
+        assert!(conversation.verify());
+
+        let json =
+            serde_json::to_string(&conversation).unwrap();
+        let decoded: Conversation<SelfStacker<Schnorr>> =
+            serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify());
+        assert_eq!(decoded.a, conversation.a);
+        assert_eq!(decoded.c, conversation.c);
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            json
+        );
+
+        // A statement whose clause count disagrees with its
+        // height is rejected
+        let mut value =
+            serde_json::to_value(&conversation).unwrap();
+        value["statement"]["clauses"] = 7.into();
+        assert!(serde_json::from_value::<
+            Conversation<SelfStacker<Schnorr>>,
+        >(value)
+        .is_err());
+
+        // So is one missing a clause
+        let mut value =
+            serde_json::to_value(&conversation).unwrap();
+        value["statement"]["statements"]
+            .as_array_mut()
+            .unwrap()
+            .pop();
+        assert!(serde_json::from_value::<
+            Conversation<SelfStacker<Schnorr>>,
+        >(value)
+        .is_err());
+        assert!(matches!(
+            StackedStatement::<Schnorr>::try_new(
+                conversation
+                    .statement
+                    .pp()
+                    .clone(),
+                conversation
+                    .statement
+                    .height(),
+                conversation
+                    .statement
+                    .statements()[1..]
+                    .to_vec(),
+            ),
+            Err(Error::StatementLengthMismatch)
+        ));
+    }
+
+    /// Regression guard for witness indistinguishability:
+    /// a proof must not reveal which clause it is bound to,
+    /// so any change to the stacker or the commitment scheme
+    /// that makes proofs depend on the binding index fails
+    /// here.
+    #[test]
+    fn binding_index_is_witness_indistinguishable() {
+        use sigmazk::testing::assert_wi_with;
+
+        use crate::stackable::Message;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let stackedsigma = SelfStacker::new(
+            4,
+            Schnorr::init(Scalar::random(rng)),
+        );
+        let (qbinding, _) =
+            QBinding::init(stackedsigma.q(), 0);
+        let pp = qbinding.setup(rng);
+
+        // Clauses 1 and 2 both have a known witness
+        let witnesses =
+            [Scalar::random(rng), Scalar::random(rng)];
+        let mut statements: Vec<Schnorr> = (0..4)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        statements[1] = Schnorr::init(witnesses[0]);
+        statements[2] = Schnorr::init(witnesses[1]);
+        let statement = StackedStatement::new(
+            pp,
+            stackedsigma.q(),
+            statements,
+        );
+        let witness = |i: usize| {
+            let (_, binding_index) =
+                QBinding::init(stackedsigma.q(), i);
+            StackedWitness::init(
+                witnesses[i - 1],
+                binding_index,
+            )
+        };
+
+        assert_wi_with::<SelfStacker<Schnorr>, _>(
+            &statement,
+            &witness(1),
+            &witness(2),
+            256,
+            |a, z| {
+                let mut bytes = Vec::new();
+                a.write(&mut bytes);
+                bytes.extend(
+                    z.to_framed_bytes()
+                        .unwrap(),
+                );
+                bytes
+            },
+        );
+    }
+
+    #[test]
+    fn stacked_witness_debug_redacts_witness() {
+        let secret = Scalar::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let witness = StackedWitness::init(
+            secret,
+            BindingIndex::new(3, 5),
+        );
+
+        let debug = format!("{:?}", witness);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(&format!("{:?}", secret)));
+        assert!(debug.contains("binding"));
+    }
+
+    #[test]
+    fn proofs_dedup_in_hash_set() {
+        use std::collections::HashSet;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 8, 3);
+
+        let mut seen = HashSet::new();
+        let proof = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        assert!(seen.insert(proof.clone()));
+        let other = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        assert!(seen.insert(other));
+
+        // A replayed proof is detected
+        assert!(!seen.insert(proof.clone()));
+        assert_eq!(seen.len(), 2);
+
+        // The first message alone is enough as a key too
+        let firsts: HashSet<StackedA> = seen
+            .iter()
+            .map(|(a, _)| a.clone())
+            .collect();
+        assert!(firsts.contains(&proof.0));
+        assert_eq!(firsts.len(), 2);
+    }
+
+    #[test]
+    fn stacked_proof_with_std_rng() {
+        use rand::rngs::StdRng;
+
+        const CLAUSES: usize = 8;
+        const B: usize = 5;
+
+        let rng = &mut StdRng::seed_from_u64(7);
+        let secret = Scalar::random(rng);
+        let stacker = SelfStacker::new(
+            CLAUSES,
+            Schnorr::init(secret),
+        );
+        let pp = stacker.setup(rng);
+        let mut statements: Vec<Schnorr> = (0..CLAUSES)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        statements[B] = Schnorr::init(secret);
+        let statement: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                pp,
+                stacker.q(),
+                statements,
+            );
+        let witness = StackedWitness::init(
+            secret,
+            BindingIndex::new(stacker.q(), B),
+        );
+
+        let (a, z) = SelfStacker::prove_ni(
+            &statement, &witness, rng,
+        )
+        .unwrap();
+        assert!(SelfStacker::verify_ni(&statement, &a, &z));
+
+        // The default third message no longer needs entropy
+        let placeholder = StackedZ::<Schnorr>::default();
+        assert_eq!(placeholder, StackedZ::default());
+        assert_ne!(
+            StackedZ::<Schnorr>::default_with_rng(rng),
+            placeholder
+        );
+    }
+
+    #[test]
+    fn prove_ni_rejects_identity_statement() {
+        use rand::rngs::StdRng;
+        use sigmazk::error::Error;
+
+        const CLAUSES: usize = 4;
+
+        let rng = &mut StdRng::seed_from_u64(7);
+        let secret = Scalar::random(rng);
+        let stacker = SelfStacker::new(
+            CLAUSES,
+            Schnorr::init(secret),
+        );
+        let pp = stacker.setup(rng);
+        let mut statements: Vec<Schnorr> = (0..CLAUSES)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        statements[1] = Schnorr::init(secret);
+        let witness = StackedWitness::init(
+            secret,
+            BindingIndex::new(stacker.q(), 1),
+        );
+        let statement: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                pp.clone(),
+                stacker.q(),
+                statements.clone(),
+            );
+        assert!(statement.is_valid());
+        assert!(SelfStacker::prove_ni(
+            &statement, &witness, rng
+        )
+        .is_ok());
+
+        // A clause whose public key is the identity
+        statements[2] = Schnorr::init(Scalar::ZERO);
+        let statement: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                pp,
+                stacker.q(),
+                statements,
+            );
+        assert!(!statement.is_valid());
+        assert!(matches!(
+            SelfStacker::prove_ni(
+                &statement, &witness, rng
+            ),
+            Err(Error::InvalidStatement)
+        ));
+    }
+
+    #[test]
+    fn new_computes_q() {
+        for (clauses, q) in
+            [(2, 1), (3, 2), (4, 2), (5, 3), (1 << 20, 20)]
+        {
+            let stacker = SelfStacker::new(
+                clauses,
+                Schnorr::init(Scalar::ONE),
+            );
+            assert_eq!(stacker.q(), q);
+            assert_eq!(stacker.clauses(), 1 << q);
+        }
+    }
+
+    #[test]
+    fn new_with_params_builds_statement() {
+        const CLAUSES: usize = 6;
+        const B: usize = 4;
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+
+        let witness = Scalar::random(rng);
+        let base = Schnorr::init(witness);
+        let pp = SelfStacker::new(CLAUSES, base).setup(rng);
+        let stacker =
+            SelfStacker::new_with_params(CLAUSES, base, pp);
+        assert_eq!(
+            stacker
+                .pp()
+                .unwrap()
+                .q(),
+            stacker.q()
+        );
+        assert!(SelfStacker::new(CLAUSES, base)
+            .statement(vec![])
+            .is_none());
+
+        let mut statements: Vec<Schnorr> = (0..stacker
+            .clauses())
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        statements[B] = base;
+        let statement = stacker
+            .statement(statements)
+            .unwrap();
+        let witness = StackedWitness::init(
+            witness,
+            BindingIndex::new(stacker.q(), B),
+        );
+
+        let (message_a, message_z) = SelfStacker::prove_ni(
+            &statement, &witness, rng,
+        )
+        .unwrap();
+        assert!(SelfStacker::verify_ni(
+            &statement, &message_a, &message_z
+        ));
+    }
+
+    #[test]
+    fn counting_writer_matches_encoded_length() {
+        use sigmazk::io::CountingWriter;
+
+        use crate::stackable::Message;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 16, 1);
+        let (message_a, _) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+
+        let mut counter = CountingWriter::new();
+        let mut bytes = Vec::new();
+        message_a.write(&mut counter);
+        message_a.write(&mut bytes);
+        assert_eq!(counter.count(), bytes.len());
+        assert_eq!(message_a.size(), bytes.len());
+    }
+
+    #[test]
+    fn proof_size_grows_with_depth() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let mut proof_size = |clauses: usize| {
+            let StackerTest {
+                s2_statement,
+                valid_witness,
+                ..
+            } = testinit(rng, clauses, 1);
+            let (message_a, message_z) =
+                SelfStacker::prove_ni(
+                    &s2_statement,
+                    &valid_witness,
+                    rng,
+                )
+                .unwrap();
+            SelfStacker::encode_proof(
+                &message_a, &message_z,
+            )
+            .unwrap()
+            .len()
+        };
+
+        // q = 2, 4 and 8
+        let (s4, s16, s256) = (
+            proof_size(4),
+            proof_size(16),
+            proof_size(256),
+        );
+        // Each extra level of the tree adds the same number
+        // of bytes, so the size is affine in q = log2(clauses)
+        assert_eq!(s256 - s16, 2 * (s16 - s4));
+        assert!(s256 < 2 * s16);
+    }
+
+    #[test]
+    fn simulate_self_test() {
+        use sigmazk::EHVzk;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, 8, 3);
+
+        // Any third message with a well-formed commit key
+        let (_, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        assert!(SelfStacker::simulate_self_test(
+            &s2_statement,
+            &message_z,
+            rng
+        ));
+    }
+
+    #[test]
+    fn verify_ni_rejects_other_statement() {
+        const CLAUSES: usize = 8;
+        const B: usize = 3;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, CLAUSES, B);
+
+        let (message_a, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        assert!(SelfStacker::verify_ni(
+            &s2_statement,
+            &message_a,
+            &message_z
+        ));
+
+        // Same public parameters, different inactive clause
+        let mut statements = s2_statement
+            .statements()
+            .to_vec();
+        statements[0] = Schnorr::init(Scalar::random(rng));
+        let other_statement: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                s2_statement
+                    .pp()
+                    .clone(),
+                s2_statement.height(),
+                statements,
+            );
+        assert!(!SelfStacker::verify_ni(
+            &other_statement,
+            &message_a,
+            &message_z
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_invalid_commit_key() {
+        use curve25519_dalek::ristretto::CompressedRistretto;
+
+        use crate::commitment_scheme::halfbinding;
+
+        const CLAUSES: usize = 8;
+        const B: usize = 3;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, CLAUSES, B);
+
+        let (message_a, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+
+        // Outer commit key that does not decompress to a point
+        let mut ck = message_a
+            .ck()
+            .clone();
+        ck.outer_ck = halfbinding::CommitKey(
+            CompressedRistretto([0xff; 32]),
+        );
+        let garbage_a =
+            StackedA::new(ck, *message_a.commitment());
+        assert!(!SelfStacker::verify_ni(
+            &s2_statement,
+            &garbage_a,
+            &message_z
+        ));
+    }
+
+    #[test]
+    fn interactive_verify_rejects_corrupted_commit_key() {
+        use curve25519_dalek::ristretto::CompressedRistretto;
+
+        use crate::commitment_scheme::halfbinding;
+
+        const Q: usize = 3;
+        const CLAUSES: usize = 1 << Q;
+        const B: usize = 3;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            s2_witness,
+            ..
+        } = testinit(rng, CLAUSES, B);
+
+        let (state, message_a) = SelfStacker::first(
+            &s2_statement,
+            &s2_witness,
+            rng,
+        );
+        let challenge = SelfStacker::<Schnorr>::second(rng);
+        let message_z = SelfStacker::third(
+            &s2_statement,
+            state,
+            &s2_witness,
+            &challenge,
+            rng,
+        );
+        assert!(SelfStacker::verify(
+            &s2_statement,
+            &message_a,
+            &challenge,
+            &message_z
+        ));
+
+        // Replace each layer of the commit key, inner and
+        // outer, with bytes that do not decompress
+        let garbage = halfbinding::CommitKey(
+            CompressedRistretto([0xff; 32]),
+        );
+        let layers = message_a
+            .ck()
+            .inner_ck
+            .0
+            .len();
+        for layer in 0..=layers {
+            let mut ck = message_a
+                .ck()
+                .clone();
+            match ck
+                .inner_ck
+                .0
+                .get_mut(layer)
+            {
+                Some(inner) => *inner = garbage,
+                None => ck.outer_ck = garbage,
+            }
+            let corrupted =
+                StackedA::new(ck, *message_a.commitment());
+            assert!(!SelfStacker::verify(
+                &s2_statement,
+                &corrupted,
+                &challenge,
+                &message_z
+            ));
+        }
+    }
+
+    #[test]
+    fn verify_rejects_tampered_aux_depth() {
+        use crate::commitment_scheme::halfbinding;
+
+        const CLAUSES: usize = 8;
+        const B: usize = 3;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest {
+            s2_statement,
+            valid_witness,
+            ..
+        } = testinit(rng, CLAUSES, B);
+
+        let (message_a, message_z) = SelfStacker::prove_ni(
+            &s2_statement,
+            &valid_witness,
+            rng,
+        )
+        .unwrap();
+        let c = SelfStacker::fiat_shamir(
+            &s2_statement,
+            &message_a,
+        );
+
+        let mut deeper = message_z
+            .aux()
+            .clone();
+        deeper
+            .inner
+            .push(halfbinding::Randomness::random(rng));
+        let mut shallower = message_z
+            .aux()
+            .clone();
+        shallower
+            .inner
+            .0
+            .clear();
+
+        for aux in [deeper, shallower] {
+            let tampered = StackedZ::new(
+                message_z
+                    .ck()
+                    .clone(),
+                *message_z.message(),
+                aux,
+            );
+            assert!(!SelfStacker::verify(
+                &s2_statement,
+                &message_a,
+                &c,
+                &tampered
+            ));
+            assert!(!SelfStacker::verify_streaming(
+                &s2_statement,
+                &message_a,
+                &c,
+                &tampered
+            ));
+        }
+    }
+
+    #[test]
+    fn statement_clone_shares_clauses() {
+        const HEIGHT: usize = 10;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let pp = QBinding::new(HEIGHT).setup(rng);
+        let statements = (0..1 << HEIGHT)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        let statement: StackedStatement<Schnorr> =
+            StackedStatement::new(pp, HEIGHT, statements);
+        let cloned = statement.clone();
+        assert!(std::ptr::eq(
+            statement
+                .statements()
+                .as_ptr(),
+            cloned
+                .statements()
+                .as_ptr()
+        ));
+    }
+
+    #[test]
+    fn statement_labels_survive_clone() {
+        const CLAUSES: usize = 4;
+        const B: usize = 1;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest { s2_statement, .. } =
+            testinit(rng, CLAUSES, B);
+        assert_eq!(s2_statement.label_at(0), None);
+
+        let labels = (0..CLAUSES)
+            .map(|i| {
+                format!("party {}", i)
+                    .into_bytes()
+                    .into()
+            })
+            .collect();
+        let labelled = s2_statement
+            .clone()
+            .with_labels(labels);
+        let cloned = labelled.clone();
+        for i in 0..CLAUSES {
+            assert_eq!(
+                cloned.label_at(i),
+                Some(format!("party {}", i).as_bytes())
+            );
+            assert_eq!(
+                cloned.statement_at(i),
+                s2_statement.statement_at(i)
+            );
+        }
+        assert_eq!(cloned.label_at(CLAUSES), None);
+    }
+
+    #[test]
+    fn statement_digest_distinguishes_statements() {
+        use sigmazk::StatementDigest;
+
+        const CLAUSES: usize = 8;
+        const B: usize = 3;
+
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let StackerTest { s2_statement, .. } =
+            testinit(rng, CLAUSES, B);
+
+        let same: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                s2_statement
+                    .pp()
+                    .clone(),
+                s2_statement.height(),
+                s2_statement
+                    .statements()
+                    .to_vec(),
+            );
+        assert_eq!(s2_statement.digest(), same.digest());
+
+        // Different clause
+        let mut statements = s2_statement
+            .statements()
+            .to_vec();
+        statements[0] = Schnorr::init(Scalar::random(rng));
+        let other: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                s2_statement
+                    .pp()
+                    .clone(),
+                s2_statement.height(),
+                statements,
+            );
+        assert_ne!(s2_statement.digest(), other.digest());
+
+        // Different public parameters
+        let pp =
+            QBinding::new(s2_statement.height()).setup(rng);
+        let other: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                pp,
+                s2_statement.height(),
+                s2_statement
+                    .statements()
+                    .to_vec(),
+            );
+        assert_ne!(s2_statement.digest(), other.digest());
+    }
+
     // #[test]
     // fn recursive_stack_works() {
     //     const Q: usize = 5;
@@ -286,4 +1267,321 @@ mod test_selfstacker {
     //         &message_z
     //     ));
     // }
+
+    #[test]
+    fn padded_stack_verifies() {
+        const Q: usize = 2;
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let witness = Scalar::random(rng);
+
+        // Three real clauses padded to a power of two; the
+        // binding index is one of the real clauses
+        let mut statements = vec![
+            Schnorr::init(Scalar::random(rng)),
+            Schnorr::init(witness),
+            Schnorr::init(Scalar::random(rng)),
+        ];
+        statements.resize(1 << Q, Schnorr::default());
+        let (qbinding, binding_index) =
+            QBinding::init(Q, 1);
+        let statement: StackedStatement<Schnorr> =
+            StackedStatement::new(
+                qbinding.setup(rng),
+                Q,
+                statements,
+            );
+        let witness =
+            StackedWitness::init(witness, binding_index);
+
+        let (state, a) =
+            SelfStacker::first(&statement, &witness, rng);
+        let c = SelfStacker::<Schnorr>::second(rng);
+        let z = SelfStacker::third(
+            &statement, state, &witness, &c, rng,
+        );
+        assert!(SelfStacker::verify(
+            &statement, &a, &c, &z
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_prover_context {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use rand_core::CryptoRngCore;
+    use sigmazk::{
+        ContextualSigmaProtocol, EHVzk, Schnorr,
+    };
+
+    use super::*;
+    use crate::stackable::Stackable;
+
+    const Q: usize = 2;
+
+    /// Schnorr with the secret key split in two: the witness
+    /// is one share and the prover context the other
+    #[derive(Clone, Copy, Debug)]
+    struct SplitSchnorr;
+
+    impl SigmaProtocol for SplitSchnorr {
+        type Statement = Schnorr;
+        type Witness = Scalar;
+        type State = Scalar;
+        type MessageA = CompressedRistretto;
+        type Challenge = Scalar;
+        type MessageZ = Scalar;
+
+        fn first<R: CryptoRngCore + Clone>(
+            statement: &Schnorr,
+            witness: &Scalar,
+            prover_rng: &mut R,
+        ) -> (Scalar, CompressedRistretto) {
+            Schnorr::first(statement, witness, prover_rng)
+        }
+
+        fn second<R: CryptoRngCore + Clone>(
+            verifier_rng: &mut R,
+        ) -> Scalar {
+            Schnorr::second(verifier_rng)
+        }
+
+        /// Answers with the share alone, as if the other
+        /// share were zero
+        fn third<R: CryptoRngCore + Clone>(
+            statement: &Schnorr,
+            state: Scalar,
+            witness: &Scalar,
+            challenge: &Scalar,
+            prover_rng: &mut R,
+        ) -> Scalar {
+            Schnorr::third(
+                statement, state, witness, challenge,
+                prover_rng,
+            )
+        }
+
+        fn verify(
+            statement: &Schnorr,
+            a: &CompressedRistretto,
+            c: &Scalar,
+            z: &Scalar,
+        ) -> bool {
+            Schnorr::verify(statement, a, c, z)
+        }
+    }
+
+    impl ContextualSigmaProtocol for SplitSchnorr {
+        type ProverContext = Scalar;
+
+        fn first_with_context<R: CryptoRngCore + Clone>(
+            statement: &Schnorr,
+            witness: &Scalar,
+            prover_rng: &mut R,
+            _prover_context: &Scalar,
+        ) -> (Scalar, CompressedRistretto) {
+            Self::first(statement, witness, prover_rng)
+        }
+
+        fn third_with_context<R: CryptoRngCore + Clone>(
+            statement: &Schnorr,
+            state: Scalar,
+            witness: &Scalar,
+            challenge: &Scalar,
+            prover_rng: &mut R,
+            prover_context: &Scalar,
+        ) -> Scalar {
+            Schnorr::third(
+                statement,
+                state,
+                &(witness + prover_context),
+                challenge,
+                prover_rng,
+            )
+        }
+    }
+
+    impl EHVzk for SplitSchnorr {
+        fn simulate(
+            statement: &Schnorr,
+            challenge: &Scalar,
+            z: &Scalar,
+        ) -> CompressedRistretto {
+            <Schnorr as EHVzk>::simulate(
+                statement, challenge, z,
+            )
+        }
+    }
+
+    impl Stackable for SplitSchnorr {}
+
+    #[test]
+    fn context_reaches_bound_clause() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let key = Scalar::random(rng);
+        let share = Scalar::random(rng);
+        let context = key - share;
+
+        let mut statements: Vec<Schnorr> = (0..1 << Q)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        statements[2] = Schnorr::init(key);
+        let pp = QBinding::new(Q).setup(rng);
+        let statement: StackedStatement<SplitSchnorr> =
+            StackedStatement::new(pp, Q, statements);
+        let witness = StackedWitness::init(
+            share,
+            BindingIndex::new(Q, 2),
+        );
+        type Stacked = SelfStacker<SplitSchnorr>;
+
+        let (state, a) = Stacked::first_with_context(
+            &statement, &witness, rng, &context,
+        );
+        let c = Stacked::second(rng);
+        let z = Stacked::third_with_context(
+            &statement,
+            state.clone(),
+            &witness,
+            &c,
+            rng,
+            &context,
+        );
+        assert!(Stacked::verify(&statement, &a, &c, &z));
+
+        // Without the context the share alone is not a
+        // witness
+        let z = Stacked::third(
+            &statement, state, &witness, &c, rng,
+        );
+        assert!(!Stacked::verify(&statement, &a, &c, &z));
+    }
+}
+
+#[cfg(test)]
+mod test_verify_report {
+    use sigmazk::Schnorr;
+
+    use super::*;
+    use crate::commitment_scheme::halfbinding;
+
+    const Q: usize = 2;
+
+    type Inner = SelfStacker<Schnorr>;
+    type Outer = SelfStacker<Inner>;
+
+    fn inner_statement(
+        rng: &mut ChaCha20Rng,
+        key: Option<(Scalar, usize)>,
+    ) -> StackedStatement<Schnorr> {
+        let mut statements: Vec<Schnorr> = (0..1 << Q)
+            .map(|_| Schnorr::init(Scalar::random(rng)))
+            .collect();
+        if let Some((key, index)) = key {
+            statements[index] = Schnorr::init(key);
+        }
+        let pp = QBinding::new(Q).setup(rng);
+        StackedStatement::new(pp, Q, statements)
+    }
+
+    #[test]
+    fn empty_stack_is_rejected() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let key = Scalar::random(rng);
+        let statement =
+            inner_statement(rng, Some((key, 2)));
+
+        // A statement without clauses proves nothing and
+        // cannot be built, so the report never sees one
+        assert!(matches!(
+            StackedStatement::<Schnorr>::try_new(
+                statement
+                    .pp()
+                    .clone(),
+                Q,
+                vec![],
+            ),
+            Err(sigmazk::error::Error::StatementLengthMismatch)
+        ));
+    }
+
+    #[test]
+    fn report_pinpoints_inner_level() {
+        let rng = &mut ChaCha20Rng::from_seed([0u8; 32]);
+        let key = Scalar::random(rng);
+
+        let mut statements: Vec<_> = (0..1 << Q)
+            .map(|_| inner_statement(rng, None))
+            .collect();
+        statements[1] =
+            inner_statement(rng, Some((key, 3)));
+        let pp = QBinding::new(Q).setup(rng);
+        let statement: StackedStatement<Inner> =
+            StackedStatement::new(pp, Q, statements);
+        let witness = StackedWitness::init(
+            StackedWitness::init(
+                key,
+                BindingIndex::new(Q, 3),
+            ),
+            BindingIndex::new(Q, 1),
+        );
+
+        let (state, a) =
+            Outer::first(&statement, &witness, rng);
+        let c = Outer::second(rng);
+        let z = Outer::third(
+            &statement, state, &witness, &c, rng,
+        );
+
+        let tree =
+            Outer::verify_tree(&statement, &a, &c, &z);
+        assert!(Outer::verify(&statement, &a, &c, &z));
+        assert!(tree.is_ok());
+        assert_eq!(tree.failed_depth(), None);
+
+        // Randomness of the wrong depth in the nested
+        // response breaks the inner level only
+        let inner_z = z.message();
+        let mut deeper = inner_z
+            .aux()
+            .clone();
+        deeper
+            .inner
+            .push(halfbinding::Randomness::random(rng));
+        let tampered = StackedZ::new(
+            z.ck()
+                .clone(),
+            StackedZ::new(
+                inner_z
+                    .ck()
+                    .clone(),
+                *inner_z.message(),
+                deeper,
+            ),
+            z.aux()
+                .clone(),
+        );
+
+        let tree = Outer::verify_tree(
+            &statement, &a, &c, &tampered,
+        );
+        assert!(!tree.is_ok());
+        assert_eq!(tree.failed_depth(), Some(1));
+        let VerifyTree::Stack {
+            commitment_ok,
+            nested_report,
+        } = tree
+        else {
+            panic!("the report of a stack is a stack");
+        };
+        assert!(!commitment_ok);
+        assert_eq!(
+            *nested_report,
+            VerifyTree::Stack {
+                commitment_ok: false,
+                nested_report: Box::new(VerifyTree::Base(
+                    true
+                )),
+            }
+        );
+    }
 }
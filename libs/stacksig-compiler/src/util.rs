@@ -1,11 +1,47 @@
 use curve25519_dalek::scalar::Scalar;
 use digest::Digest;
 use sha2::Sha512;
+use sigmazk::error::Error;
+use sigmazk::Challenge;
 
-use crate::stackable::Message;
+pub(crate) use sigmazk::message::{
+    read_framed, write_framed,
+};
+
+use crate::stackable::{Message, ReadMessage};
 
 pub(crate) fn hash<M: Message + ?Sized>(v: &M) -> Scalar {
     let mut hash = Sha512::new();
     v.write(&mut hash);
     Scalar::from_hash(hash)
 }
+
+/// Finalise the hash and map the 64-byte digest to a
+/// challenge (Fiat-Shamir)
+pub(crate) fn challenge_from_hash<C: Challenge>(
+    hash: Sha512,
+) -> C {
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(
+        hash.finalize()
+            .as_slice(),
+    );
+    C::new(&bytes)
+}
+
+/// Read `bytes` as a sequence of `size`-byte messages
+pub(crate) fn read_chunks<T: ReadMessage>(
+    bytes: &[u8],
+    size: usize,
+) -> Result<Vec<T>, Error> {
+    if !bytes
+        .len()
+        .is_multiple_of(size)
+    {
+        return Err(Error::InvalidEncoding);
+    }
+    bytes
+        .chunks(size)
+        .map(T::read)
+        .collect()
+}
@@ -11,9 +11,10 @@ use group::{Group, GroupEncoding};
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use rand_core::CryptoRngCore;
-use subtle::{Choice, CtOption};
+use subtle::{Choice, ConstantTimeEq, CtOption};
 
 use crate::scalar::WrappedScalar;
+use crate::CommonField;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct WrappedRistretto(pub RistrettoPoint);
@@ -31,19 +32,32 @@ impl WrappedRistretto {
 
 impl PrimeGroup for WrappedRistretto {}
 
+/// Lets the compression mechanism of `speed-stacking` use
+/// Ristretto for both of its groups
+impl CommonField<WrappedRistretto> for WrappedRistretto {}
+
+impl ConstantTimeEq for WrappedRistretto {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0
+            .ct_eq(&other.0)
+    }
+}
+
 impl GroupEncoding for WrappedRistretto {
     type Repr = [u8; 32];
 
+    /// `None` if `bytes` is not the encoding of a point
     fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
-        CtOption::new(
-            WrappedRistretto(
-                CompressedRistretto::from_slice(bytes.as_slice())
-                    .unwrap()
-                    .decompress()
-                    .unwrap(),
+        match CompressedRistretto(*bytes).decompress() {
+            Some(point) => CtOption::new(
+                Self(point),
+                Choice::from(1u8),
             ),
-            Choice::from(1u8),
-        )
+            None => CtOption::new(
+                Self::identity(),
+                Choice::from(0u8),
+            ),
+        }
     }
 
     fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
@@ -266,3 +280,24 @@ impl<'b> MulAssign<&'b WrappedScalar> for WrappedRistretto {
         *self = *self * *rhs;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoding_round_trip() {
+        let point = WrappedRistretto::random(
+            &mut ChaCha20Rng::from_seed([0u8; 32]),
+        );
+        let bytes = GroupEncoding::to_bytes(&point);
+        assert_eq!(
+            WrappedRistretto::from_bytes(&bytes).unwrap(),
+            point
+        );
+        assert!(bool::from(
+            WrappedRistretto::from_bytes(&[0xff; 32])
+                .is_none()
+        ));
+    }
+}
@@ -10,8 +10,28 @@ use rand_core::CryptoRngCore;
 use sigmazk::Challenge;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
 
+/// Wrapper around [`Scalar`] implementing the traits of the
+/// `group` crate.
+///
+/// `PartialEq` compares the stored bytes. Every constructor
+/// but [`WrappedScalar::from_bits`] reduces modulo the group
+/// order, but two unreduced scalars that are congruent modulo
+/// the group order compare unequal; use
+/// [`WrappedScalar::eq_reduced`] to compare them as field
+/// elements.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
-pub struct WrappedScalar(pub Scalar);
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct WrappedScalar(
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "sigmazk::serde_helpers::scalar")
+    )]
+    pub Scalar,
+);
 
 impl WrappedScalar {
     pub fn from_bytes_mod_order(bytes: [u8; 32]) -> Self {
@@ -66,6 +86,12 @@ impl WrappedScalar {
         self.0
             .is_canonical()
     }
+
+    /// Whether `self` and `other` are the same scalar modulo
+    /// the group order, whatever their byte representation
+    pub fn eq_reduced(&self, other: &Self) -> bool {
+        self.reduce() == other.reduce()
+    }
 }
 
 impl PrimeGroup for WrappedScalar {}
@@ -451,3 +477,31 @@ impl Challenge for WrappedScalar {
         Self(Scalar::from_bytes_mod_order_wide(bytes))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian encoding of the group order plus one
+    const ORDER_PLUS_ONE: [u8; 32] = [
+        0xee, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58,
+        0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+    ];
+
+    #[test]
+    fn eq_reduced_ignores_non_canonical_encodings() {
+        let one = WrappedScalar::from(1u64);
+        let unreduced =
+            WrappedScalar::from_bits(ORDER_PLUS_ONE);
+        assert_ne!(unreduced, one);
+        assert!(unreduced.eq_reduced(&one));
+        assert_eq!(
+            WrappedScalar::from(ORDER_PLUS_ONE),
+            one
+        );
+        assert!(!unreduced
+            .eq_reduced(&WrappedScalar::from(2u64)));
+    }
+}